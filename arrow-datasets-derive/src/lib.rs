@@ -0,0 +1,58 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+// Function to derive ArrowRecord for a struct with named fields, mapping each field to a column
+// named after it, in declaration order
+#[proc_macro_derive(ArrowRecord)]
+pub fn derive_arrow_record(input: TokenStream) -> TokenStream {
+    // Parse the struct the derive is attached to
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    // Only structs with named fields can be mapped to columns
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return syn::Error::new_spanned(name, "ArrowRecord requires named fields")
+                    .to_compile_error()
+                    .into()
+            }
+        },
+        _ => {
+            return syn::Error::new_spanned(name, "ArrowRecord can only be derived for structs")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    // Build the field definition and the column of each struct field
+    let idents: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+    let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+    let names: Vec<_> = idents
+        .iter()
+        .map(|ident| ident.as_ref().map(ToString::to_string))
+        .collect();
+
+    // Generate the implementation relying on the ArrowField implementation of each field type
+    quote! {
+        impl #impl_generics ::arrow_datasets::record::ArrowRecord for #name #type_generics #where_clause {
+            fn fields() -> ::std::vec::Vec<::arrow_datasets::record::Field> {
+                ::std::vec![
+                    #(::arrow_datasets::record::field::<#types>(#names)),*
+                ]
+            }
+
+            fn columns(records: &[Self]) -> ::std::vec::Vec<::arrow_datasets::record::ArrayRef> {
+                ::std::vec![
+                    #(<#types as ::arrow_datasets::record::ArrowField>::build_column(
+                        records.iter().map(|record| &record.#idents)
+                    )),*
+                ]
+            }
+        }
+    }
+    .into()
+}
@@ -0,0 +1,26 @@
+#ifndef ARROW_DATASETS_H
+#define ARROW_DATASETS_H
+
+/* Generated with cbindgen from src/ffi.rs, regenerate with `cbindgen --output include/arrow_datasets.h` */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define EXIT_INVALID_CONFIG 2
+
+// Convert a dataset described by a NUL-terminated JSON object with the fields of a batch
+// manifest entry, returning the exit status of the `convert` subcommand, or 2 when the
+// config is invalid.
+//
+// # Safety
+//
+// `config_json` must be null or point to a valid NUL-terminated string.
+int ad_convert(const char *config_json);
+
+// Return the message of the last failed `ad_convert` call on the calling thread, or null.
+// The string stays valid until the next `ad_convert` call on the same thread.
+const char *ad_last_error(void);
+
+#endif  /* ARROW_DATASETS_H */
@@ -0,0 +1,363 @@
+use crate::cancel::CancellationToken;
+use crate::chunk_io::ChunkFormat;
+use crate::cli::{
+    self, ConvertArgs, InputFormat, LabelHierarchy, Profile, ProgressFormat,
+    DEFAULT_HUB_COMMIT_MESSAGE,
+};
+use crate::defaults;
+use crate::error::{Error, Result};
+use crate::export::{ExportFormat, JsonlImages};
+use crate::filter::default_extensions;
+use crate::metadata::FileNameTemplate;
+use crate::profile::{Compression, IpcFormat};
+use crate::report::{ConvertSummary, EXIT_CANCELLED, EXIT_FAILURE, EXIT_PARTIAL_FAILURE};
+use crate::run_convert;
+use crate::schema::SchemaSettings;
+use crate::source;
+use log::{error, info};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fs;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Define a struct describing one dataset entry of a batch manifest, also accepted by the C API
+#[derive(Deserialize)]
+pub(crate) struct ManifestEntry {
+    name: String,
+    input: PathBuf,
+    output: PathBuf,
+    hub_token: Option<String>,
+    paths_from: Option<PathBuf>,
+    input_format: Option<InputFormat>,
+    profile: Option<Profile>,
+    format: Option<ChunkFormat>,
+    compression: Option<Compression>,
+    row_group_size: Option<NonZeroUsize>,
+    ipc_format: Option<IpcFormat>,
+    #[serde(default)]
+    exports: Vec<ExportFormat>,
+    #[serde(default)]
+    jsonl_images: JsonlImages,
+    #[serde(default)]
+    export_only: bool,
+    #[serde(default)]
+    manifest_db: bool,
+    #[serde(default)]
+    single_file: bool,
+    #[serde(default)]
+    fsync: bool,
+    #[serde(default)]
+    verify_output: bool,
+    #[serde(default)]
+    dataset_card: bool,
+    #[serde(default)]
+    dataset_dict: bool,
+    push_to_hub: Option<String>,
+    hub_commit_message: Option<String>,
+    #[serde(default)]
+    hub_private: bool,
+    config: Option<PathBuf>,
+    synset_map: Option<PathBuf>,
+    label_names: Option<PathBuf>,
+    label_delimiter: Option<String>,
+    #[serde(default)]
+    multi_hot: bool,
+    video_frames: Option<NonZeroUsize>,
+    #[serde(default)]
+    captions: bool,
+    label_hierarchy: Option<LabelHierarchy>,
+    pair_folders: Option<(String, String)>,
+    hdf5_images: Option<String>,
+    hdf5_labels: Option<String>,
+    #[serde(default)]
+    hdf5_columns: BTreeMap<String, String>,
+    #[serde(default)]
+    splits: BTreeMap<String, PathBuf>,
+    extensions: Option<Vec<String>>,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    chunk_size: Option<NonZeroUsize>,
+    #[serde(default, deserialize_with = "deserialize_byte_size")]
+    target_chunk_bytes: Option<NonZeroU64>,
+    #[serde(default)]
+    file_name_template: FileNameTemplate,
+    threads: Option<NonZeroUsize>,
+    download_concurrency: Option<NonZeroUsize>,
+    download_timeout: Option<u64>,
+    max_samples: Option<NonZeroUsize>,
+    seed: Option<u64>,
+    #[serde(default)]
+    resume: bool,
+    #[serde(default)]
+    force: bool,
+    #[serde(default)]
+    append: bool,
+}
+
+// Function to read a size in bytes from a manifest, written as a number of bytes or as a string
+// with a unit such as "2GB"
+fn deserialize_byte_size<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<NonZeroU64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ByteSize {
+        Bytes(NonZeroU64),
+        Text(String),
+    }
+    match ByteSize::deserialize(deserializer)? {
+        ByteSize::Bytes(bytes) => Ok(Some(bytes)),
+        ByteSize::Text(text) => cli::parse_byte_size(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+// Define a struct describing a whole batch manifest
+#[derive(Deserialize)]
+struct Manifest {
+    datasets: Vec<ManifestEntry>,
+}
+
+// Define a struct to store the outcome of converting one dataset
+struct BatchResult {
+    name: String,
+    outcome: Result<ConvertSummary>,
+    elapsed: Duration,
+}
+
+// Function to read a batch manifest in JSON or TOML format depending on its extension
+fn read_manifest(manifest_path: &Path) -> Result<Manifest> {
+    // Read the whole manifest file into a string
+    let content =
+        fs::read_to_string(manifest_path).map_err(Error::io("Failed to read", manifest_path))?;
+
+    // Parse the content as TOML or JSON according to the file extension
+    match manifest_path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&content).map_err(Error::toml(manifest_path)),
+        _ => serde_json::from_str(&content).map_err(Error::json(manifest_path)),
+    }
+}
+
+// Function to turn a manifest entry into convert arguments, resolving paths against the manifest directory
+pub(crate) fn entry_to_convert_args(entry: &ManifestEntry, base_dir: &Path) -> ConvertArgs {
+    ConvertArgs {
+        input: if source::is_local_input(&entry.input) {
+            base_dir.join(&entry.input)
+        } else {
+            entry.input.clone()
+        },
+        output: if source::remote_url(&entry.output).is_none() {
+            base_dir.join(&entry.output)
+        } else {
+            entry.output.clone()
+        },
+        hub_token: entry.hub_token.clone(),
+        paths_from: entry.paths_from.as_ref().map(|path| base_dir.join(path)),
+        input_format: entry.input_format.unwrap_or(InputFormat::Folder),
+        name: entry.name.clone(),
+        config: entry.config.as_ref().map(|config| base_dir.join(config)),
+        synset_map: entry.synset_map.as_ref().map(|path| base_dir.join(path)),
+        label_names: entry.label_names.as_ref().map(|path| base_dir.join(path)),
+        label_delimiter: entry.label_delimiter.clone(),
+        multi_hot: entry.multi_hot,
+        video_frames: entry.video_frames,
+        captions: entry.captions,
+        label_hierarchy: entry.label_hierarchy,
+        pair_folders: entry.pair_folders.clone(),
+        hdf5_images: entry.hdf5_images.clone(),
+        hdf5_labels: entry.hdf5_labels.clone(),
+        hdf5_columns: entry
+            .hdf5_columns
+            .iter()
+            .map(|(column, dataset)| (column.clone(), dataset.clone()))
+            .collect(),
+        profile: entry.profile.unwrap_or(Profile::Hf),
+        format: entry.format,
+        compression: entry.compression,
+        row_group_size: entry.row_group_size,
+        ipc_format: entry.ipc_format,
+        exports: entry.exports.clone(),
+        jsonl_images: entry.jsonl_images,
+        export_only: entry.export_only,
+        manifest_db: entry.manifest_db,
+        single_file: entry.single_file,
+        fsync: entry.fsync,
+        verify_output: entry.verify_output,
+        dataset_card: entry.dataset_card,
+        dataset_dict: entry.dataset_dict,
+        push_to_hub: entry.push_to_hub.clone(),
+        hub_commit_message: entry
+            .hub_commit_message
+            .clone()
+            .unwrap_or_else(|| DEFAULT_HUB_COMMIT_MESSAGE.to_string()),
+        hub_private: entry.hub_private,
+        splits: entry
+            .splits
+            .iter()
+            .map(|(split, path)| (split.clone(), base_dir.join(path)))
+            .collect(),
+        extensions: entry.extensions.clone().unwrap_or_else(default_extensions),
+        include: entry.include.clone(),
+        exclude: entry.exclude.clone(),
+        chunk_size: entry.chunk_size,
+        target_chunk_bytes: entry.target_chunk_bytes,
+        file_name_template: entry.file_name_template.clone(),
+        threads: entry.threads,
+        download_concurrency: entry
+            .download_concurrency
+            .unwrap_or(defaults::DOWNLOAD_CONCURRENCY),
+        download_timeout: entry
+            .download_timeout
+            .unwrap_or(defaults::DOWNLOAD_TIMEOUT_SECS),
+        max_samples: entry.max_samples,
+        seed: entry.seed,
+        resume: entry.resume,
+        force: entry.force,
+        append: entry.append,
+        watch: false,
+        progress_format: ProgressFormat::None,
+        dry_run: false,
+        manifest_only: false,
+        schema: SchemaSettings::default(),
+    }
+}
+
+// Function to convert every dataset of a manifest, returning the exit code summarizing the batch,
+// or an error when the manifest itself cannot be read
+pub fn run_batch(manifest_path: &Path, parallel: usize, cancel: &CancellationToken) -> Result<i32> {
+    // Read the manifest and resolve the directory its relative paths are based on
+    let manifest = read_manifest(manifest_path)?;
+    let base_dir = manifest_path.parent().unwrap_or(Path::new("."));
+
+    // Build the convert arguments of every dataset
+    let jobs: Vec<ConvertArgs> = manifest
+        .datasets
+        .iter()
+        .map(|entry| entry_to_convert_args(entry, base_dir))
+        .collect();
+
+    // Print status message with the batch details
+    info!(
+        "Converting {} datasets, {} at a time...",
+        jobs.len(),
+        parallel
+    );
+
+    // Convert the datasets in groups of at most `parallel` concurrent conversions
+    let mut results = Vec::new();
+    for group in jobs.chunks(parallel) {
+        // Stop before starting another group once the batch has been cancelled
+        if cancel.is_cancelled() {
+            break;
+        }
+
+        thread::scope(|scope| {
+            // Spawn one thread per dataset of the group
+            let handles: Vec<_> = group
+                .iter()
+                .map(|args| {
+                    scope.spawn(move || {
+                        // Measure how long the conversion takes
+                        let start = Instant::now();
+                        let summary =
+                            run_convert(args, None, Vec::new(), Vec::new(), cancel.clone());
+                        (summary, start.elapsed())
+                    })
+                })
+                .collect();
+
+            // Wait for every conversion of the group and record its outcome
+            for (args, handle) in group.iter().zip(handles) {
+                let result = match handle.join() {
+                    Ok((outcome, elapsed)) => BatchResult {
+                        name: args.name.clone(),
+                        outcome,
+                        elapsed,
+                    },
+                    Err(_) => BatchResult {
+                        name: args.name.clone(),
+                        outcome: Err(Error::WorkerFailed),
+                        elapsed: Duration::ZERO,
+                    },
+                };
+
+                // Log the reason of a failed conversion as soon as it is known
+                if let Err(e) = &result.outcome {
+                    error!("Failed to convert '{}': {}", result.name, e);
+                }
+                results.push(result);
+            }
+        });
+    }
+
+    // Print the summary report of the batch
+    println!("Batch summary:");
+    for result in &results {
+        match &result.outcome {
+            Ok(summary) if summary.cancelled => {
+                println!("  {:<32} CANCELLED", result.name)
+            }
+            Ok(summary) if summary.is_total_failure() => println!(
+                "  {:<32} FAILED  all {} samples failed",
+                result.name, summary.failed_samples
+            ),
+            Ok(summary) if summary.failed_samples > 0 => println!(
+                "  {:<32} PARTIAL {} samples in {:.1}s, {} failed",
+                result.name,
+                summary.samples,
+                result.elapsed.as_secs_f64(),
+                summary.failed_samples
+            ),
+            Ok(summary) => println!(
+                "  {:<32} OK      {} samples in {:.1}s",
+                result.name,
+                summary.samples,
+                result.elapsed.as_secs_f64()
+            ),
+            Err(e) => println!("  {:<32} FAILED  {}", result.name, e),
+        }
+    }
+
+    // Count the failed and partially failed conversions and report them, a conversion whose every
+    // sample failed counting as failed
+    let failed = results
+        .iter()
+        .filter(|r| {
+            r.outcome
+                .as_ref()
+                .map_or(true, ConvertSummary::is_total_failure)
+        })
+        .count();
+    let partial = results
+        .iter()
+        .filter(|r| {
+            r.outcome
+                .as_ref()
+                .is_ok_and(|s| s.failed_samples > 0 && !s.is_total_failure())
+        })
+        .count();
+    println!(
+        "{} succeeded, {} partially failed, {} failed",
+        results.len() - failed - partial,
+        partial,
+        failed
+    );
+
+    // Return the cancellation status when interrupted, and a total failure only when no dataset
+    // could be converted at all
+    Ok(if cancel.is_cancelled() {
+        EXIT_CANCELLED
+    } else if !results.is_empty() && failed == results.len() {
+        EXIT_FAILURE
+    } else if failed > 0 || partial > 0 {
+        EXIT_PARTIAL_FAILURE
+    } else {
+        0
+    })
+}
@@ -0,0 +1,115 @@
+use crate::error::{Error, Result};
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::record_batch::RecordBatch;
+use clap::ValueEnum;
+#[cfg(feature = "parquet")]
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+// Define the on-disk formats of the chunk files
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkFormat {
+    /// Arrow IPC files (.arrow)
+    Arrow,
+    /// Parquet files (.parquet)
+    Parquet,
+}
+
+impl ChunkFormat {
+    // Function to get the file extension of chunks in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            ChunkFormat::Arrow => "arrow",
+            ChunkFormat::Parquet => "parquet",
+        }
+    }
+
+    // Function to detect the format of a chunk from its file extension, defaulting to Arrow
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("parquet") => ChunkFormat::Parquet,
+            _ => ChunkFormat::Arrow,
+        }
+    }
+}
+
+// Define the magic bytes starting Arrow IPC files, which IPC streams do not have
+const IPC_FILE_MAGIC: &[u8] = b"ARROW1";
+
+// Function to check whether an Arrow chunk is an IPC file rather than an IPC stream, leaving the
+// file at its start
+fn is_ipc_file(file: &mut File) -> io::Result<bool> {
+    let mut magic = [0; IPC_FILE_MAGIC.len()];
+    let is_file = file.read_exact(&mut magic).is_ok() && magic == IPC_FILE_MAGIC;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(is_file)
+}
+
+// Function to read the schema of a chunk file
+pub fn read_chunk_schema(path: &Path) -> Result<SchemaRef> {
+    // Open the chunk file
+    let mut file = File::open(path).map_err(Error::io("Failed to open", path))?;
+
+    // Read the schema from the footer of the chunk, or from the start of an IPC stream
+    match ChunkFormat::from_path(path) {
+        ChunkFormat::Arrow
+            if is_ipc_file(&mut file).map_err(Error::io("Failed to read", path))? =>
+        {
+            Ok(FileReader::try_new(file, None)?.schema())
+        }
+        ChunkFormat::Arrow => Ok(StreamReader::try_new(BufReader::new(file), None)?.schema()),
+        #[cfg(feature = "parquet")]
+        ChunkFormat::Parquet => Ok(ParquetRecordBatchReaderBuilder::try_new(file)?
+            .schema()
+            .clone()),
+        #[cfg(not(feature = "parquet"))]
+        ChunkFormat::Parquet => Err(Error::FeatureDisabled("Reading Parquet chunks", "parquet")),
+    }
+}
+
+// Define an iterator decoding the record batches of a chunk file one at a time
+pub type ChunkBatches =
+    Box<dyn Iterator<Item = std::result::Result<RecordBatch, ArrowError>> + Send>;
+
+// Function to open a chunk file and create an iterator over its record batches
+pub fn read_chunk_batches(path: &Path) -> Result<ChunkBatches> {
+    // Open the chunk file
+    let mut file = File::open(path).map_err(Error::io("Failed to open", path))?;
+
+    // Create a reader over the record batches of the chunk, written as an IPC file or stream
+    Ok(match ChunkFormat::from_path(path) {
+        ChunkFormat::Arrow
+            if is_ipc_file(&mut file).map_err(Error::io("Failed to read", path))? =>
+        {
+            Box::new(FileReader::try_new(file, None)?)
+        }
+        ChunkFormat::Arrow => Box::new(StreamReader::try_new(BufReader::new(file), None)?),
+        #[cfg(feature = "parquet")]
+        ChunkFormat::Parquet => Box::new(ParquetRecordBatchReaderBuilder::try_new(file)?.build()?),
+        #[cfg(not(feature = "parquet"))]
+        ChunkFormat::Parquet => {
+            return Err(Error::FeatureDisabled("Reading Parquet chunks", "parquet"))
+        }
+    })
+}
+
+// Function to count the rows of a chunk file by decoding every record batch
+pub fn count_chunk_rows(path: &Path) -> Result<usize> {
+    // Create a reader over the record batches of the chunk
+    let batches = read_chunk_batches(path)?;
+
+    // Sum the number of rows of every record batch in the chunk
+    let mut rows = 0;
+    for batch in batches {
+        rows += batch?.num_rows();
+    }
+
+    // Return the total row count
+    Ok(rows)
+}
@@ -0,0 +1,661 @@
+use crate::chunk_io::ChunkFormat;
+use crate::defaults;
+use crate::export::{ExportFormat, JsonlImages};
+use crate::filter::{parse_glob, DEFAULT_EXTENSIONS};
+use crate::metadata::{FileNameTemplate, DEFAULT_FILE_NAME_TEMPLATE};
+use crate::profile::{Compression, IpcFormat};
+use crate::schema::SchemaSettings;
+use clap::builder::BoolishValueParser;
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::PathBuf;
+
+// Define the message of the commits uploading a dataset to the HuggingFace Hub when none is given
+pub const DEFAULT_HUB_COMMIT_MESSAGE: &str = "Upload dataset with arrow-datasets";
+
+// Define the top-level command line interface
+#[derive(Parser)]
+#[command(
+    name = "arrow-datasets",
+    version,
+    about = "Convert image datasets into chunked Apache Arrow files"
+)]
+pub struct Cli {
+    // Subcommand selected by the user
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Increase the log verbosity, may be repeated (-v for debug, -vv for trace)
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// Decrease the log verbosity, may be repeated (-q for warnings, -qq for errors, -qqq for silence)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub quiet: u8,
+
+    /// Format of the log lines
+    #[arg(long, global = true, env = "ARROW_DATASETS_LOG_FORMAT", value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Write the logs to this file instead of stderr
+    #[arg(long, global = true, env = "ARROW_DATASETS_LOG_FILE")]
+    pub log_file: Option<PathBuf>,
+}
+
+// Define the supported log line formats
+#[derive(Clone, Copy, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines with a timestamp and level
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+// Define the available subcommands
+#[derive(Subcommand)]
+pub enum Command {
+    /// Convert an image folder dataset into chunked Arrow files
+    Convert(Box<ConvertArgs>),
+    /// Print a summary of an existing Arrow dataset
+    Inspect(InspectArgs),
+    /// Check that every chunk of an existing Arrow dataset is readable and complete
+    Verify(VerifyArgs),
+    /// Merge several converted splits into a single split
+    Merge(MergeArgs),
+    /// Rewrite a converted dataset with another chunk size or sample order
+    Reshard(ReshardArgs),
+    /// Convert every dataset listed in a JSON or TOML manifest
+    Batch(BatchArgs),
+    /// Serve the splits of a converted dataset to remote readers over Arrow Flight
+    Serve(ServeArgs),
+}
+
+// Define the supported progress output formats
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ProgressFormat {
+    /// Progress bars when stderr is a terminal, nothing otherwise
+    Auto,
+    /// Interactive progress bars with throughput and ETA on stderr
+    Bar,
+    /// No progress records, only log lines
+    None,
+    /// One JSON object per completed chunk
+    Json,
+}
+
+// Define the named presets of output settings
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    /// Uncompressed Arrow IPC chunks loadable by HuggingFace datasets
+    Hf,
+    /// Snappy-compressed Parquet chunks
+    Parquet,
+    /// Zstd-compressed Arrow IPC chunks
+    Compact,
+}
+
+// Define the layouts the samples of a split can be read from
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFormat {
+    /// Image files in one subfolder per label
+    Folder,
+    /// Tar archives read without extracting them, labelled by the folder of each member or else by the archive name
+    Tar,
+    /// Zip archives read without extracting them, labelled by the folder of each entry or else by the archive name
+    Zip,
+    /// WebDataset tar shards, labelled by the .cls member of each sample or else the label field of its .json member
+    Webdataset,
+    /// CSV or TSV manifests with a path column, relative to the manifest, and a label column
+    Csv,
+    /// JSON Lines manifests with a file and a label key per line, the other keys becoming extra columns
+    Jsonl,
+    /// COCO instances_*.json annotation files, the objects of every image going into box and category list columns
+    Coco,
+    /// Pascal VOC datasets with JPEGImages and Annotations folders, one split per image set of ImageSets/Main
+    Voc,
+    /// Text files with an image URL and an optional label per line, or CSV or TSV files with url and label columns, downloaded during the conversion
+    Urls,
+    /// Parquet files with an image column and an optional label column, such as HuggingFace datasets exports, re-chunked into Arrow IPC files
+    Parquet,
+    /// LMDB databases of encoded images, such as LSUN, or of Caffe Datum messages, labelled by the Datum label or else the database folder
+    Lmdb,
+    /// TFRecord files of tf.Example records with an encoded image feature and an integer or string label feature
+    Tfrecord,
+    /// Text documents (.txt) in one subfolder per label, written to a text column instead of an image column
+    Text,
+    /// WAV, FLAC or MP3 files in one subfolder per label, written to an audio column with the sampling rate read from each header
+    Audio,
+    /// Input and target images in two parallel folders paired by file name, such as super-resolution, segmentation or pix2pix datasets, written to an input and a target column
+    Paired,
+    /// NumPy .npy files and the arrays of .npz archives in one subfolder per label, written as raw data with shape and dtype columns
+    Numpy,
+    /// Video files in one subfolder per label, stored as encoded clips or as frames extracted with --video-frames
+    Video,
+    /// HDF5 files (.h5, .hdf5) holding a stack of images and a label array, named with --hdf5-images and --hdf5-labels, every image written as raw data with shape and dtype columns
+    Hdf5,
+}
+
+// Define how the labels of images nested in several class folders are recorded
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelHierarchy {
+    /// Label every image by the path of its class folders, such as animal/dog/husky
+    Path,
+    /// Keep the immediate folder as the label and add a label_1, label_2, ... column per folder level
+    Columns,
+}
+
+// Define the arguments of the convert subcommand
+#[derive(Args)]
+pub struct ConvertArgs {
+    /// Input dataset directory whose top-level folders are converted as splits, a HuggingFace Hub dataset as hf://org/name, a Kaggle dataset as kaggle://owner/name, or an object storage prefix as s3://bucket/prefix, gs://bucket/prefix or az://container/prefix
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_INPUT",
+        default_value = "D:/datasets/imagenet21k-p"
+    )]
+    pub input: PathBuf,
+
+    /// Output directory for the Arrow dataset, an object storage prefix as s3://bucket/prefix, gs://bucket/prefix or az://container/prefix receiving every file once it is written, or - to write the single converted split to stdout as an Arrow IPC stream
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_OUTPUT",
+        default_value = "D:/datasets/imagenet21k-p-arrow"
+    )]
+    pub output: PathBuf,
+
+    /// Access token for private or gated HuggingFace Hub datasets and for --push-to-hub, defaults to HF_TOKEN or the saved Hub login
+    #[arg(long, env = "ARROW_DATASETS_HUB_TOKEN", hide_env_values = true)]
+    pub hub_token: Option<String>,
+
+    /// Text file listing the images of a folder input to convert, one path per line relative to --input, or - to read the list from the standard input, instead of walking the split folders
+    #[arg(long, env = "ARROW_DATASETS_PATHS_FROM", value_name = "FILE")]
+    pub paths_from: Option<PathBuf>,
+
+    /// Layout of the input: image folders, archives, manifests, or lists of image URLs; top-level archives and manifests are also discovered as splits
+    #[arg(long, env = "ARROW_DATASETS_INPUT_FORMAT", value_enum, default_value_t = InputFormat::Folder)]
+    pub input_format: InputFormat,
+
+    /// Dataset name prefix written into the metadata of each split
+    #[arg(long, env = "ARROW_DATASETS_NAME", default_value = "imagenet21k")]
+    pub name: String,
+
+    /// JSON or TOML file setting the output schema and overriding the settings of individual splits
+    #[arg(long, env = "ARROW_DATASETS_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Text or JSON file mapping WordNet IDs to class names, adding a class_name column for ImageNet-style labels
+    #[arg(long, env = "ARROW_DATASETS_SYNSET_MAP")]
+    pub synset_map: Option<PathBuf>,
+
+    /// Text file naming the integer labels of LMDB and TFRecord inputs, or the classes of --multi-hot, one class name per line in label order
+    #[arg(long, env = "ARROW_DATASETS_LABEL_NAMES")]
+    pub label_names: Option<PathBuf>,
+
+    /// Delimiter separating the labels of multi-label samples, such as "|" in "cat|dog", adding a labels column holding the list of labels of every sample
+    #[arg(long, env = "ARROW_DATASETS_LABEL_DELIMITER")]
+    pub label_delimiter: Option<String>,
+
+    /// Write the labels column as a multi-hot vector over the classes listed by --label-names instead of a list
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_MULTI_HOT",
+        value_parser = BoolishValueParser::new(),
+        requires_all = ["label_delimiter", "label_names"]
+    )]
+    pub multi_hot: bool,
+
+    /// Number of evenly spaced frames extracted from every video of a video input with ffmpeg into a list column of JPEG images, instead of storing the encoded clips
+    #[arg(long, env = "ARROW_DATASETS_VIDEO_FRAMES")]
+    pub video_frames: Option<NonZeroUsize>,
+
+    /// Read the caption of every image of a folder input from the .txt file of the same name next to it into a caption column
+    #[arg(long, env = "ARROW_DATASETS_CAPTIONS", value_parser = BoolishValueParser::new())]
+    pub captions: bool,
+
+    /// Record the nested class folders of a folder input, as a label path or as one column per level, instead of only the immediate folder
+    #[arg(long, env = "ARROW_DATASETS_LABEL_HIERARCHY", value_enum)]
+    pub label_hierarchy: Option<LabelHierarchy>,
+
+    /// Folders of a paired input holding the input and target images, matched by their path without extension; defaults to input,target
+    #[arg(long, env = "ARROW_DATASETS_PAIR_FOLDERS", value_name = "INPUT,TARGET", value_parser = parse_pair_folders)]
+    pub pair_folders: Option<(String, String)>,
+
+    /// Dataset of an HDF5 input holding the stack of images, one image per index of its first axis; defaults to images
+    #[arg(long, env = "ARROW_DATASETS_HDF5_IMAGES", value_name = "DATASET")]
+    pub hdf5_images: Option<String>,
+
+    /// Dataset of an HDF5 input holding the integer or string label of every image; defaults to labels when the files have one
+    #[arg(long, env = "ARROW_DATASETS_HDF5_LABELS", value_name = "DATASET")]
+    pub hdf5_labels: Option<String>,
+
+    /// Other datasets of an HDF5 input with one value per image written to extra columns, as COLUMN=DATASET, may be repeated or comma-separated
+    #[arg(
+        long = "hdf5-column",
+        env = "ARROW_DATASETS_HDF5_COLUMNS",
+        value_name = "COLUMN=DATASET",
+        value_delimiter = ',',
+        value_parser = parse_hdf5_column
+    )]
+    pub hdf5_columns: Vec<(String, String)>,
+
+    /// Preset of output settings: file format and compression of the chunks
+    #[arg(long, env = "ARROW_DATASETS_PROFILE", value_enum, default_value_t = Profile::Hf)]
+    pub profile: Profile,
+
+    /// File format of the chunks, replacing the one of the profile
+    #[arg(long, env = "ARROW_DATASETS_FORMAT", value_enum)]
+    pub format: Option<ChunkFormat>,
+
+    /// Compression codec of the chunks, replacing the one of the profile; snappy only applies to Parquet chunks
+    #[arg(long, env = "ARROW_DATASETS_COMPRESSION", value_enum)]
+    pub compression: Option<Compression>,
+
+    /// Maximum number of rows per row group of Parquet chunks, defaults to the Parquet writer default of 1048576
+    #[arg(long, env = "ARROW_DATASETS_ROW_GROUP_SIZE")]
+    pub row_group_size: Option<NonZeroUsize>,
+
+    /// Layout of Arrow IPC chunks: the file format, or the streaming format of the cache files of HuggingFace datasets
+    #[arg(long, env = "ARROW_DATASETS_IPC_FORMAT", value_enum)]
+    pub ipc_format: Option<IpcFormat>,
+
+    /// Also export the samples of every chunk in another format, may be repeated or comma-separated
+    #[arg(
+        long = "export",
+        env = "ARROW_DATASETS_EXPORTS",
+        value_name = "FORMAT",
+        value_enum,
+        value_delimiter = ','
+    )]
+    pub exports: Vec<ExportFormat>,
+
+    /// How --export jsonl stores the images, inlined as base64 or written to an images folder and referenced by path
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_JSONL_IMAGES",
+        value_enum,
+        default_value_t = JsonlImages::Base64
+    )]
+    pub jsonl_images: JsonlImages,
+
+    /// Only write the exported files of --export, without the chunk files and their metadata
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_EXPORT_ONLY",
+        value_parser = BoolishValueParser::new(),
+        requires = "exports",
+        conflicts_with_all = ["append", "resume", "watch", "manifest_only"]
+    )]
+    pub export_only: bool,
+
+    /// Also write manifest.db, an SQLite database listing every sample of a split with its chunk file, row, label, size and SHA-256 digest
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_MANIFEST_DB",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["append", "resume", "watch", "export_only"]
+    )]
+    pub manifest_db: bool,
+
+    /// Write every split as a single file named after it, such as train.arrow, holding one record batch per chunk with the dataset info embedded in its schema metadata, instead of a directory of chunks with JSON metadata
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_SINGLE_FILE",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["append", "resume", "watch", "exports", "export_only", "manifest_db"]
+    )]
+    pub single_file: bool,
+
+    /// Flush every chunk file and the directory entry naming it to disk before reporting the chunk saved, and the metadata files of every split once written, so a node failure on a network filesystem loses no finished chunk
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_FSYNC",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub fsync: bool,
+
+    /// Re-open every chunk of a split once its metadata is written, failing the conversion unless each one decodes with the Arrow reader of its layout, has the columns of the output schema and holds the rows recorded in index.json
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_VERIFY_OUTPUT",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with = "export_only"
+    )]
+    pub verify_output: bool,
+
+    /// Write a dataset card, README.md, at the root of the output once every split is converted, its YAML front matter listing the configs, splits, size and license read by the HuggingFace Hub
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_DATASET_CARD",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["single_file", "export_only"]
+    )]
+    pub dataset_card: bool,
+
+    /// Write dataset_dict.json listing the splits at the root of the output, with the chunks as Arrow IPC streams, so datasets.load_from_disk loads the output as a DatasetDict
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_DATASET_DICT",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["single_file", "export_only"]
+    )]
+    pub dataset_dict: bool,
+
+    /// HuggingFace Hub dataset repository, as org/name, to upload the output to once the conversion succeeds, created when missing
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_PUSH_TO_HUB",
+        value_name = "REPO",
+        conflicts_with_all = ["dry_run", "watch"]
+    )]
+    pub push_to_hub: Option<String>,
+
+    /// Message of the commits uploading the output to the HuggingFace Hub
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_HUB_COMMIT_MESSAGE",
+        value_name = "MESSAGE",
+        default_value = DEFAULT_HUB_COMMIT_MESSAGE,
+        requires = "push_to_hub"
+    )]
+    pub hub_commit_message: String,
+
+    /// Create the HuggingFace Hub repository of --push-to-hub as private
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_HUB_PRIVATE",
+        value_parser = BoolishValueParser::new(),
+        requires = "push_to_hub"
+    )]
+    pub hub_private: bool,
+
+    /// Split to convert as NAME=PATH, may be repeated or comma-separated; defaults to every top-level input folder
+    #[arg(
+        long = "split",
+        env = "ARROW_DATASETS_SPLITS",
+        value_name = "NAME=PATH",
+        value_delimiter = ',',
+        value_parser = parse_split
+    )]
+    pub splits: Vec<(String, PathBuf)>,
+
+    /// Image file extensions to convert, matched case-insensitively
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_EXTENSIONS",
+        value_name = "EXT",
+        value_delimiter = ',',
+        default_values = DEFAULT_EXTENSIONS
+    )]
+    pub extensions: Vec<String>,
+
+    /// Only convert files whose path relative to the split folder matches one of these globs
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_INCLUDE",
+        value_name = "GLOB",
+        value_delimiter = ',',
+        value_parser = parse_glob
+    )]
+    pub include: Vec<String>,
+
+    /// Skip files whose path relative to the split folder matches one of these globs
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_EXCLUDE",
+        value_name = "GLOB",
+        value_delimiter = ',',
+        value_parser = parse_glob
+    )]
+    pub exclude: Vec<String>,
+
+    /// Number of samples per Arrow file, defaults to a value derived from the available memory
+    #[arg(long, env = "ARROW_DATASETS_CHUNK_SIZE")]
+    pub chunk_size: Option<NonZeroUsize>,
+
+    /// Target size of every Arrow file, such as 2GB or 512MiB, filling the chunks with samples up to this size estimated from the input files; a chunk ends at the chunk size or at the target size, whichever is reached first
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_TARGET_CHUNK_BYTES",
+        value_name = "SIZE",
+        value_parser = parse_byte_size
+    )]
+    pub target_chunk_bytes: Option<NonZeroU64>,
+
+    /// Template of the chunk file names, made of the {split}, {index}, {num_chunks} and {ext} placeholders where {index:05} pads the index with zeros to 5 digits, such as "{split}-{index:05}-of-{num_chunks:05}.{ext}"
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_FILE_NAME_TEMPLATE",
+        value_name = "TEMPLATE",
+        value_parser = FileNameTemplate::parse,
+        default_value = DEFAULT_FILE_NAME_TEMPLATE
+    )]
+    pub file_name_template: FileNameTemplate,
+
+    /// Maximum number of chunks processed in parallel, defaults to the number of available cores
+    #[arg(long, env = "ARROW_DATASETS_THREADS")]
+    pub threads: Option<NonZeroUsize>,
+
+    /// Maximum number of images downloaded at the same time from URL lists
+    #[arg(long, env = "ARROW_DATASETS_DOWNLOAD_CONCURRENCY", default_value_t = defaults::DOWNLOAD_CONCURRENCY)]
+    pub download_concurrency: NonZeroUsize,
+
+    /// Seconds allowed to download each image of a URL list before it is reported as failed
+    #[arg(long, env = "ARROW_DATASETS_DOWNLOAD_TIMEOUT", default_value_t = defaults::DOWNLOAD_TIMEOUT_SECS)]
+    pub download_timeout: u64,
+
+    /// Keep at most this many samples per split, picked after shuffling
+    #[arg(long, env = "ARROW_DATASETS_MAX_SAMPLES")]
+    pub max_samples: Option<NonZeroUsize>,
+
+    /// Seed of the shuffle, making the sample order reproducible; random when omitted
+    #[arg(long, env = "ARROW_DATASETS_SEED")]
+    pub seed: Option<u64>,
+
+    /// Skip chunks already written completely by an interrupted conversion into the same output
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_RESUME",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with = "append"
+    )]
+    pub resume: bool,
+
+    /// Overwrite the chunks and metadata of an earlier conversion in a non-empty output directory
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_FORCE",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with = "append"
+    )]
+    pub force: bool,
+
+    /// Add new chunks after those of an earlier conversion in a non-empty output directory
+    #[arg(long, env = "ARROW_DATASETS_APPEND", value_parser = BoolishValueParser::new())]
+    pub append: bool,
+
+    /// Keep running after the conversion and append new images as extra chunks as they appear
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_WATCH",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["dry_run", "max_samples", "paths_from"]
+    )]
+    pub watch: bool,
+
+    /// How to report progress: bars on stderr, or one JSON record per chunk on stdout
+    #[arg(long, env = "ARROW_DATASETS_PROGRESS_FORMAT", value_enum, default_value_t = ProgressFormat::Auto)]
+    pub progress_format: ProgressFormat,
+
+    /// Report the planned samples, chunks and output size per split without writing anything
+    #[arg(long, env = "ARROW_DATASETS_DRY_RUN", value_parser = BoolishValueParser::new())]
+    pub dry_run: bool,
+
+    /// Only scan the input, writing a CSV manifest of the paths, labels and sizes of every split and a summary of the class counts without reading any sample
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_MANIFEST_ONLY",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["dry_run", "watch", "append", "resume"]
+    )]
+    pub manifest_only: bool,
+
+    // Columns of the chunk files set through the library builder, replaced by the [schema] section
+    // of the config file when it has one
+    #[arg(skip)]
+    pub schema: SchemaSettings,
+}
+
+// Function to parse a split declaration of the form NAME=PATH
+fn parse_split(value: &str) -> Result<(String, PathBuf), String> {
+    // Split the declaration at the first equals sign
+    let (name, path) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=PATH, got '{}'", value))?;
+
+    // Reject empty split names and paths
+    if name.is_empty() || path.is_empty() {
+        return Err(format!("expected NAME=PATH, got '{}'", value));
+    }
+
+    // Return the split name and its path
+    Ok((name.to_string(), PathBuf::from(path)))
+}
+
+// Function to parse a size in bytes such as 2GB, 512MiB or 1048576, with decimal and binary units
+pub(crate) fn parse_byte_size(value: &str) -> Result<NonZeroU64, String> {
+    // Split the number from its unit
+    let invalid = || format!("expected a size such as 2GB or 512MiB, got '{}'", value);
+    let trimmed = value.trim();
+    let unit_start = trimmed
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(unit_start);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+
+    // Scale the number by the unit, powers of 1000 for KB to TB and of 1024 for KiB to TiB
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "t" | "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return Err(invalid()),
+    };
+    NonZeroU64::new((number * multiplier as f64) as u64)
+        .ok_or_else(|| format!("size must be greater than zero, got '{}'", value))
+}
+
+// Function to parse an extra column of an HDF5 input of the form COLUMN=DATASET
+fn parse_hdf5_column(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((column, dataset)) if !column.is_empty() && !dataset.is_empty() => {
+            Ok((column.to_string(), dataset.to_string()))
+        }
+        _ => Err(format!("expected COLUMN=DATASET, got '{}'", value)),
+    }
+}
+
+// Function to parse the folders of a paired input of the form INPUT,TARGET
+fn parse_pair_folders(value: &str) -> Result<(String, String), String> {
+    match value.split_once(',') {
+        Some((input, target)) if !input.is_empty() && !target.is_empty() && input != target => {
+            Ok((input.to_string(), target.to_string()))
+        }
+        _ => Err(format!(
+            "expected two different folders as INPUT,TARGET, got '{}'",
+            value
+        )),
+    }
+}
+
+// Define the arguments of the inspect subcommand
+#[derive(Args)]
+pub struct InspectArgs {
+    /// Converted dataset directory or a single split directory
+    pub path: PathBuf,
+}
+
+// Define the arguments of the verify subcommand
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Converted dataset directory or a single split directory
+    pub path: PathBuf,
+}
+
+// Define the arguments of the merge subcommand
+#[derive(Args)]
+pub struct MergeArgs {
+    /// Split directories to merge, in order
+    #[arg(required = true, num_args = 2..)]
+    pub inputs: Vec<PathBuf>,
+
+    /// Output split directory for the merged chunks
+    #[arg(long, env = "ARROW_DATASETS_OUTPUT")]
+    pub output: PathBuf,
+
+    /// Dataset name for the merged split, defaults to the name of the first input
+    #[arg(long, env = "ARROW_DATASETS_NAME")]
+    pub name: Option<String>,
+}
+
+// Define the arguments of the reshard subcommand
+#[derive(Args)]
+pub struct ReshardArgs {
+    /// Converted dataset directory or a single split directory
+    pub path: PathBuf,
+
+    /// Output directory for the rewritten dataset, or split when a single split is given
+    #[arg(long, env = "ARROW_DATASETS_OUTPUT")]
+    pub output: PathBuf,
+
+    /// Number of samples per chunk, defaults to the chunk size of the input
+    #[arg(long, env = "ARROW_DATASETS_CHUNK_SIZE")]
+    pub chunk_size: Option<NonZeroUsize>,
+
+    /// Shuffle the samples again instead of keeping their order
+    #[arg(long, env = "ARROW_DATASETS_SHUFFLE", value_parser = BoolishValueParser::new())]
+    pub shuffle: bool,
+
+    /// Seed of the new shuffle, implying --shuffle; random when omitted
+    #[arg(long, env = "ARROW_DATASETS_SEED")]
+    pub seed: Option<u64>,
+
+    /// Preset of output settings, defaults to keeping the chunk format of the input
+    #[arg(long, env = "ARROW_DATASETS_PROFILE", value_enum)]
+    pub profile: Option<Profile>,
+}
+
+// Define the arguments of the batch subcommand
+#[derive(Args)]
+pub struct BatchArgs {
+    /// Manifest file listing the datasets to convert (.json or .toml)
+    pub manifest: PathBuf,
+
+    /// Number of datasets converted at the same time
+    #[arg(long, env = "ARROW_DATASETS_PARALLEL", default_value = "1")]
+    pub parallel: NonZeroUsize,
+}
+
+// Define the arguments of the serve subcommand
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Converted dataset directory or a single split directory
+    pub path: PathBuf,
+
+    /// Address the Flight server listens on, use 0.0.0.0:PORT to accept remote connections
+    #[arg(
+        long,
+        env = "ARROW_DATASETS_ADDRESS",
+        default_value = "127.0.0.1:50051"
+    )]
+    pub address: SocketAddr,
+}
@@ -0,0 +1,105 @@
+use crate::record::{field, ArrowField};
+use crate::source::Record;
+use arrow::array::ArrayRef;
+use arrow::datatypes::Field;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Arc;
+
+// Define a loaded sample handed to the computed columns, after the transforms ran on it
+pub struct ComputedSample<'a> {
+    pub path: &'a Path,
+    pub record: &'a Record,
+}
+
+// Define the interface of a column derived from the samples while their chunk is processed, so
+// basic attributes are stored without reading the images again later
+pub trait ComputedColumn: Send + Sync {
+    // Function to get the field of the column in the output schema
+    fn field(&self) -> Field;
+
+    // Function to compute the column for the samples of a chunk, in order
+    fn compute(&self, samples: &[ComputedSample<'_>]) -> ArrayRef;
+}
+
+// Define a computed column holding the value returned by a closure for every sample
+pub struct PerSample<T, F> {
+    name: String,
+    compute: F,
+    value_type: PhantomData<fn() -> T>,
+}
+
+impl<T, F> ComputedColumn for PerSample<T, F>
+where
+    T: ArrowField,
+    F: Fn(&ComputedSample<'_>) -> T + Send + Sync,
+{
+    fn field(&self) -> Field {
+        field::<T>(&self.name)
+    }
+
+    fn compute(&self, samples: &[ComputedSample<'_>]) -> ArrayRef {
+        let values: Vec<T> = samples.iter().map(&self.compute).collect();
+        T::build_column(values.iter())
+    }
+}
+
+// Function to define a computed column from a closure called on every sample, whose return type
+// sets the type of the column like a field of #[derive(ArrowRecord)]
+pub fn per_sample<T, F>(name: impl Into<String>, compute: F) -> PerSample<T, F>
+where
+    T: ArrowField,
+    F: Fn(&ComputedSample<'_>) -> T + Send + Sync,
+{
+    PerSample {
+        name: name.into(),
+        compute,
+        value_type: PhantomData,
+    }
+}
+
+// Function to define the "file_path" column holding the path each sample was loaded from
+pub fn file_path() -> impl ComputedColumn {
+    per_sample("file_path", |sample: &ComputedSample<'_>| {
+        sample.path.to_string_lossy().to_string()
+    })
+}
+
+// Function to define the "byte_size" column holding the size of the stored image bytes
+pub fn byte_size() -> impl ComputedColumn {
+    per_sample("byte_size", |sample: &ComputedSample<'_>| {
+        sample.record.bytes.len() as u64
+    })
+}
+
+// Function to define the "sha256" column holding the hex digest of the stored image bytes
+pub fn sha256() -> impl ComputedColumn {
+    per_sample("sha256", |sample: &ComputedSample<'_>| {
+        Sha256::digest(&sample.record.bytes)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>()
+    })
+}
+
+// Define the computed columns that can be requested by name in the [schema] section of a config
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinColumn {
+    FilePath,
+    ByteSize,
+    Sha256,
+}
+
+impl BuiltinColumn {
+    // Function to create the computed column of this name
+    pub fn column(self) -> Arc<dyn ComputedColumn> {
+        match self {
+            BuiltinColumn::FilePath => Arc::new(file_path()),
+            BuiltinColumn::ByteSize => Arc::new(byte_size()),
+            BuiltinColumn::Sha256 => Arc::new(sha256()),
+        }
+    }
+}
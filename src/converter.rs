@@ -0,0 +1,610 @@
+use crate::cancel::CancellationToken;
+use crate::chunk_io::ChunkFormat;
+use crate::cli::{
+    ConvertArgs, InputFormat, LabelHierarchy, Profile, ProgressFormat, DEFAULT_HUB_COMMIT_MESSAGE,
+};
+use crate::computed::ComputedColumn;
+use crate::defaults;
+use crate::error::{Error, Result};
+use crate::export::{ExportFormat, JsonlImages};
+use crate::filter::default_extensions;
+use crate::metadata::FileNameTemplate;
+use crate::profile::{Compression, IpcFormat};
+use crate::progress::ProgressCallback;
+use crate::report::ConvertSummary;
+use crate::run_convert;
+use crate::schema::{ColumnType, SchemaSettings};
+use crate::source::Record;
+use crate::transform::{Keep, Transform};
+use std::mem;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// Define a guard cancelling a conversion when the future awaiting it is dropped before it completes
+#[cfg(feature = "async")]
+struct CancelOnDrop(Option<CancellationToken>);
+
+#[cfg(feature = "async")]
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.0.take() {
+            cancel.cancel();
+        }
+    }
+}
+
+// Define a builder configuring and running the conversion of an image folder dataset
+pub struct DatasetConverter {
+    args: ConvertArgs,
+    callback: Option<Arc<dyn ProgressCallback>>,
+    transforms: Vec<Arc<dyn Transform>>,
+    computed: Vec<Arc<dyn ComputedColumn>>,
+    cancel: CancellationToken,
+    invalid_setting: Option<&'static str>,
+}
+
+impl DatasetConverter {
+    // Function to start configuring the conversion of the given input directory, writing next to
+    // it in "<input>-arrow" and naming the dataset after the input directory by default
+    pub fn new(input: impl Into<PathBuf>) -> Self {
+        // Resolve the input path and derive the default output path and name from it
+        let input = input.into();
+        let mut output = input.clone().into_os_string();
+        output.push("-arrow");
+        let name = input.file_name().map_or("dataset".to_string(), |name| {
+            name.to_string_lossy().to_string()
+        });
+
+        // Return a converter with the same defaults as the convert subcommand
+        DatasetConverter {
+            args: ConvertArgs {
+                input,
+                output: output.into(),
+                hub_token: None,
+                paths_from: None,
+                input_format: InputFormat::Folder,
+                name,
+                config: None,
+                synset_map: None,
+                label_names: None,
+                label_delimiter: None,
+                multi_hot: false,
+                video_frames: None,
+                captions: false,
+                label_hierarchy: None,
+                pair_folders: None,
+                hdf5_images: None,
+                hdf5_labels: None,
+                hdf5_columns: Vec::new(),
+                profile: Profile::Hf,
+                format: None,
+                compression: None,
+                row_group_size: None,
+                ipc_format: None,
+                exports: Vec::new(),
+                jsonl_images: JsonlImages::Base64,
+                export_only: false,
+                manifest_db: false,
+                single_file: false,
+                fsync: false,
+                verify_output: false,
+                dataset_card: false,
+                dataset_dict: false,
+                push_to_hub: None,
+                hub_commit_message: DEFAULT_HUB_COMMIT_MESSAGE.to_string(),
+                hub_private: false,
+                splits: Vec::new(),
+                extensions: default_extensions(),
+                include: Vec::new(),
+                exclude: Vec::new(),
+                chunk_size: None,
+                target_chunk_bytes: None,
+                file_name_template: FileNameTemplate::default(),
+                threads: None,
+                download_concurrency: defaults::DOWNLOAD_CONCURRENCY,
+                download_timeout: defaults::DOWNLOAD_TIMEOUT_SECS,
+                max_samples: None,
+                seed: None,
+                resume: false,
+                force: false,
+                append: false,
+                watch: false,
+                progress_format: ProgressFormat::None,
+                dry_run: false,
+                manifest_only: false,
+                schema: SchemaSettings::default(),
+            },
+            callback: None,
+            transforms: Vec::new(),
+            computed: Vec::new(),
+            cancel: CancellationToken::new(),
+            invalid_setting: None,
+        }
+    }
+
+    // Function to create a converter from parsed convert arguments
+    pub fn from_args(args: ConvertArgs) -> Self {
+        DatasetConverter {
+            args,
+            callback: None,
+            transforms: Vec::new(),
+            computed: Vec::new(),
+            cancel: CancellationToken::new(),
+            invalid_setting: None,
+        }
+    }
+
+    // Function to turn a count given to the builder into a non-zero value, remembering the setting
+    // so running the converter fails when it is zero
+    fn non_zero(&mut self, value: usize, setting: &'static str) -> Option<NonZeroUsize> {
+        let value = NonZeroUsize::new(value);
+        if value.is_none() {
+            self.invalid_setting.get_or_insert(setting);
+        }
+        value
+    }
+
+    // Function to set the output directory
+    pub fn output(mut self, output: impl Into<PathBuf>) -> Self {
+        self.args.output = output.into();
+        self
+    }
+
+    // Function to select the layout the samples of every split are read from
+    pub fn input_format(mut self, input_format: InputFormat) -> Self {
+        self.args.input_format = input_format;
+        self
+    }
+
+    // Function to convert the images listed in a text file, one path per line relative to the
+    // input, instead of walking the split folders
+    pub fn paths_from(mut self, path: impl Into<PathBuf>) -> Self {
+        self.args.paths_from = Some(path.into());
+        self
+    }
+
+    // Function to set the dataset name prefix written into the metadata of each split
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.args.name = name.into();
+        self
+    }
+
+    // Function to set the number of samples per chunk file
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.args.chunk_size = self.non_zero(chunk_size, "Chunk size");
+        self
+    }
+
+    // Function to fill every chunk file with samples up to the given estimated size in bytes, ending
+    // a chunk earlier when it reaches the chunk size
+    pub fn target_chunk_bytes(mut self, target_chunk_bytes: u64) -> Self {
+        self.args.target_chunk_bytes = NonZeroU64::new(target_chunk_bytes);
+        if self.args.target_chunk_bytes.is_none() {
+            self.invalid_setting.get_or_insert("Target chunk size");
+        }
+        self
+    }
+
+    // Function to name the chunk files after the given template instead of
+    // "data-00000-of-00042.arrow"
+    pub fn file_name_template(mut self, template: FileNameTemplate) -> Self {
+        self.args.file_name_template = template;
+        self
+    }
+
+    // Function to set the number of chunks processed in parallel
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.args.threads = self.non_zero(threads, "Thread count");
+        self
+    }
+
+    // Function to set how many images of URL lists are downloaded at the same time
+    pub fn download_concurrency(mut self, concurrency: usize) -> Self {
+        if let Some(concurrency) = self.non_zero(concurrency, "Download concurrency") {
+            self.args.download_concurrency = concurrency;
+        }
+        self
+    }
+
+    // Function to set how many seconds the download of an image of a URL list may take
+    pub fn download_timeout(mut self, seconds: u64) -> Self {
+        self.args.download_timeout = seconds;
+        self
+    }
+
+    // Function to shuffle every split reproducibly with the given seed
+    pub fn shuffle(mut self, seed: u64) -> Self {
+        self.args.seed = Some(seed);
+        self
+    }
+
+    // Function to declare a split explicitly instead of discovering the top-level input folders
+    pub fn split(mut self, name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        self.args.splits.push((name.into(), path.into()));
+        self
+    }
+
+    // Function to replace the image file extensions to convert, matched case-insensitively
+    pub fn extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    // Function to only convert files matching the given glob pattern
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.args.include.push(pattern.into());
+        self
+    }
+
+    // Function to skip files matching the given glob pattern
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.args.exclude.push(pattern.into());
+        self
+    }
+
+    // Function to keep at most the given number of samples per split, picked after shuffling
+    pub fn max_samples(mut self, max_samples: usize) -> Self {
+        self.args.max_samples = self.non_zero(max_samples, "Maximum sample count");
+        self
+    }
+
+    // Function to select the preset of output settings
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.args.profile = profile;
+        self
+    }
+
+    // Function to write the chunks in the given file format instead of the one of the profile
+    pub fn format(mut self, format: ChunkFormat) -> Self {
+        self.args.format = Some(format);
+        self
+    }
+
+    // Function to compress the chunks with the given codec instead of the one of the profile
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.args.compression = Some(compression);
+        self
+    }
+
+    // Function to cap the number of rows per row group of Parquet chunks
+    pub fn row_group_size(mut self, row_group_size: usize) -> Self {
+        self.args.row_group_size = self.non_zero(row_group_size, "Row group size");
+        self
+    }
+
+    // Function to write Arrow IPC chunks in the file or the streaming format
+    pub fn ipc_format(mut self, ipc_format: IpcFormat) -> Self {
+        self.args.ipc_format = Some(ipc_format);
+        self
+    }
+
+    // Function to also export the samples of every chunk in the given format
+    pub fn export(mut self, format: ExportFormat) -> Self {
+        self.args.exports.push(format);
+        self
+    }
+
+    // Function to set how the JSONL export stores the images
+    pub fn jsonl_images(mut self, jsonl_images: JsonlImages) -> Self {
+        self.args.jsonl_images = jsonl_images;
+        self
+    }
+
+    // Function to only write the exported files, without the chunk files and their metadata
+    pub fn export_only(mut self, export_only: bool) -> Self {
+        self.args.export_only = export_only;
+        self
+    }
+
+    // Function to also write the SQLite database listing the samples of every split
+    pub fn manifest_db(mut self, manifest_db: bool) -> Self {
+        self.args.manifest_db = manifest_db;
+        self
+    }
+
+    // Function to write every split as a single file instead of a directory of chunks
+    pub fn single_file(mut self, single_file: bool) -> Self {
+        self.args.single_file = single_file;
+        self
+    }
+
+    // Function to flush the chunk and metadata files to disk as soon as they are written
+    pub fn fsync(mut self, fsync: bool) -> Self {
+        self.args.fsync = fsync;
+        self
+    }
+
+    // Function to re-open and check every chunk of a split once its metadata is written
+    pub fn verify_output(mut self, verify_output: bool) -> Self {
+        self.args.verify_output = verify_output;
+        self
+    }
+
+    // Function to write a dataset card for the HuggingFace Hub once every split is converted
+    pub fn dataset_card(mut self, dataset_card: bool) -> Self {
+        self.args.dataset_card = dataset_card;
+        self
+    }
+
+    // Function to write dataset_dict.json so datasets.load_from_disk loads the output
+    pub fn dataset_dict(mut self, dataset_dict: bool) -> Self {
+        self.args.dataset_dict = dataset_dict;
+        self
+    }
+
+    // Function to upload the output to the given HuggingFace Hub dataset repository once converted
+    pub fn push_to_hub(mut self, repo_id: impl Into<String>) -> Self {
+        self.args.push_to_hub = Some(repo_id.into());
+        self
+    }
+
+    // Function to set the message of the commits uploading the output to the HuggingFace Hub
+    pub fn hub_commit_message(mut self, message: impl Into<String>) -> Self {
+        self.args.hub_commit_message = message.into();
+        self
+    }
+
+    // Function to create the HuggingFace Hub repository as private when it does not exist
+    pub fn hub_private(mut self, private: bool) -> Self {
+        self.args.hub_private = private;
+        self
+    }
+
+    // Function to read the output schema and per-split overrides from a JSON or TOML config file
+    pub fn config(mut self, config: impl Into<PathBuf>) -> Self {
+        self.args.config = Some(config.into());
+        self
+    }
+
+    // Function to set the access token used to download private or gated Hub datasets
+    pub fn hub_token(mut self, token: impl Into<String>) -> Self {
+        self.args.hub_token = Some(token.into());
+        self
+    }
+
+    // Function to add a class_name column holding the name mapped from the WordNet ID label of every
+    // sample by the given synset mapping file
+    pub fn synset_map(mut self, synset_map: impl Into<PathBuf>) -> Self {
+        self.args.synset_map = Some(synset_map.into());
+        self
+    }
+
+    // Function to name the integer labels of LMDB and TFRecord inputs from a text file with one class name per
+    // line in label order
+    pub fn label_names(mut self, label_names: impl Into<PathBuf>) -> Self {
+        self.args.label_names = Some(label_names.into());
+        self
+    }
+
+    // Function to split the label of every sample at the delimiter into a labels column holding the
+    // list of labels
+    pub fn label_delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.args.label_delimiter = Some(delimiter.into());
+        self
+    }
+
+    // Function to write the labels column as a multi-hot vector over the classes named by the label
+    // names file instead of a list
+    pub fn multi_hot(mut self, multi_hot: bool) -> Self {
+        self.args.multi_hot = multi_hot;
+        self
+    }
+
+    // Function to extract the given number of evenly spaced frames from every video of a video input
+    // instead of storing the encoded clips
+    pub fn video_frames(mut self, video_frames: usize) -> Self {
+        self.args.video_frames = self.non_zero(video_frames, "Video frame count");
+        self
+    }
+
+    // Function to read the caption of every image of a folder input from the .txt file of the same
+    // name next to it into a caption column
+    pub fn captions(mut self, captions: bool) -> Self {
+        self.args.captions = captions;
+        self
+    }
+
+    // Function to record the nested class folders of a folder input as a label path or as one
+    // column per level
+    pub fn label_hierarchy(mut self, label_hierarchy: LabelHierarchy) -> Self {
+        self.args.label_hierarchy = Some(label_hierarchy);
+        self
+    }
+
+    // Function to name the folders of a paired input holding the input and target images
+    pub fn pair_folders(mut self, input: impl Into<String>, target: impl Into<String>) -> Self {
+        self.args.pair_folders = Some((input.into(), target.into()));
+        self
+    }
+
+    // Function to name the datasets of an HDF5 input holding the stack of images and the labels
+    pub fn hdf5_datasets(mut self, images: impl Into<String>, labels: impl Into<String>) -> Self {
+        self.args.hdf5_images = Some(images.into());
+        self.args.hdf5_labels = Some(labels.into());
+        self
+    }
+
+    // Function to write another dataset of an HDF5 input with one value per image to an extra column
+    pub fn hdf5_column(mut self, column: impl Into<String>, dataset: impl Into<String>) -> Self {
+        self.args.hdf5_columns.push((column.into(), dataset.into()));
+        self
+    }
+
+    // Function to rename the column holding the image bytes
+    pub fn image_column(mut self, name: impl Into<String>) -> Self {
+        self.args.schema.image_column = Some(name.into());
+        self
+    }
+
+    // Function to rename the column holding the labels
+    pub fn label_column(mut self, name: impl Into<String>) -> Self {
+        self.args.schema.label_column = Some(name.into());
+        self
+    }
+
+    // Function to store the image bytes as LargeBinary, whose 64-bit offsets let a chunk exceed 2 GiB
+    pub fn large_binary(mut self, large_binary: bool) -> Self {
+        self.args.schema.large_binary = large_binary;
+        self
+    }
+
+    // Function to store the labels as a dictionary-encoded column, holding every distinct label of a
+    // chunk once and an index per row
+    pub fn dictionary_labels(mut self, dictionary_labels: bool) -> Self {
+        self.args.schema.dictionary_labels = dictionary_labels;
+        self
+    }
+
+    // Function to store the labels as the ids of their class names, recorded in the metadata
+    pub fn class_labels(mut self, class_labels: bool) -> Self {
+        self.args.schema.class_labels = class_labels;
+        self
+    }
+
+    // Function to store the images as structs of their bytes and file name, the layout of the
+    // Image feature of HuggingFace
+    pub fn image_struct(mut self, image_struct: bool) -> Self {
+        self.args.schema.image_struct = image_struct;
+        self
+    }
+
+    // Function to add a nullable column of the given type, left null for every converted sample
+    pub fn nullable_column(mut self, name: impl Into<String>, column_type: ColumnType) -> Self {
+        self.args
+            .schema
+            .nullable_columns
+            .insert(name.into(), column_type);
+        self
+    }
+
+    // Function to add a column repeating the same string value in every row of every split
+    pub fn constant_column(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.args
+            .schema
+            .constant_columns
+            .insert(name.into(), value.into());
+        self
+    }
+
+    // Function to replace the output of an earlier conversion
+    pub fn force(mut self, force: bool) -> Self {
+        self.args.force = force;
+        self
+    }
+
+    // Function to add the samples as new chunks after those of an earlier conversion
+    pub fn append(mut self, append: bool) -> Self {
+        self.args.append = append;
+        self
+    }
+
+    // Function to resume an interrupted conversion, keeping the chunks that were complete
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.args.resume = resume;
+        self
+    }
+
+    // Function to select how progress is reported
+    pub fn progress_format(mut self, progress_format: ProgressFormat) -> Self {
+        self.args.progress_format = progress_format;
+        self
+    }
+
+    // Function to notify the given callback of started and completed chunks and failed samples
+    pub fn callback(mut self, callback: impl ProgressCallback + 'static) -> Self {
+        self.callback = Some(Arc::new(callback));
+        self
+    }
+
+    // Function to register a transform run on every sample after it is loaded, in the order the
+    // transforms were added
+    pub fn transform(mut self, transform: impl Transform + 'static) -> Self {
+        self.transforms.push(Arc::new(transform));
+        self
+    }
+
+    // Function to register a closure receiving the bytes and label of every sample and returning
+    // their replacement, or None to drop the sample
+    pub fn with_transform<F>(self, transform: F) -> Self
+    where
+        F: Fn(Vec<u8>, String) -> Option<(Vec<u8>, String)> + Send + Sync + 'static,
+    {
+        self.transform(move |record: &mut Record| {
+            // Hand the bytes and the label to the closure without copying them
+            let bytes = mem::take(&mut record.bytes);
+            let label = mem::take(&mut record.label);
+
+            // Put back the replacement, or drop the sample when the closure returns None
+            match transform(bytes, label) {
+                Some((bytes, label)) => {
+                    record.bytes = bytes;
+                    record.label = label;
+                    Ok(Keep::Yes)
+                }
+                None => Ok(Keep::No),
+            }
+        })
+    }
+
+    // Function to add a column computed from every sample after the transforms, placed after the
+    // columns of the schema in the order the computed columns were added
+    pub fn computed_column(mut self, column: impl ComputedColumn + 'static) -> Self {
+        self.computed.push(Arc::new(column));
+        self
+    }
+
+    // Function to stop the conversion between samples once the given token is cancelled
+    pub fn cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    // Function to only report the planned output without writing anything
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.args.dry_run = dry_run;
+        self
+    }
+
+    // Function to only write the manifest and class counts of every split without reading any sample
+    pub fn manifest_only(mut self, manifest_only: bool) -> Self {
+        self.args.manifest_only = manifest_only;
+        self
+    }
+
+    // Function to run the conversion, returning the number of converted and failed samples, or the
+    // error that stopped it; samples that cannot be read are skipped and counted instead
+    pub fn run(&self) -> Result<ConvertSummary> {
+        // Refuse to run with a count set to zero
+        if let Some(setting) = self.invalid_setting {
+            return Err(Error::InvalidSetting(setting));
+        }
+
+        run_convert(
+            &self.args,
+            self.callback.clone(),
+            self.transforms.clone(),
+            self.computed.clone(),
+            self.cancel.clone(),
+        )
+    }
+
+    // Function to run the conversion on tokio's blocking thread pool so the calling runtime keeps
+    // serving other tasks, cancelling the conversion like Ctrl-C if the future is dropped first
+    #[cfg(feature = "async")]
+    pub async fn convert_async(self) -> Result<ConvertSummary> {
+        // Arm the guard before handing the converter to the blocking pool
+        let mut guard = CancelOnDrop(Some(self.cancel.clone()));
+
+        // Wait for the conversion without blocking the runtime
+        let result = tokio::task::spawn_blocking(move || self.run()).await;
+
+        // Disarm the guard now that the conversion finished
+        guard.0 = None;
+        result.map_err(|_| Error::WorkerFailed)?
+    }
+}
@@ -0,0 +1,150 @@
+use crate::chunk_io::{read_chunk_schema, ChunkFormat};
+use crate::error::Result;
+use crate::metadata::{
+    find_split_dirs, read_chunk_rows, read_dataset_info, read_dataset_state, DatasetInfo,
+    DatasetState,
+};
+use crate::reader::DatasetReader;
+use arrow::datatypes::SchemaRef;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Define a handle on a converted dataset, either a single split directory or a directory holding
+// one subdirectory per split
+pub struct ArrowDataset {
+    root: PathBuf,
+    splits: Vec<DatasetSplit>,
+}
+
+// Define a handle on one converted split with its metadata and the chunk files listed in its state
+pub struct DatasetSplit {
+    name: String,
+    dir: PathBuf,
+    info: DatasetInfo,
+    state: DatasetState,
+}
+
+impl ArrowDataset {
+    // Function to open a converted dataset by reading the metadata of every split under it
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        // Locate the split directories and read each of them
+        let root = dir.as_ref();
+        let splits = find_split_dirs(root)?
+            .iter()
+            .map(DatasetSplit::open)
+            .collect::<Result<Vec<_>>>()?;
+
+        // Return the handle on the dataset
+        Ok(ArrowDataset {
+            root: root.to_path_buf(),
+            splits,
+        })
+    }
+
+    // Function to get the directory the dataset was opened from
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // Function to get every split of the dataset, sorted by directory
+    pub fn splits(&self) -> &[DatasetSplit] {
+        &self.splits
+    }
+
+    // Function to get a split by its directory name
+    pub fn split(&self, name: &str) -> Option<&DatasetSplit> {
+        self.splits.iter().find(|split| split.name == name)
+    }
+
+    // Function to get the total number of samples across every split
+    pub fn num_samples(&self) -> usize {
+        self.splits.iter().map(DatasetSplit::num_samples).sum()
+    }
+}
+
+impl DatasetSplit {
+    // Function to open a converted split directory by reading its metadata and state files
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        // Read the metadata and the state of the split
+        let dir = dir.as_ref();
+        let info = read_dataset_info(dir)?;
+        let state = read_dataset_state(dir)?;
+
+        // Name the split after its directory
+        let name = dir
+            .file_name()
+            .map_or(String::new(), |name| name.to_string_lossy().to_string());
+
+        // Return the handle on the split
+        Ok(DatasetSplit {
+            name,
+            dir: dir.to_path_buf(),
+            info,
+            state,
+        })
+    }
+
+    // Function to get the name of the split directory
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Function to get the path of the split directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // Function to get the metadata recorded in dataset_info.json
+    pub fn info(&self) -> &DatasetInfo {
+        &self.info
+    }
+
+    // Function to get the number of samples recorded in the metadata
+    pub fn num_samples(&self) -> usize {
+        self.info.num_samples
+    }
+
+    // Function to get the format of the chunk files recorded in the state
+    pub fn format(&self) -> ChunkFormat {
+        match self.state.data_type.as_str() {
+            "parquet" => ChunkFormat::Parquet,
+            _ => ChunkFormat::Arrow,
+        }
+    }
+
+    // Function to get the paths of the chunk files in order
+    pub fn chunk_paths(&self) -> Vec<PathBuf> {
+        self.state
+            .data_files
+            .iter()
+            .map(|data_file| self.dir.join(&data_file.filename))
+            .collect()
+    }
+
+    // Function to get the number of rows of every chunk, from the index when there is one
+    pub fn chunk_rows(&self) -> Result<Vec<usize>> {
+        read_chunk_rows(&self.dir, &self.state)
+    }
+
+    // Function to get the total on-disk size of the chunk files, ignoring missing ones
+    pub fn size_bytes(&self) -> u64 {
+        self.chunk_paths()
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    // Function to read the schema from the footer of the first chunk, if the split has one
+    pub fn schema(&self) -> Result<Option<SchemaRef>> {
+        self.chunk_paths()
+            .first()
+            .map(|path| read_chunk_schema(path))
+            .transpose()
+    }
+
+    // Function to open a reader over the samples of the split
+    pub fn reader(&self) -> Result<DatasetReader> {
+        DatasetReader::open(&self.dir)
+    }
+}
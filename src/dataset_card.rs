@@ -0,0 +1,210 @@
+use crate::dataset::ArrowDataset;
+use crate::error::{Error, Result};
+use crate::metadata::{Attribution, Feature};
+use indicatif::HumanBytes;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Define the name of the dataset card written at the root of the output, which the HuggingFace Hub
+// reads its YAML front matter from
+pub const DATASET_CARD_FILE_NAME: &str = "README.md";
+
+// Define the number of class names written inline in the card, above which class labels are
+// described as plain ids and the summary names only the first classes, so the card of a large
+// taxonomy such as ImageNet-21k stays readable; dataset_info.json always holds every name
+const MAX_CARD_CLASS_NAMES: usize = 1000;
+
+// Define the details of a converted dataset described by its card besides the converted splits
+pub struct CardDetails<'a> {
+    pub name: &'a str,
+    pub attribution: &'a Attribution,
+    pub classes: &'a BTreeSet<String>,
+}
+
+// Function to quote a string as a YAML scalar, written as a JSON string which YAML reads the same
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).expect("Failed to serialize dataset card")
+}
+
+// Function to name the size category of the Hub matching the given number of samples
+fn size_category(samples: usize) -> &'static str {
+    match samples {
+        0..1_000 => "n<1K",
+        1_000..10_000 => "1K<n<10K",
+        10_000..100_000 => "10K<n<100K",
+        100_000..1_000_000 => "100K<n<1M",
+        1_000_000..10_000_000 => "1M<n<10M",
+        10_000_000..100_000_000 => "10M<n<100M",
+        100_000_000..1_000_000_000 => "100M<n<1B",
+        _ => "n>1B",
+    }
+}
+
+// Function to write a feature as the value of the given key, a plain type name for images, values
+// and audio of varying sampling rates, a mapping for audio of one sampling rate and class labels,
+// or their id type when they have too many names to list, and the entry of the items of sequences
+fn write_feature_value(card: &mut String, indent: usize, key: &str, feature: &Feature) {
+    let pad = " ".repeat(indent);
+    match feature {
+        Feature::Image => writeln!(card, "{}{}: image", pad, key).unwrap(),
+        Feature::Audio {
+            sampling_rate: Some(sampling_rate),
+        } => {
+            writeln!(card, "{}{}:", pad, key).unwrap();
+            writeln!(card, "{}  audio:", pad).unwrap();
+            writeln!(card, "{}    sampling_rate: {}", pad, sampling_rate).unwrap();
+        }
+        Feature::Audio {
+            sampling_rate: None,
+        } => writeln!(card, "{}{}: audio", pad, key).unwrap(),
+        Feature::Value { dtype } => writeln!(card, "{}{}: {}", pad, key, dtype).unwrap(),
+        Feature::ClassLabel { names } if names.len() > MAX_CARD_CLASS_NAMES => {
+            writeln!(
+                card,
+                "{}{}: int64 # {} class names in dataset_info.json",
+                pad,
+                key,
+                names.len()
+            )
+            .unwrap();
+        }
+        Feature::ClassLabel { names } => {
+            writeln!(card, "{}{}:", pad, key).unwrap();
+            writeln!(card, "{}  class_label:", pad).unwrap();
+            writeln!(card, "{}    names:", pad).unwrap();
+            for (id, name) in names.iter().enumerate() {
+                writeln!(card, "{}      '{}': {}", pad, id, yaml_string(name)).unwrap();
+            }
+        }
+        Feature::Sequence { .. } => {
+            writeln!(card, "{}{}:", pad, key).unwrap();
+            write_feature_entry(card, indent + 2, feature);
+        }
+    }
+}
+
+// Function to write a feature in the layout of the features of dataset cards, as a dtype or, for
+// lists, as the sequence of the feature of their items with its length when it is fixed
+fn write_feature_entry(card: &mut String, indent: usize, feature: &Feature) {
+    match feature {
+        Feature::Sequence { feature, length } => {
+            write_feature_value(card, indent, "sequence", feature);
+            if let Some(length) = length {
+                writeln!(card, "{}length: {}", " ".repeat(indent), length).unwrap();
+            }
+        }
+        feature => write_feature_value(card, indent, "dtype", feature),
+    }
+}
+
+// Function to write the dataset card of a converted dataset at the root of the output, its YAML
+// front matter listing the chunk files of every split as the default config of the Hub, the
+// features and sizes of the splits, the size category and the license, followed by the description
+// and homepage of the dataset, a summary of the splits and classes and the citation
+pub fn write_dataset_card(output_dir: &Path, details: &CardDetails) -> Result<PathBuf> {
+    // Open the converted splits to read their metadata
+    let dataset = ArrowDataset::open(output_dir)?;
+    let splits = dataset.splits();
+    let total_bytes: u64 = splits.iter().map(|split| split.size_bytes()).sum();
+
+    // Write the license and size category of the dataset
+    let attribution = details.attribution;
+    let mut card = String::from("---\n");
+    if let Some(license) = &attribution.license {
+        writeln!(card, "license: {}", yaml_string(license)).unwrap();
+    }
+    writeln!(card, "pretty_name: {}", yaml_string(details.name)).unwrap();
+    writeln!(card, "size_categories:").unwrap();
+    writeln!(card, "- {}", size_category(dataset.num_samples())).unwrap();
+
+    // List the chunk files of every split, by their path relative to the card
+    writeln!(card, "configs:").unwrap();
+    writeln!(card, "- config_name: default").unwrap();
+    writeln!(card, "  data_files:").unwrap();
+    for split in splits {
+        writeln!(card, "  - split: {}", yaml_string(split.name())).unwrap();
+        writeln!(card, "    path:").unwrap();
+        for chunk_path in split.chunk_paths() {
+            let relative = chunk_path.strip_prefix(output_dir).unwrap_or(&chunk_path);
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            writeln!(card, "    - {}", yaml_string(&relative)).unwrap();
+        }
+    }
+
+    // Describe the features of the columns, shared by every split, and the size of every split
+    writeln!(card, "dataset_info:").unwrap();
+    if let Some(features) = splits
+        .first()
+        .and_then(|split| split.info().features.as_ref())
+    {
+        writeln!(card, "  features:").unwrap();
+        for (name, feature) in &features.0 {
+            writeln!(card, "  - name: {}", yaml_string(name)).unwrap();
+            write_feature_entry(&mut card, 4, feature);
+        }
+    }
+    writeln!(card, "  splits:").unwrap();
+    for split in splits {
+        writeln!(card, "  - name: {}", yaml_string(split.name())).unwrap();
+        writeln!(card, "    num_bytes: {}", split.size_bytes()).unwrap();
+        writeln!(card, "    num_examples: {}", split.num_samples()).unwrap();
+    }
+    writeln!(card, "  download_size: {}", total_bytes).unwrap();
+    writeln!(card, "  dataset_size: {}", total_bytes).unwrap();
+    writeln!(card, "---").unwrap();
+
+    // Describe the dataset and summarize its splits and classes for readers of the card
+    writeln!(card, "\n# {}\n", details.name).unwrap();
+    if let Some(description) = &attribution.description {
+        writeln!(card, "{}\n", description.trim()).unwrap();
+    }
+    if let Some(homepage) = &attribution.homepage {
+        writeln!(card, "Homepage: <{}>\n", homepage).unwrap();
+    }
+    writeln!(card, "| Split | Examples | Size |").unwrap();
+    writeln!(card, "| --- | ---: | ---: |").unwrap();
+    for split in splits {
+        writeln!(
+            card,
+            "| {} | {} | {} |",
+            split.name(),
+            split.num_samples(),
+            HumanBytes(split.size_bytes())
+        )
+        .unwrap();
+    }
+    if !details.classes.is_empty() {
+        let classes: Vec<String> = details
+            .classes
+            .iter()
+            .take(MAX_CARD_CLASS_NAMES)
+            .map(|class| format!("`{}`", class))
+            .collect();
+        let more = details.classes.len().saturating_sub(MAX_CARD_CLASS_NAMES);
+        let more = match more {
+            0 => String::new(),
+            more => format!(" and {} more", more),
+        };
+        writeln!(
+            card,
+            "\n{} classes: {}{}.",
+            details.classes.len(),
+            classes.join(", "),
+            more
+        )
+        .unwrap();
+    }
+    if let Some(license) = &attribution.license {
+        writeln!(card, "\nLicense: {}.", license).unwrap();
+    }
+    if let Some(citation) = &attribution.citation {
+        writeln!(card, "\n## Citation\n\n```bibtex\n{}\n```", citation.trim()).unwrap();
+    }
+
+    // Save the card at the root of the output
+    let card_path = output_dir.join(DATASET_CARD_FILE_NAME);
+    fs::write(&card_path, card).map_err(Error::io("Failed to write", &card_path))?;
+    Ok(card_path)
+}
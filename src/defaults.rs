@@ -0,0 +1,58 @@
+use std::fs;
+use std::num::NonZeroUsize;
+use std::thread;
+
+// Define the largest default chunk size, matching the original constant
+pub const MAX_DEFAULT_CHUNK_SIZE: usize = 49152;
+
+// Define how many images of URL lists are downloaded at the same time by default
+pub const DOWNLOAD_CONCURRENCY: NonZeroUsize = NonZeroUsize::new(16).unwrap();
+
+// Define how many seconds the download of an image may take by default
+pub const DOWNLOAD_TIMEOUT_SECS: u64 = 30;
+
+// Define the smallest chunk size picked automatically on low-memory machines
+const MIN_DEFAULT_CHUNK_SIZE: usize = 1024;
+
+// Define the assumed average size of a sample when sizing chunks from memory
+const ESTIMATED_SAMPLE_BYTES: u64 = 128 * 1024;
+
+// Function to read the available system memory in bytes, if it can be determined
+fn available_memory_bytes() -> Option<u64> {
+    // Read the kernel memory statistics
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+
+    // Find the "MemAvailable" line and parse its value in kilobytes
+    let kilobytes = meminfo
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse::<u64>()
+        .ok()?;
+
+    // Convert the value to bytes
+    Some(kilobytes * 1024)
+}
+
+// Function to derive the default thread count from the available cores
+pub fn default_threads() -> usize {
+    // Use the available parallelism, falling back to the original constant of 8
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(8)
+}
+
+// Function to derive the default chunk size so that all in-flight chunks fit in half the memory
+pub fn default_chunk_size(threads: usize) -> usize {
+    // Fall back to the largest chunk size when the memory cannot be determined
+    let Some(memory) = available_memory_bytes() else {
+        return MAX_DEFAULT_CHUNK_SIZE;
+    };
+
+    // Divide half of the available memory between the worker threads
+    let samples = memory / 2 / threads.max(1) as u64 / ESTIMATED_SAMPLE_BYTES;
+
+    // Keep the result within sane bounds
+    (samples as usize).clamp(MIN_DEFAULT_CHUNK_SIZE, MAX_DEFAULT_CHUNK_SIZE)
+}
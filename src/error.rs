@@ -0,0 +1,141 @@
+use arrow::error::ArrowError;
+#[cfg(feature = "parquet")]
+use parquet::errors::ParquetError;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Define every way a conversion, or one of the other subcommands, can fail
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{action} {path:?}: {source}")]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("Invalid JSON in {path:?}: {source}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("Invalid TOML in {path:?}: {source}")]
+    Toml {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Invalid CSV in {path:?}: {source}")]
+    Csv { path: PathBuf, source: csv::Error },
+    #[error("Manifest {path:?} has no '{column}' column")]
+    MissingColumn { path: PathBuf, column: String },
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] ArrowError),
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] ParquetError),
+    #[cfg(feature = "flight")]
+    #[error("Flight server error: {0}")]
+    Flight(#[from] tonic::transport::Error),
+    #[cfg(feature = "hub")]
+    #[error("HuggingFace Hub error: {0}")]
+    Hub(#[from] hf_hub::HFError),
+    #[cfg(any(feature = "azure", feature = "gcs", feature = "s3"))]
+    #[error("Object storage error: {0}")]
+    ObjectStore(#[from] object_store::Error),
+    #[cfg(feature = "lmdb")]
+    #[error("LMDB error: {0}")]
+    Lmdb(#[from] heed::Error),
+    #[cfg(feature = "lance")]
+    #[error("Lance error: {0}")]
+    Lance(#[from] lance::Error),
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error(
+        "Kaggle credentials not found, set KAGGLE_USERNAME and KAGGLE_KEY or save them in {0:?}"
+    )]
+    KaggleCredentials(PathBuf),
+    #[error("Invalid glob pattern: {0}")]
+    Glob(#[from] globset::Error),
+    #[cfg(feature = "watch")]
+    #[error("File watcher error: {0}")]
+    Watch(#[from] notify::Error),
+    #[error("Output directory {0:?} is not empty, pass --force to overwrite it or --append to add to it")]
+    OutputNotEmpty(PathBuf),
+    #[error("Cannot append to {0:?}: its chunks were written with another profile")]
+    FormatMismatch(PathBuf),
+    #[error("Cannot resume {path:?}: {reason}")]
+    Resume { path: PathBuf, reason: String },
+    #[error("Verification of {path:?} failed: {reason}")]
+    Verification { path: PathBuf, reason: String },
+    #[error("{kind} of {path:?} does not match the other chunks")]
+    ChunkMismatch { kind: &'static str, path: PathBuf },
+    #[error("Sample index {index} is out of range for a split of {len} samples")]
+    IndexOutOfRange { index: usize, len: usize },
+    #[error("Labels not listed in {path:?}: {}", format_labels(labels))]
+    UnknownLabels { path: PathBuf, labels: Vec<String> },
+    #[error("Column '{0}' is defined more than once in the output schema")]
+    DuplicateColumn(String),
+    #[error("{0} must be greater than zero")]
+    InvalidSetting(&'static str),
+    #[error("{0} requires the \"{1}\" feature of the crate")]
+    FeatureDisabled(&'static str, &'static str),
+    #[error("Transform failed: {0}")]
+    Transform(Box<dyn std::error::Error + Send + Sync>),
+    #[error("{0} is not supported")]
+    Unsupported(String),
+    #[error("A chunk worker stopped unexpectedly")]
+    WorkerFailed,
+    #[error("File watcher stopped unexpectedly")]
+    WatcherStopped,
+    #[error("Failed to install the logger: {0}")]
+    Logger(#[from] log::SetLoggerError),
+}
+
+// Define the result type returned by the fallible functions of the crate
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    // Function to build a mapper attaching the failed action and the path to an I/O error
+    pub fn io(action: &'static str, path: &Path) -> impl FnOnce(io::Error) -> Error {
+        let path = path.to_path_buf();
+        move |source| Error::Io {
+            action,
+            path,
+            source,
+        }
+    }
+
+    // Function to build a mapper attaching the path of the parsed file to a JSON error
+    pub fn json(path: &Path) -> impl FnOnce(serde_json::Error) -> Error {
+        let path = path.to_path_buf();
+        move |source| Error::Json { path, source }
+    }
+
+    // Function to build a mapper attaching the path of the parsed file to a CSV error
+    pub fn csv(path: &Path) -> impl FnOnce(csv::Error) -> Error {
+        let path = path.to_path_buf();
+        move |source| Error::Csv { path, source }
+    }
+
+    // Function to build a mapper attaching the path of the parsed file to a TOML error
+    pub fn toml(path: &Path) -> impl FnOnce(toml::de::Error) -> Error {
+        let path = path.to_path_buf();
+        move |source| Error::Toml { path, source }
+    }
+}
+
+// Define the number of unknown labels named in an error before the others are only counted
+const MAX_LISTED_LABELS: usize = 10;
+
+// Function to list the labels of an error, quoted, naming the first ones and counting the others
+fn format_labels(labels: &[String]) -> String {
+    let listed: Vec<String> = labels
+        .iter()
+        .take(MAX_LISTED_LABELS)
+        .map(|label| format!("'{}'", label))
+        .collect();
+    match labels.len().saturating_sub(MAX_LISTED_LABELS) {
+        0 => listed.join(", "),
+        more => format!("{} and {} more", listed.join(", "), more),
+    }
+}
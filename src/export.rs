@@ -0,0 +1,513 @@
+use crate::chunk_io::ChunkFormat;
+use crate::error::{Error, Result};
+use crate::profile::{Compression, OutputSettings};
+use crate::schema::SampleKind;
+use crate::sink;
+use crate::source::Record;
+use arrow::record_batch::RecordBatch;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+mod imagefolder;
+mod jsonl;
+#[cfg_attr(not(feature = "lance"), path = "export/lance_disabled.rs")]
+mod lance;
+#[cfg_attr(not(feature = "lmdb"), path = "export/lmdb_disabled.rs")]
+mod lmdb;
+mod safetensors;
+#[cfg_attr(not(feature = "tfrecord"), path = "export/tfrecord_disabled.rs")]
+mod tfrecord;
+mod webdataset;
+#[cfg_attr(not(feature = "zarr"), path = "export/zarr_disabled.rs")]
+mod zarr;
+
+pub use imagefolder::{ImagefolderExport, ImagefolderMetadata};
+pub use jsonl::JsonlExport;
+pub use lance::LanceDataset;
+pub use lmdb::{LmdbDatabase, LmdbExport};
+pub use safetensors::{SafetensorsExport, SafetensorsIndex};
+pub use tfrecord::TfrecordExport;
+pub use webdataset::WebDatasetExport;
+pub use zarr::{ZarrArray, ZarrExport};
+
+// Define the prefix of the names of the exported files, followed by the index of their chunk
+const EXPORT_FILE_PREFIX: &str = "shard-";
+
+// Define the folder of the LMDB environment every chunk of a split is added to
+const LMDB_DIR_NAME: &str = "data.lmdb";
+
+// Define the folder of the Lance dataset every chunk of a split is added to
+const LANCE_DIR_NAME: &str = "data.lance";
+
+// Define the folder of the Zarr group every chunk of a split is added to
+const ZARR_DIR_NAME: &str = "data.zarr";
+
+// Define the index listing the safetensors shards of a split
+const SAFETENSORS_INDEX_FILE_NAME: &str = "safetensors.index.json";
+
+// Define the folder of the images referenced by path from the JSONL files and metadata.csv of a
+// split
+const IMAGES_DIR_NAME: &str = "images";
+
+// Define the metadata file listing the images of a split in the layout of HuggingFace imagefolder
+// datasets
+const METADATA_CSV_FILE_NAME: &str = "metadata.csv";
+
+// Define the formats the samples of every chunk can be exported to, next to or instead of the chunk
+// files, for training stacks that do not read Arrow
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// WebDataset tar shards (shard-000000.tar) holding the sample with its .cls label and .json metadata
+    Webdataset,
+    /// TFRecord files (shard-000000.tfrecord) of tf.Example records with image/encoded and image/class/label features
+    Tfrecord,
+    /// An LMDB database (data.lmdb) shared by the chunks, mapping sample indices to Caffe Datum records
+    Lmdb,
+    /// A Lance dataset (data.lance) shared by the chunks, with the columns of the chunk files
+    Lance,
+    /// Arrow IPC files (shard-000000.arrow) written from the same record batches as the chunk files
+    Arrow,
+    /// Parquet files (shard-000000.parquet) written from the same record batches as the chunk files
+    Parquet,
+    /// Safetensors files (shard-000000.safetensors) stacking the arrays of array sources into a data tensor, listed in safetensors.index.json
+    Safetensors,
+    /// JSONL files (shard-000000.jsonl) with one JSON object per sample holding its label, metadata and image
+    Jsonl,
+    /// The images written to an images folder, listed in metadata.csv with their label, split and size like a HuggingFace imagefolder dataset
+    Imagefolder,
+    /// A Zarr group (data.zarr) shared by the chunks, with an N×H×W×C array of the decoded RGB images and an array of their class ids
+    Zarr,
+}
+
+// Define how the JSONL export stores the image of every sample
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonlImages {
+    /// Inline the image as a base64 string in the "image" field
+    #[default]
+    Base64,
+    /// Write the image to the images folder of the split and reference it in the "file_name" field
+    Path,
+}
+
+impl ExportFormat {
+    // Function to get the file extension of the files of this format
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Webdataset => "tar",
+            ExportFormat::Tfrecord => "tfrecord",
+            ExportFormat::Lmdb => "lmdb",
+            ExportFormat::Lance => "lance",
+            ExportFormat::Arrow => "arrow",
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Safetensors => "safetensors",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Imagefolder => "csv",
+            ExportFormat::Zarr => "zarr",
+        }
+    }
+
+    // Function to get the name of the file exporting the samples of the chunk of the given index,
+    // the LMDB database, Lance dataset or Zarr group holding every chunk, or the metadata.csv
+    // listing them
+    pub fn file_name(self, index: usize) -> String {
+        match self {
+            ExportFormat::Lmdb => LMDB_DIR_NAME.to_string(),
+            ExportFormat::Lance => LANCE_DIR_NAME.to_string(),
+            ExportFormat::Zarr => ZARR_DIR_NAME.to_string(),
+            ExportFormat::Imagefolder => METADATA_CSV_FILE_NAME.to_string(),
+            _ => format!("{}{:06}.{}", EXPORT_FILE_PREFIX, index, self.extension()),
+        }
+    }
+
+    // Function to check whether the format is written from the record batch of every chunk, with
+    // the columns of the chunk files, rather than from its samples
+    fn writes_batches(self) -> bool {
+        matches!(
+            self,
+            ExportFormat::Lance | ExportFormat::Arrow | ExportFormat::Parquet
+        )
+    }
+
+    // Function to refuse exporting kinds of samples whose record packs several files, which the
+    // formats written from the samples have no layout for, samples other than arrays as the
+    // tensors of safetensors files, and samples other than images as the pixels of Zarr arrays
+    pub fn check_sample_kind(self, kind: SampleKind) -> Result<()> {
+        match kind {
+            _ if self == ExportFormat::Safetensors && kind != SampleKind::Array => {
+                Err(Error::Unsupported(
+                    "Exporting samples other than arrays as safetensors".to_string(),
+                ))
+            }
+            _ if self == ExportFormat::Zarr && kind != SampleKind::Image => {
+                Err(Error::Unsupported(
+                    "Exporting samples other than images as Zarr arrays".to_string(),
+                ))
+            }
+            SampleKind::Paired | SampleKind::VideoFrames if !self.writes_batches() => {
+                Err(Error::Unsupported(format!(
+                    "Exporting {} samples as {}",
+                    match kind {
+                        SampleKind::Paired => "paired",
+                        _ => "video frame",
+                    },
+                    self.name()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Function to check whether the format stores the labels as the integer ids of their classes
+    pub fn stores_class_ids(self) -> bool {
+        matches!(
+            self,
+            ExportFormat::Tfrecord
+                | ExportFormat::Lmdb
+                | ExportFormat::Safetensors
+                | ExportFormat::Zarr
+        )
+    }
+
+    // Function to get the name of the format in messages
+    fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Webdataset => "WebDataset shards",
+            ExportFormat::Tfrecord => "TFRecord files",
+            ExportFormat::Lmdb => "LMDB records",
+            ExportFormat::Lance => "a Lance dataset",
+            ExportFormat::Arrow => "Arrow IPC files",
+            ExportFormat::Parquet => "Parquet files",
+            ExportFormat::Safetensors => "safetensors files",
+            ExportFormat::Jsonl => "JSONL files",
+            ExportFormat::Imagefolder => "an imagefolder dataset",
+            ExportFormat::Zarr => "Zarr arrays",
+        }
+    }
+}
+
+// Define the extension of the image files written for samples whose path has none
+const DEFAULT_IMAGE_EXTENSION: &str = "bin";
+
+// Function to write the image of the sample at the given chunk and row to the images folder of the
+// split, named after them with the extension of the sample like WebDataset members, returning its
+// path relative to the split folder
+fn write_image_file(
+    images_dir: &Path,
+    chunk: usize,
+    row: usize,
+    path: &Path,
+    bytes: &[u8],
+) -> Result<String> {
+    let extension = path
+        .extension()
+        .map_or(DEFAULT_IMAGE_EXTENSION.to_string(), |ext| {
+            ext.to_string_lossy().to_lowercase()
+        });
+    let file_name = format!("{:06}_{:06}.{}", chunk, row, extension);
+    let image_path = images_dir.join(&file_name);
+    fs::write(&image_path, bytes).map_err(Error::io("Failed to write", &image_path))?;
+    Ok(format!("{}/{}", IMAGES_DIR_NAME, file_name))
+}
+
+// Define the integer id of every class of a conversion, shared by its splits, for the formats
+// storing integer labels
+pub type ClassIds = Arc<HashMap<String, i64>>;
+
+// Function to number the given class names in order, the id of a class being its index
+pub fn class_ids(names: &[String]) -> ClassIds {
+    Arc::new(
+        names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.clone(), id as i64))
+            .collect(),
+    )
+}
+
+// Define the files of an exported split naming the classes of its integer labels in the order of
+// their ids
+#[derive(Deserialize)]
+struct ClassNamesFile {
+    #[serde(default)]
+    classes: Vec<String>,
+}
+
+// Function to read the class names of the exports of a split, from the index of its safetensors
+// shards or the attributes of its Zarr group, or none when it has neither
+pub fn read_class_names(output_dir: &Path) -> Result<Vec<String>> {
+    let paths = [
+        output_dir.join(SAFETENSORS_INDEX_FILE_NAME),
+        output_dir.join(ZARR_DIR_NAME).join(".zattrs"),
+    ];
+    for path in paths {
+        match fs::read_to_string(&path) {
+            Ok(json) => {
+                let file: ClassNamesFile =
+                    serde_json::from_str(&json).map_err(Error::json(&path))?;
+                return Ok(file.classes);
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(Error::io("Failed to read", &path)(e)),
+        }
+    }
+    Ok(Vec::new())
+}
+
+// Define the interface of an export writer receiving the samples of one chunk in row order
+pub trait SampleExport: Send {
+    // Function to write a sample with the path it was loaded from
+    fn write_sample(&mut self, path: &Path, record: &Record) -> Result<()>;
+
+    // Function to complete the export once every sample has been written
+    fn finalize(self: Box<Self>) -> Result<()>;
+}
+
+// Define the exports of the chunks of a split, sharing the ids of the classes and the LMDB database
+// and Lance dataset every chunk is added to, the output settings of the chunk files that the Arrow
+// and Parquet exports are written with, and how the JSONL exports store the images
+pub struct SplitExports {
+    formats: Vec<ExportFormat>,
+    output: OutputSettings,
+    jsonl_images: JsonlImages,
+    output_dir: PathBuf,
+    classes: ClassIds,
+    lmdb: Option<LmdbDatabase>,
+    lance: Option<LanceDataset>,
+    safetensors: Option<SafetensorsIndex>,
+    imagefolder: Option<ImagefolderMetadata>,
+    zarr: Option<ZarrArray>,
+}
+
+impl SplitExports {
+    // Function to prepare the exports of a split in the given formats, refusing the kinds of
+    // samples they have no layout for, labelling the samples with the class ids shared by every
+    // split of the conversion, opening the LMDB database with room for the estimated size of the
+    // samples and committing the chunks from the given one on, and creating the folder of the
+    // images referenced by the JSONL files
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        formats: &[ExportFormat],
+        output: OutputSettings,
+        jsonl_images: JsonlImages,
+        kind: SampleKind,
+        output_dir: &Path,
+        classes: ClassIds,
+        estimated_bytes: u64,
+        first_chunk: usize,
+    ) -> Result<Self> {
+        for format in formats {
+            format.check_sample_kind(kind)?;
+        }
+        if formats.contains(&ExportFormat::Parquet) && cfg!(not(feature = "parquet")) {
+            return Err(Error::FeatureDisabled("Exporting Parquet files", "parquet"));
+        }
+        let lmdb = formats
+            .contains(&ExportFormat::Lmdb)
+            .then(|| {
+                LmdbDatabase::open(
+                    &output_dir.join(ExportFormat::Lmdb.file_name(0)),
+                    estimated_bytes,
+                    first_chunk,
+                )
+            })
+            .transpose()?;
+        let lance = formats
+            .contains(&ExportFormat::Lance)
+            .then(|| LanceDataset::open(&output_dir.join(ExportFormat::Lance.file_name(0))))
+            .transpose()?;
+        let safetensors = formats
+            .contains(&ExportFormat::Safetensors)
+            .then(|| SafetensorsIndex::open(&output_dir.join(SAFETENSORS_INDEX_FILE_NAME)))
+            .transpose()?;
+        let zarr = formats
+            .contains(&ExportFormat::Zarr)
+            .then(|| ZarrArray::open(&output_dir.join(ZARR_DIR_NAME)))
+            .transpose()?;
+        let imagefolder = formats
+            .contains(&ExportFormat::Imagefolder)
+            .then(|| {
+                let split = output_dir.file_name().unwrap_or_default().to_string_lossy();
+                ImagefolderMetadata::open(&output_dir.join(METADATA_CSV_FILE_NAME), &split)
+            })
+            .transpose()?;
+        if imagefolder.is_some()
+            || formats.contains(&ExportFormat::Jsonl) && jsonl_images == JsonlImages::Path
+        {
+            let images_dir = output_dir.join(IMAGES_DIR_NAME);
+            fs::create_dir_all(&images_dir).map_err(Error::io("Failed to create", &images_dir))?;
+        }
+        Ok(SplitExports {
+            formats: formats.to_vec(),
+            output,
+            jsonl_images,
+            output_dir: output_dir.to_path_buf(),
+            classes,
+            lmdb,
+            lance,
+            safetensors,
+            imagefolder,
+            zarr,
+        })
+    }
+
+    // Function to create the export of the chunk of the given index in the given format, returning
+    // it with the path of the file it writes
+    fn create_export(
+        &self,
+        format: ExportFormat,
+        index: usize,
+    ) -> Result<(Box<dyn SampleExport>, PathBuf)> {
+        let path = self.output_dir.join(format.file_name(index));
+        let export: Box<dyn SampleExport> = match (format, &self.lmdb) {
+            (ExportFormat::Webdataset, _) => Box::new(WebDatasetExport::new(&path, index)?),
+            (ExportFormat::Tfrecord, _) => {
+                Box::new(TfrecordExport::new(&path, self.classes.clone())?)
+            }
+            (ExportFormat::Lmdb, Some(database)) => {
+                Box::new(LmdbExport::new(database, index, self.classes.clone()))
+            }
+            (ExportFormat::Lmdb, None) => unreachable!("LMDB database was opened with the split"),
+            (ExportFormat::Safetensors, _) => Box::new(SafetensorsExport::new(
+                &path,
+                index,
+                self.classes.clone(),
+                self.safetensors
+                    .as_ref()
+                    .expect("Safetensors index was opened with the split"),
+            )),
+            (ExportFormat::Jsonl, _) => Box::new(JsonlExport::new(
+                &path,
+                index,
+                self.jsonl_images,
+                &self.output_dir.join(IMAGES_DIR_NAME),
+            )?),
+            (ExportFormat::Zarr, _) => Box::new(ZarrExport::new(
+                self.zarr
+                    .as_ref()
+                    .expect("Zarr group was opened with the split"),
+                self.classes.clone(),
+            )),
+            (ExportFormat::Imagefolder, _) => Box::new(ImagefolderExport::new(
+                index,
+                &self.output_dir.join(IMAGES_DIR_NAME),
+                self.imagefolder
+                    .as_ref()
+                    .expect("Imagefolder metadata was opened with the split"),
+            )),
+            (ExportFormat::Lance | ExportFormat::Arrow | ExportFormat::Parquet, _) => {
+                unreachable!("{} are written from batches", format.name())
+            }
+        };
+        Ok((export, path))
+    }
+
+    // Function to write the samples of a chunk to the exports of the split written from samples,
+    // returning the paths of the written files in the order of the formats
+    pub fn export_samples(
+        &self,
+        index: usize,
+        samples: &[(&Path, Record)],
+    ) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::with_capacity(self.formats.len());
+        for &format in self
+            .formats
+            .iter()
+            .filter(|format| !format.writes_batches())
+        {
+            let (mut export, path) = self.create_export(format, index)?;
+            for (sample_path, record) in samples {
+                export.write_sample(sample_path, record)?;
+            }
+            export.finalize()?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    // Function to pass over the chunk of the given index in the exports numbering the samples of the
+    // split in chunk order, when no sample of the chunk is exported in this run
+    pub fn skip_chunk(&self, index: usize) -> Result<()> {
+        match &self.lmdb {
+            Some(lmdb) => lmdb.skip_chunk(index),
+            None => Ok(()),
+        }
+    }
+
+    // Function to write the files describing the exports of the whole split once every chunk is
+    // exported, the index of the safetensors shards, the metadata.csv of the imagefolder images and
+    // the labels and metadata of the Zarr group
+    pub fn finish(&self) -> Result<()> {
+        if let Some(safetensors) = &self.safetensors {
+            safetensors.save(&self.classes)?;
+        }
+        if let Some(imagefolder) = &self.imagefolder {
+            imagefolder.save()?;
+        }
+        if let Some(zarr) = &self.zarr {
+            zarr.save(&self.classes)?;
+        }
+        Ok(())
+    }
+
+    // Function to check whether an export of the split is written from the record batch of every
+    // chunk, which is then built even when no chunk file is written
+    pub fn exports_batches(&self) -> bool {
+        self.formats.iter().any(|format| format.writes_batches())
+    }
+
+    // Function to write the record batch of the chunk of the given index to the exports of the
+    // split written from batches, returning the paths they write
+    pub fn export_batch(&self, index: usize, batch: &RecordBatch) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        if let Some(lance) = &self.lance {
+            lance.write_batch(batch)?;
+            paths.push(self.output_dir.join(ExportFormat::Lance.file_name(0)));
+        }
+
+        // Write the batch through a second sink for the Arrow and Parquet exports, compressed like
+        // the chunk files when the format supports their codec and uncompressed otherwise
+        for &format in &self.formats {
+            let chunk_format = match format {
+                ExportFormat::Arrow => ChunkFormat::Arrow,
+                ExportFormat::Parquet => ChunkFormat::Parquet,
+                _ => continue,
+            };
+            let compression = match (chunk_format, self.output.compression) {
+                (ChunkFormat::Arrow, Compression::Snappy) => Compression::None,
+                (_, compression) => compression,
+            };
+            let output = OutputSettings {
+                format: chunk_format,
+                compression,
+                ..self.output
+            };
+            let path = self.output_dir.join(format.file_name(index));
+            let mut sink = sink::create_sink(&path, batch.schema_ref(), &output)?;
+            sink.write_batch(batch)?;
+            sink.finalize()?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+// Function to check whether an entry of a split directory was written by an export
+pub fn is_export_file(file_name: &str) -> bool {
+    file_name == LMDB_DIR_NAME
+        || file_name == LANCE_DIR_NAME
+        || file_name == SAFETENSORS_INDEX_FILE_NAME
+        || file_name == ZARR_DIR_NAME
+        || file_name == IMAGES_DIR_NAME
+        || file_name == METADATA_CSV_FILE_NAME
+        || file_name.starts_with(EXPORT_FILE_PREFIX)
+            && ExportFormat::value_variants()
+                .iter()
+                .any(|format| file_name.ends_with(&format!(".{}", format.extension())))
+}
@@ -0,0 +1,115 @@
+use super::{write_image_file, SampleExport};
+use crate::error::{Error, Result};
+use crate::source::Record;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Define a row of metadata.csv, describing an image of the split by its path relative to the file
+#[derive(Clone, Serialize, Deserialize)]
+struct MetadataRow {
+    file_name: String,
+    label: String,
+    split: String,
+    byte_size: u64,
+}
+
+// Define the metadata.csv of a split in the layout of HuggingFace imagefolder datasets, filled by
+// the exports of its chunks and starting with the rows written by an earlier run when appending
+#[derive(Clone)]
+pub struct ImagefolderMetadata {
+    path: PathBuf,
+    split: String,
+    existing: Arc<Vec<MetadataRow>>,
+    chunks: Arc<Mutex<BTreeMap<usize, Vec<MetadataRow>>>>,
+}
+
+impl ImagefolderMetadata {
+    // Function to open the metadata.csv at the given path of the given split, keeping the rows it
+    // already lists
+    pub fn open(path: &Path, split: &str) -> Result<Self> {
+        let existing = match fs::read(path) {
+            Ok(content) => csv::Reader::from_reader(content.as_slice())
+                .deserialize()
+                .collect::<std::result::Result<Vec<MetadataRow>, _>>()
+                .map_err(Error::csv(path))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(Error::io("Failed to read", path)(e)),
+        };
+        Ok(ImagefolderMetadata {
+            path: path.to_path_buf(),
+            split: split.to_string(),
+            existing: Arc::new(existing),
+            chunks: Arc::new(Mutex::new(BTreeMap::new())),
+        })
+    }
+
+    // Function to write metadata.csv with the rows of every chunk in chunk order after the existing
+    // ones
+    pub fn save(&self) -> Result<()> {
+        let chunks = self.chunks.lock().unwrap();
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for row in self.existing.iter().chain(chunks.values().flatten()) {
+            writer
+                .serialize(row)
+                .expect("Failed to serialize metadata.csv");
+        }
+        let content = writer
+            .into_inner()
+            .expect("Failed to serialize metadata.csv");
+        fs::write(&self.path, content).map_err(Error::io("Failed to write", &self.path))
+    }
+}
+
+// Define an export writing the images of a chunk to the images folder of the split, listed in its
+// metadata.csv with their label, split and size so the split also loads as an imagefolder dataset
+pub struct ImagefolderExport {
+    chunk: usize,
+    images_dir: PathBuf,
+    metadata: ImagefolderMetadata,
+    rows: Vec<MetadataRow>,
+}
+
+impl ImagefolderExport {
+    // Function to create the export of the chunk of the given index, writing the images to the given
+    // folder and listing them in the metadata once written
+    pub fn new(chunk: usize, images_dir: &Path, metadata: &ImagefolderMetadata) -> Self {
+        ImagefolderExport {
+            chunk,
+            images_dir: images_dir.to_path_buf(),
+            metadata: metadata.clone(),
+            rows: Vec::new(),
+        }
+    }
+}
+
+impl SampleExport for ImagefolderExport {
+    fn write_sample(&mut self, path: &Path, record: &Record) -> Result<()> {
+        let file_name = write_image_file(
+            &self.images_dir,
+            self.chunk,
+            self.rows.len(),
+            path,
+            &record.bytes,
+        )?;
+        self.rows.push(MetadataRow {
+            file_name,
+            label: record.label.clone(),
+            split: self.metadata.split.clone(),
+            byte_size: record.bytes.len() as u64,
+        });
+        Ok(())
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        self.metadata
+            .chunks
+            .lock()
+            .unwrap()
+            .insert(self.chunk, self.rows);
+        Ok(())
+    }
+}
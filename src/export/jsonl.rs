@@ -0,0 +1,74 @@
+use super::{write_image_file, JsonlImages, SampleExport};
+use crate::error::{Error, Result};
+use crate::source::Record;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+// Define an export writing the samples of a chunk as a JSONL file, one JSON object per line holding
+// the label and metadata of the sample with its image inlined as base64, or written to the images
+// folder of the split and referenced by its path relative to the JSONL file
+pub struct JsonlExport {
+    path: PathBuf,
+    chunk: usize,
+    row: usize,
+    images: JsonlImages,
+    images_dir: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl JsonlExport {
+    // Function to create the JSONL file of the chunk of the given index at the given path, writing
+    // the images referenced by path to the given folder
+    pub fn new(path: &Path, chunk: usize, images: JsonlImages, images_dir: &Path) -> Result<Self> {
+        let file = File::create(path).map_err(Error::io("Failed to create", path))?;
+        Ok(JsonlExport {
+            path: path.to_path_buf(),
+            chunk,
+            row: 0,
+            images,
+            images_dir: images_dir.to_path_buf(),
+            writer: BufWriter::new(file),
+        })
+    }
+}
+
+impl SampleExport for JsonlExport {
+    fn write_sample(&mut self, path: &Path, record: &Record) -> Result<()> {
+        // Describe the sample by its label and metadata values, such as its caption
+        let mut json = serde_json::Map::new();
+        json.insert("label".to_string(), record.label.clone().into());
+        for (name, value) in &record.metadata {
+            json.insert(name.clone(), value.clone().into());
+        }
+
+        // Inline the image, or write it to the images folder and reference it by its relative path
+        match self.images {
+            JsonlImages::Base64 => {
+                json.insert("image".to_string(), STANDARD.encode(&record.bytes).into());
+            }
+            JsonlImages::Path => {
+                let file_name =
+                    write_image_file(&self.images_dir, self.chunk, self.row, path, &record.bytes)?;
+                json.insert("file_name".to_string(), file_name.into());
+            }
+        }
+
+        // Write the sample as one line
+        let line = serde_json::to_vec(&json).expect("Failed to serialize sample");
+        self.writer
+            .write_all(&line)
+            .and_then(|_| self.writer.write_all(b"\n"))
+            .map_err(Error::io("Failed to write", &self.path))?;
+        self.row += 1;
+        Ok(())
+    }
+
+    fn finalize(mut self: Box<Self>) -> Result<()> {
+        self.writer
+            .flush()
+            .map_err(Error::io("Failed to write", &self.path))
+    }
+}
@@ -0,0 +1,56 @@
+use crate::error::{Error, Result};
+use arrow::record_batch::{RecordBatch, RecordBatchIterator};
+use lance::dataset::{Dataset, WriteMode, WriteParams};
+use std::path::Path;
+use std::sync::Mutex;
+use tokio::runtime::{self, Runtime};
+
+// Define the folder holding the versions of a Lance dataset, present once it has been created
+const VERSIONS_DIR_NAME: &str = "_versions";
+
+// Define the number of threads driving the writes of a dataset
+const RUNTIME_THREADS: usize = 2;
+
+// Define the Lance dataset a split is exported to, every chunk being added to it as a new fragment
+// with the columns of the chunk files
+pub struct LanceDataset {
+    uri: String,
+    runtime: Runtime,
+    created: Mutex<bool>,
+}
+
+impl LanceDataset {
+    // Function to prepare the export into the dataset in the given folder, which is created with
+    // the first chunk unless an earlier conversion created it
+    pub fn open(path: &Path) -> Result<Self> {
+        let runtime = runtime::Builder::new_multi_thread()
+            .worker_threads(RUNTIME_THREADS)
+            .enable_all()
+            .build()
+            .map_err(Error::io("Failed to start the runtime writing", path))?;
+        Ok(LanceDataset {
+            uri: path.to_string_lossy().to_string(),
+            runtime,
+            created: Mutex::new(path.join(VERSIONS_DIR_NAME).is_dir()),
+        })
+    }
+
+    // Function to add the record batch of a chunk to the dataset as a new version, one chunk at a
+    // time so the versions do not conflict
+    pub fn write_batch(&self, batch: &RecordBatch) -> Result<()> {
+        let mut created = self.created.lock().unwrap();
+        let params = WriteParams {
+            mode: if *created {
+                WriteMode::Append
+            } else {
+                WriteMode::Create
+            },
+            ..WriteParams::default()
+        };
+        let batches = RecordBatchIterator::new([Ok(batch.clone())], batch.schema());
+        self.runtime
+            .block_on(Dataset::write(batches, self.uri.as_str(), Some(params)))?;
+        *created = true;
+        Ok(())
+    }
+}
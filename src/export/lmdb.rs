@@ -0,0 +1,137 @@
+use super::{ClassIds, SampleExport};
+use crate::error::{Error, Result};
+use crate::source::{Datum, Record};
+use heed::types::Bytes;
+use heed::{Database, Env, EnvOpenOptions};
+use prost::Message;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+// Define the data file of an LMDB environment, whose size counts towards the size of its map
+const DATA_FILE_NAME: &str = "data.mdb";
+
+// Define the room added to the map of the environment beyond twice the size of the samples
+const MAP_SIZE_MARGIN: u64 = 64 << 20;
+
+// Define the granularity of the map size, a multiple of the page size of every platform
+const MAP_SIZE_ALIGNMENT: u64 = 1 << 20;
+
+// Define the chunks committed to the database so far, with the index of the next chunk to commit
+// and the records of the chunks finished ahead of it
+struct CommitOrder {
+    next_chunk: usize,
+    pending: BTreeMap<usize, Vec<Vec<u8>>>,
+}
+
+// Define the LMDB environment a split is exported to, with its unnamed database, shared by the
+// exports of its chunks, which are committed in chunk order so the keys follow the samples of the
+// split
+#[derive(Clone)]
+pub struct LmdbDatabase {
+    env: Env,
+    database: Database<Bytes, Bytes>,
+    order: Arc<Mutex<CommitOrder>>,
+}
+
+impl LmdbDatabase {
+    // Function to open or create the environment in the given folder, with a map large enough for
+    // the samples it already holds and twice the estimated size of the new ones, the chunks of this
+    // run starting at the given index
+    pub fn open(path: &Path, estimated_bytes: u64, first_chunk: usize) -> Result<Self> {
+        // Size the map from the existing data and the samples about to be added
+        fs::create_dir_all(path).map_err(Error::io("Failed to create", path))?;
+        let existing_bytes = fs::metadata(path.join(DATA_FILE_NAME)).map_or(0, |m| m.len());
+        let map_size = (existing_bytes + 2 * estimated_bytes + MAP_SIZE_MARGIN)
+            .next_multiple_of(MAP_SIZE_ALIGNMENT);
+
+        // SAFETY: the environment is only opened once per split and nothing else writes to it
+        // during the conversion
+        let env = unsafe {
+            EnvOpenOptions::new()
+                .map_size(map_size as usize)
+                .open(path)?
+        };
+
+        // Create the unnamed database holding the samples
+        let mut wtxn = env.write_txn()?;
+        let database = env.create_database::<Bytes, Bytes>(&mut wtxn, None)?;
+        wtxn.commit()?;
+        Ok(LmdbDatabase {
+            env,
+            database,
+            order: Arc::new(Mutex::new(CommitOrder {
+                next_chunk: first_chunk,
+                pending: BTreeMap::new(),
+            })),
+        })
+    }
+
+    // Function to add the records of a finished chunk, committing it and the chunks waiting for it
+    // once every chunk before it is committed, or keeping it until then
+    fn add_chunk(&self, chunk: usize, values: Vec<Vec<u8>>) -> Result<()> {
+        let mut guard = self.order.lock().unwrap();
+        let order = &mut *guard;
+        order.pending.insert(chunk, values);
+        while let Some(values) = order.pending.remove(&order.next_chunk) {
+            // Number the records after those already in the database within a single write
+            // transaction, so key N is sample N of the split
+            let mut wtxn = self.env.write_txn()?;
+            let first_index = self.database.len(&wtxn)?;
+            for (offset, value) in values.iter().enumerate() {
+                let key = format!("{:08}", first_index + offset as u64);
+                self.database.put(&mut wtxn, key.as_bytes(), value)?;
+            }
+            wtxn.commit()?;
+            order.next_chunk += 1;
+        }
+        Ok(())
+    }
+
+    // Function to pass over a chunk that adds no record in this run, already written by an
+    // interrupted run or abandoned after cancellation, so the chunks after it are committed
+    pub fn skip_chunk(&self, chunk: usize) -> Result<()> {
+        self.add_chunk(chunk, Vec::new())
+    }
+}
+
+// Define an export adding the samples of a chunk to the LMDB database of the split as Caffe Datum
+// records holding the encoded sample and its class id, keyed by sample index
+pub struct LmdbExport {
+    database: LmdbDatabase,
+    index: usize,
+    classes: ClassIds,
+    values: Vec<Vec<u8>>,
+}
+
+impl LmdbExport {
+    // Function to create the export of the chunk of the given index into the database of the split
+    pub fn new(database: &LmdbDatabase, index: usize, classes: ClassIds) -> Self {
+        LmdbExport {
+            database: database.clone(),
+            index,
+            classes,
+            values: Vec::new(),
+        }
+    }
+}
+
+impl SampleExport for LmdbExport {
+    fn write_sample(&mut self, _path: &Path, record: &Record) -> Result<()> {
+        // Encode the sample as a Datum, keeping it until the whole chunk is written at once
+        let datum = Datum {
+            data: record.bytes.clone(),
+            label: self.classes.get(&record.label).map_or(-1, |&id| id as i32),
+            encoded: true,
+            ..Datum::default()
+        };
+        self.values.push(datum.encode_to_vec());
+        Ok(())
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        // Commit the samples once the chunks before this one are in the database
+        self.database.add_chunk(self.index, self.values)
+    }
+}
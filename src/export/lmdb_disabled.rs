@@ -0,0 +1,39 @@
+use super::{ClassIds, SampleExport};
+use crate::error::{Error, Result};
+use crate::source::Record;
+use std::path::Path;
+
+// Define the LMDB database of a split, which cannot be opened in this build
+pub enum LmdbDatabase {}
+
+impl LmdbDatabase {
+    // Function to report that writing LMDB databases is unavailable in this build
+    pub fn open(_path: &Path, _estimated_bytes: u64, _first_chunk: usize) -> Result<Self> {
+        Err(Error::FeatureDisabled("Writing LMDB databases", "lmdb"))
+    }
+
+    // Function to pass over a chunk, never called without a database
+    pub fn skip_chunk(&self, _chunk: usize) -> Result<()> {
+        match *self {}
+    }
+}
+
+// Define the LMDB export, which cannot be created in this build
+pub struct LmdbExport;
+
+impl LmdbExport {
+    // Function to create the export of a chunk, never called without a database
+    pub fn new(database: &LmdbDatabase, _index: usize, _classes: ClassIds) -> Self {
+        match *database {}
+    }
+}
+
+impl SampleExport for LmdbExport {
+    fn write_sample(&mut self, _path: &Path, _record: &Record) -> Result<()> {
+        Err(Error::FeatureDisabled("Writing LMDB databases", "lmdb"))
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        Err(Error::FeatureDisabled("Writing LMDB databases", "lmdb"))
+    }
+}
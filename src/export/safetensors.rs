@@ -0,0 +1,263 @@
+use super::{ClassIds, SampleExport};
+use crate::error::{Error, Result};
+use crate::source::Record;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Define the columns describing the layout of the raw data of every array sample
+const SHAPE_COLUMN: &str = "shape";
+const DTYPE_COLUMN: &str = "dtype";
+const FORTRAN_ORDER_COLUMN: &str = "fortran_order";
+
+// Define the names of the tensors of every shard, the stacked samples and their class ids
+const DATA_TENSOR: &str = "data";
+const LABELS_TENSOR: &str = "labels";
+
+// Define the alignment of the data following the header, which lets readers map the tensors
+const HEADER_ALIGNMENT: usize = 8;
+
+// Define a shard listed in the index, with the data type and shape of its samples
+#[derive(Clone, Serialize, Deserialize)]
+struct SafetensorsShard {
+    file: String,
+    num_samples: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dtype: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shape: Option<Vec<u64>>,
+}
+
+// Define the index of the safetensors shards of a split, naming the classes of the label ids
+#[derive(Serialize, Deserialize)]
+struct IndexFile {
+    num_samples: usize,
+    classes: Vec<String>,
+    shards: Vec<SafetensorsShard>,
+}
+
+// Define the index of the safetensors shards of a split, filled by the exports of its chunks and
+// starting with the shards listed by an earlier run when appending
+#[derive(Clone)]
+pub struct SafetensorsIndex {
+    path: PathBuf,
+    shards: Arc<Mutex<BTreeMap<usize, SafetensorsShard>>>,
+}
+
+impl SafetensorsIndex {
+    // Function to open the index at the given path, keeping the shards it already lists
+    pub fn open(path: &Path) -> Result<Self> {
+        let shards = match fs::read_to_string(path) {
+            Ok(json) => {
+                let index: IndexFile = serde_json::from_str(&json).map_err(Error::json(path))?;
+                index.shards.into_iter().enumerate().collect()
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(Error::io("Failed to read", path)(e)),
+        };
+        Ok(SafetensorsIndex {
+            path: path.to_path_buf(),
+            shards: Arc::new(Mutex::new(shards)),
+        })
+    }
+
+    // Function to write the index listing every shard in chunk order with the names of the classes
+    // in the order of their ids
+    pub fn save(&self, classes: &ClassIds) -> Result<()> {
+        let shards: Vec<SafetensorsShard> = self.shards.lock().unwrap().values().cloned().collect();
+        let index = IndexFile {
+            num_samples: shards.iter().map(|shard| shard.num_samples).sum(),
+            classes: class_names(classes),
+            shards,
+        };
+        let json = serde_json::to_string_pretty(&index).expect("Failed to serialize index");
+        fs::write(&self.path, json).map_err(Error::io("Failed to write", &self.path))
+    }
+}
+
+// Function to list the names of the classes shared by every split in the order of their ids
+fn class_names(classes: &ClassIds) -> Vec<String> {
+    let mut names: Vec<(&String, &i64)> = classes.iter().collect();
+    names.sort_by_key(|&(_, id)| *id);
+    names.into_iter().map(|(name, _)| name.clone()).collect()
+}
+
+// Function to get the safetensors data type of a little-endian NumPy data type such as "<f4"
+fn safetensors_dtype(dtype: &str) -> Option<&'static str> {
+    let dtype = dtype.strip_prefix(['<', '|', '=']).unwrap_or(dtype);
+    Some(match dtype {
+        "f2" => "F16",
+        "f4" => "F32",
+        "f8" => "F64",
+        "i1" => "I8",
+        "i2" => "I16",
+        "i4" => "I32",
+        "i8" => "I64",
+        "u1" => "U8",
+        "u2" => "U16",
+        "u4" => "U32",
+        "u8" => "U64",
+        "b1" => "BOOL",
+        _ => return None,
+    })
+}
+
+// Define the layout shared by every array of a shard
+#[derive(PartialEq)]
+struct ArrayLayout {
+    dtype: &'static str,
+    shape: Vec<u64>,
+}
+
+// Define an export stacking the arrays of a chunk into a safetensors file holding a "data" tensor of
+// all the samples and a "labels" tensor of their class ids, for pipelines loading precomputed
+// embeddings or features
+pub struct SafetensorsExport {
+    path: PathBuf,
+    index: usize,
+    classes: ClassIds,
+    shards: SafetensorsIndex,
+    layout: Option<ArrayLayout>,
+    data: Vec<u8>,
+    labels: Vec<i64>,
+}
+
+impl SafetensorsExport {
+    // Function to create the export of the chunk of the given index, listed in the index once written
+    pub fn new(path: &Path, index: usize, classes: ClassIds, shards: &SafetensorsIndex) -> Self {
+        SafetensorsExport {
+            path: path.to_path_buf(),
+            index,
+            classes,
+            shards: shards.clone(),
+            layout: None,
+            data: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+}
+
+// Function to read the layout of an array sample from its metadata, refusing the arrays that cannot
+// be stacked into a safetensors tensor
+fn array_layout(path: &Path, record: &Record) -> Result<ArrayLayout> {
+    let unsupported = |reason: &str| Error::Unsupported(format!("{} in {:?}", reason, path));
+    if record
+        .metadata
+        .get(FORTRAN_ORDER_COLUMN)
+        .map(String::as_str)
+        == Some("true")
+    {
+        return Err(unsupported(
+            "Exporting Fortran-ordered arrays as safetensors",
+        ));
+    }
+    let dtype = record
+        .metadata
+        .get(DTYPE_COLUMN)
+        .ok_or_else(|| unsupported("Exporting samples without a data type as safetensors"))?;
+    let dtype = safetensors_dtype(dtype).ok_or_else(|| {
+        unsupported(&format!(
+            "Exporting arrays of data type '{}' as safetensors",
+            dtype
+        ))
+    })?;
+    let shape = record
+        .metadata
+        .get(SHAPE_COLUMN)
+        .and_then(|shape| serde_json::from_str(shape).ok())
+        .ok_or_else(|| unsupported("Exporting samples without a shape as safetensors"))?;
+    Ok(ArrayLayout { dtype, shape })
+}
+
+impl SampleExport for SafetensorsExport {
+    fn write_sample(&mut self, path: &Path, record: &Record) -> Result<()> {
+        // Check that the array has the data type and shape of the first one of the chunk
+        let layout = array_layout(path, record)?;
+        match &self.layout {
+            Some(first) if *first != layout => {
+                return Err(Error::Unsupported(format!(
+                    "Exporting arrays of different shapes or data types as safetensors in {:?}",
+                    path
+                )))
+            }
+            Some(_) => {}
+            None => self.layout = Some(layout),
+        }
+
+        // Append the raw data of the array and the id of its class
+        self.data.extend_from_slice(&record.bytes);
+        self.labels
+            .push(self.classes.get(&record.label).copied().unwrap_or(-1));
+        Ok(())
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        // Describe the stacked arrays, when there are any, followed by the class ids, naming the
+        // classes in the metadata of the file as a JSON array, since metadata values are strings
+        let rows = self.labels.len() as u64;
+        let labels: Vec<u8> = self.labels.iter().flat_map(|id| id.to_le_bytes()).collect();
+        let classes = serde_json::to_string(&class_names(&self.classes))
+            .expect("Failed to serialize classes");
+        let mut header = serde_json::Map::new();
+        header.insert(
+            "__metadata__".to_string(),
+            json!({ "format": "pt", "classes": classes }),
+        );
+        if let Some(layout) = &self.layout {
+            let shape: Vec<u64> = [rows].into_iter().chain(layout.shape.clone()).collect();
+            header.insert(
+                DATA_TENSOR.to_string(),
+                json!({
+                    "dtype": layout.dtype,
+                    "shape": shape,
+                    "data_offsets": [0, self.data.len()],
+                }),
+            );
+        }
+        header.insert(
+            LABELS_TENSOR.to_string(),
+            json!({
+                "dtype": "I64",
+                "shape": [rows],
+                "data_offsets": [self.data.len(), self.data.len() + labels.len()],
+            }),
+        );
+
+        // Pad the header with spaces so the data that follows it is aligned
+        let mut header = serde_json::to_vec(&header).expect("Failed to serialize header");
+        header.resize(header.len().next_multiple_of(HEADER_ALIGNMENT), b' ');
+
+        // Write the length of the header, the header and the tensors
+        let file = File::create(&self.path).map_err(Error::io("Failed to create", &self.path))?;
+        let mut writer = BufWriter::new(file);
+        writer
+            .write_all(&(header.len() as u64).to_le_bytes())
+            .and_then(|_| writer.write_all(&header))
+            .and_then(|_| writer.write_all(&self.data))
+            .and_then(|_| writer.write_all(&labels))
+            .and_then(|_| writer.flush())
+            .map_err(Error::io("Failed to write", &self.path))?;
+
+        // List the shard in the index of the split
+        let file = self
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        self.shards.shards.lock().unwrap().insert(
+            self.index,
+            SafetensorsShard {
+                file,
+                num_samples: rows as usize,
+                dtype: self.layout.as_ref().map(|layout| layout.dtype.to_string()),
+                shape: self.layout.map(|layout| layout.shape),
+            },
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use super::{ClassIds, SampleExport};
+use crate::error::{Error, Result};
+use crate::source::Record;
+use crate::tfrecord::{self, BytesList, Example, Feature, Features, Int64List, Kind};
+use prost::Message;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+// Define the format recorded for samples whose path has no extension
+const DEFAULT_FORMAT: &str = "bin";
+
+// Define an export writing the samples of a chunk as a TFRecord file of tf.Example records, with
+// the features read by the TensorFlow slim and object detection datasets
+pub struct TfrecordExport {
+    path: PathBuf,
+    classes: ClassIds,
+    writer: BufWriter<File>,
+}
+
+impl TfrecordExport {
+    // Function to create the TFRecord file at the given path, labelling the samples with the ids of
+    // their classes
+    pub fn new(path: &Path, classes: ClassIds) -> Result<Self> {
+        let file = File::create(path).map_err(Error::io("Failed to create", path))?;
+        Ok(TfrecordExport {
+            path: path.to_path_buf(),
+            classes,
+            writer: BufWriter::new(file),
+        })
+    }
+}
+
+// Function to create a feature holding a single byte string
+fn bytes_feature(value: impl Into<Vec<u8>>) -> Feature {
+    Feature {
+        kind: Some(Kind::Bytes(BytesList {
+            value: vec![value.into()],
+        })),
+    }
+}
+
+impl SampleExport for TfrecordExport {
+    fn write_sample(&mut self, path: &Path, record: &Record) -> Result<()> {
+        // Describe the sample by its encoded image, its format, its class id and its class name
+        let format = path.extension().map_or(DEFAULT_FORMAT.to_string(), |ext| {
+            ext.to_string_lossy().to_lowercase()
+        });
+        let label = self.classes.get(&record.label).copied().unwrap_or(-1);
+        let mut feature = HashMap::from([
+            (
+                "image/encoded".to_string(),
+                bytes_feature(record.bytes.as_slice()),
+            ),
+            ("image/format".to_string(), bytes_feature(format)),
+            (
+                "image/class/label".to_string(),
+                Feature {
+                    kind: Some(Kind::Int64(Int64List { value: vec![label] })),
+                },
+            ),
+            (
+                "image/class/text".to_string(),
+                bytes_feature(record.label.as_str()),
+            ),
+        ]);
+
+        // Keep the metadata values of the sample as byte string features of the same name
+        for (name, value) in &record.metadata {
+            feature
+                .entry(name.clone())
+                .or_insert_with(|| bytes_feature(value.as_str()));
+        }
+
+        // Append the Example as a framed record
+        let example = Example {
+            features: Some(Features { feature }),
+        };
+        tfrecord::write_record(&mut self.writer, &example.encode_to_vec())
+            .map_err(Error::io("Failed to write", &self.path))
+    }
+
+    fn finalize(mut self: Box<Self>) -> Result<()> {
+        self.writer
+            .flush()
+            .map_err(Error::io("Failed to write", &self.path))
+    }
+}
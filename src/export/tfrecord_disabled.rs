@@ -0,0 +1,24 @@
+use super::{ClassIds, SampleExport};
+use crate::error::{Error, Result};
+use crate::source::Record;
+use std::path::Path;
+
+// Define the TFRecord export, which cannot be created in this build
+pub enum TfrecordExport {}
+
+impl TfrecordExport {
+    // Function to report that writing TFRecord files is unavailable in this build
+    pub fn new(_path: &Path, _classes: ClassIds) -> Result<Self> {
+        Err(Error::FeatureDisabled("Writing TFRecord files", "tfrecord"))
+    }
+}
+
+impl SampleExport for TfrecordExport {
+    fn write_sample(&mut self, _path: &Path, _record: &Record) -> Result<()> {
+        match *self {}
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        match *self {}
+    }
+}
@@ -0,0 +1,80 @@
+use super::SampleExport;
+use crate::error::{Error, Result};
+use crate::source::Record;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tar::{Builder, EntryType, Header};
+
+// Define the extension of the members holding samples whose path has none
+const DEFAULT_EXTENSION: &str = "bin";
+
+// Define an export writing the samples of a chunk as a WebDataset shard, a tar archive where the
+// sample, its ".cls" label and its ".json" metadata share a key made of the chunk and row indices
+pub struct WebDatasetExport {
+    path: PathBuf,
+    chunk: usize,
+    row: usize,
+    builder: Builder<BufWriter<File>>,
+}
+
+impl WebDatasetExport {
+    // Function to create the shard of the chunk of the given index at the given path
+    pub fn new(path: &Path, chunk: usize) -> Result<Self> {
+        let file = File::create(path).map_err(Error::io("Failed to create", path))?;
+        Ok(WebDatasetExport {
+            path: path.to_path_buf(),
+            chunk,
+            row: 0,
+            builder: Builder::new(BufWriter::new(file)),
+        })
+    }
+
+    // Function to append a member holding the given data to the shard
+    fn append_member(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        let mut header = Header::new_gnu();
+        header.set_entry_type(EntryType::Regular);
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        self.builder
+            .append_data(&mut header, name, data)
+            .map_err(Error::io("Failed to write", &self.path))
+    }
+}
+
+impl SampleExport for WebDatasetExport {
+    fn write_sample(&mut self, path: &Path, record: &Record) -> Result<()> {
+        // Name the members after the chunk and row, keeping the extension of the sample so
+        // WebDataset decoders know how to read it
+        let key = format!("{:06}_{:06}", self.chunk, self.row);
+        let extension = path
+            .extension()
+            .map_or(DEFAULT_EXTENSION.to_string(), |ext| {
+                ext.to_string_lossy().to_lowercase()
+            });
+
+        // Describe the sample by its label and metadata values
+        let mut json = serde_json::Map::new();
+        json.insert("label".to_string(), record.label.clone().into());
+        for (name, value) in &record.metadata {
+            json.insert(name.clone(), value.clone().into());
+        }
+        let json = serde_json::to_vec(&json).expect("Failed to serialize sample metadata");
+
+        // Write the sample followed by its label and metadata members
+        self.append_member(&format!("{}.{}", key, extension), &record.bytes)?;
+        self.append_member(&format!("{}.cls", key), record.label.as_bytes())?;
+        self.append_member(&format!("{}.json", key), &json)?;
+        self.row += 1;
+        Ok(())
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        // Write the end of the archive and flush the buffered members to the file
+        let WebDatasetExport { path, builder, .. } = *self;
+        builder
+            .into_inner()
+            .and_then(|mut writer| writer.flush())
+            .map_err(Error::io("Failed to write", &path))
+    }
+}
@@ -0,0 +1,221 @@
+use super::{ClassIds, SampleExport};
+use crate::error::{Error, Result};
+use crate::source::Record;
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Define the arrays of the Zarr group of a split
+const IMAGES_ARRAY: &str = "images";
+const LABELS_ARRAY: &str = "labels";
+
+// Define the number of channels of the decoded images, which are all converted to RGB
+const CHANNELS: u64 = 3;
+
+// Define the number of class ids stored in every chunk of the labels array
+const LABELS_CHUNK_LEN: usize = 1 << 16;
+
+// Define the images added to the group so far, with the size they share and their class ids in
+// row order
+struct ZarrState {
+    size: Option<(u64, u64)>,
+    labels: Vec<i64>,
+}
+
+// Define the Zarr group a split is exported to, holding an images array of the decoded samples and
+// a labels array of their class ids, shared by the exports of its chunks and starting with the
+// images added by an earlier run when appending
+#[derive(Clone)]
+pub struct ZarrArray {
+    path: PathBuf,
+    state: Arc<Mutex<ZarrState>>,
+}
+
+// Function to map an error of the image decoder to an I/O error of the given sample
+fn decode_error(path: &Path) -> impl FnOnce(image::ImageError) -> Error + '_ {
+    move |e| Error::io("Failed to decode", path)(io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+// Function to read a JSON metadata file of the group
+fn read_json(path: &Path) -> Result<Value> {
+    let json = fs::read_to_string(path).map_err(Error::io("Failed to read", path))?;
+    serde_json::from_str(&json).map_err(Error::json(path))
+}
+
+// Function to write a JSON metadata file of the group
+fn write_json(path: &Path, value: &Value) -> Result<()> {
+    let json = serde_json::to_string_pretty(value).expect("Failed to serialize Zarr metadata");
+    fs::write(path, json).map_err(Error::io("Failed to write", path))
+}
+
+impl ZarrArray {
+    // Function to open or create the group in the given folder, reading the size and class ids of
+    // the images it already holds
+    pub fn open(path: &Path) -> Result<Self> {
+        // Create the folders of both arrays
+        for array in [IMAGES_ARRAY, LABELS_ARRAY] {
+            let dir = path.join(array);
+            fs::create_dir_all(&dir).map_err(Error::io("Failed to create", &dir))?;
+        }
+
+        // Read the shape of the existing images and their class ids, chunk by chunk
+        let mut state = ZarrState {
+            size: None,
+            labels: Vec::new(),
+        };
+        let images_meta = path.join(IMAGES_ARRAY).join(".zarray");
+        if images_meta.is_file() {
+            let shape: Vec<u64> = serde_json::from_value(read_json(&images_meta)?["shape"].clone())
+                .map_err(Error::json(&images_meta))?;
+            let rows = shape.first().copied().unwrap_or(0) as usize;
+            if rows > 0 {
+                state.size = Some((shape[1], shape[2]));
+            }
+            let mut chunk = 0;
+            while state.labels.len() < rows {
+                let chunk_path = path.join(LABELS_ARRAY).join(chunk.to_string());
+                let bytes =
+                    fs::read(&chunk_path).map_err(Error::io("Failed to read", &chunk_path))?;
+                state.labels.extend(
+                    bytes
+                        .chunks_exact(8)
+                        .map(|id| i64::from_le_bytes(id.try_into().unwrap())),
+                );
+                chunk += 1;
+            }
+            state.labels.truncate(rows);
+        }
+        Ok(ZarrArray {
+            path: path.to_path_buf(),
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    // Function to reserve the row of an image of the given height and width with its class id,
+    // refusing images whose size differs from the first one
+    fn add_row(&self, path: &Path, size: (u64, u64), label: i64) -> Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        if *state.size.get_or_insert(size) != size {
+            return Err(Error::Unsupported(format!(
+                "Exporting images of different sizes as Zarr arrays in {:?}",
+                path
+            )));
+        }
+        state.labels.push(label);
+        Ok(state.labels.len() - 1)
+    }
+
+    // Function to write the labels and the metadata of the group, consolidated so xarray and dask
+    // open it with a single read, naming the classes shared by every split in the order of their ids
+    // in the attributes of the group and of the labels array
+    pub fn save(&self, classes: &ClassIds) -> Result<()> {
+        let state = self.state.lock().unwrap();
+        let rows = state.labels.len() as u64;
+        let (height, width) = state.size.unwrap_or((0, 0));
+
+        // Write the class ids in chunks of a fixed length, the last one padded with the fill value
+        for (chunk, ids) in state.labels.chunks(LABELS_CHUNK_LEN).enumerate() {
+            let mut bytes: Vec<u8> = ids.iter().flat_map(|id| id.to_le_bytes()).collect();
+            bytes.resize(LABELS_CHUNK_LEN * 8, 0xff);
+            let chunk_path = self.path.join(LABELS_ARRAY).join(chunk.to_string());
+            fs::write(&chunk_path, bytes).map_err(Error::io("Failed to write", &chunk_path))?;
+        }
+
+        // Describe the images as an array of uncompressed chunks of one image each, and the labels
+        // as an array of 64-bit class ids
+        let mut names: Vec<(&String, &i64)> = classes.iter().collect();
+        names.sort_by_key(|&(_, id)| *id);
+        let names: Vec<&String> = names.into_iter().map(|(name, _)| name).collect();
+        let metadata = [
+            (".zgroup".to_string(), json!({ "zarr_format": 2 })),
+            (".zattrs".to_string(), json!({ "classes": names })),
+            (
+                format!("{}/.zarray", IMAGES_ARRAY),
+                json!({
+                    "zarr_format": 2,
+                    "shape": [rows, height, width, CHANNELS],
+                    "chunks": [1, height.max(1), width.max(1), CHANNELS],
+                    "dtype": "|u1",
+                    "compressor": null,
+                    "fill_value": 0,
+                    "order": "C",
+                    "filters": null,
+                    "dimension_separator": ".",
+                }),
+            ),
+            (
+                format!("{}/.zattrs", IMAGES_ARRAY),
+                json!({ "_ARRAY_DIMENSIONS": ["sample", "height", "width", "channel"] }),
+            ),
+            (
+                format!("{}/.zarray", LABELS_ARRAY),
+                json!({
+                    "zarr_format": 2,
+                    "shape": [rows],
+                    "chunks": [LABELS_CHUNK_LEN],
+                    "dtype": "<i8",
+                    "compressor": null,
+                    "fill_value": -1,
+                    "order": "C",
+                    "filters": null,
+                    "dimension_separator": ".",
+                }),
+            ),
+            (
+                format!("{}/.zattrs", LABELS_ARRAY),
+                json!({ "_ARRAY_DIMENSIONS": ["sample"], "classes": names }),
+            ),
+        ];
+        for (name, value) in &metadata {
+            write_json(&self.path.join(name), value)?;
+        }
+        let consolidated: serde_json::Map<String, Value> = metadata.into_iter().collect();
+        write_json(
+            &self.path.join(".zmetadata"),
+            &json!({ "zarr_consolidated_format": 1, "metadata": consolidated }),
+        )
+    }
+}
+
+// Define an export decoding the images of a chunk into RGB pixels, each written as one chunk of the
+// images array of the group of the split with its class id in the labels array
+pub struct ZarrExport {
+    array: ZarrArray,
+    classes: ClassIds,
+}
+
+impl ZarrExport {
+    // Function to create the export of a chunk into the group of the split
+    pub fn new(array: &ZarrArray, classes: ClassIds) -> Self {
+        ZarrExport {
+            array: array.clone(),
+            classes,
+        }
+    }
+}
+
+impl SampleExport for ZarrExport {
+    fn write_sample(&mut self, path: &Path, record: &Record) -> Result<()> {
+        // Decode the image into RGB pixels and reserve its row
+        let pixels = image::load_from_memory(&record.bytes)
+            .map_err(decode_error(path))?
+            .into_rgb8();
+        let size = (pixels.height() as u64, pixels.width() as u64);
+        let label = self.classes.get(&record.label).copied().unwrap_or(-1);
+        let row = self.array.add_row(path, size, label)?;
+
+        // Write the pixels as the chunk of the row
+        let chunk_path = self
+            .array
+            .path
+            .join(IMAGES_ARRAY)
+            .join(format!("{}.0.0.0", row));
+        fs::write(&chunk_path, pixels.as_raw()).map_err(Error::io("Failed to write", &chunk_path))
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use super::{ClassIds, SampleExport};
+use crate::error::{Error, Result};
+use crate::source::Record;
+use std::path::Path;
+
+// Define the Zarr group of a split, which cannot be opened in this build
+pub enum ZarrArray {}
+
+impl ZarrArray {
+    // Function to report that writing Zarr arrays is unavailable in this build
+    pub fn open(_path: &Path) -> Result<Self> {
+        Err(Error::FeatureDisabled("Writing Zarr arrays", "zarr"))
+    }
+
+    // Function to write the metadata of the group, never called without a group
+    pub fn save(&self, _classes: &ClassIds) -> Result<()> {
+        match *self {}
+    }
+}
+
+// Define the Zarr export, which cannot be created in this build
+pub struct ZarrExport;
+
+impl ZarrExport {
+    // Function to create the export of a chunk, never called without a group
+    pub fn new(array: &ZarrArray, _classes: ClassIds) -> Self {
+        match *array {}
+    }
+}
+
+impl SampleExport for ZarrExport {
+    fn write_sample(&mut self, _path: &Path, _record: &Record) -> Result<()> {
+        Err(Error::FeatureDisabled("Writing Zarr arrays", "zarr"))
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        Err(Error::FeatureDisabled("Writing Zarr arrays", "zarr"))
+    }
+}
@@ -0,0 +1,75 @@
+use crate::batch::{entry_to_convert_args, ManifestEntry};
+use crate::report::EXIT_FAILURE;
+use crate::DatasetConverter;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic;
+use std::path::Path;
+use std::ptr;
+
+// Define the status returned when the configuration passed to the C API is invalid
+pub const EXIT_INVALID_CONFIG: c_int = 2;
+
+thread_local! {
+    // Keep the message of the last failure of the calling thread for ad_last_error
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+// Function to remember the message of a failure for the calling thread and return its status
+fn fail(status: c_int, message: String) -> c_int {
+    let message = CString::new(message.replace('\0', " ")).expect("Nul bytes were replaced");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    status
+}
+
+// Function to run a conversion described by a JSON config, returning its exit status
+fn convert_json(config_json: &str) -> c_int {
+    // Parse the config, which has the fields of a batch manifest entry
+    let entry: ManifestEntry = match serde_json::from_str(config_json) {
+        Ok(entry) => entry,
+        Err(e) => return fail(EXIT_INVALID_CONFIG, format!("Invalid config: {}", e)),
+    };
+
+    // Run the conversion with paths relative to the working directory
+    let args = entry_to_convert_args(&entry, Path::new(""));
+    match DatasetConverter::from_args(args).run() {
+        Ok(summary) => summary.exit_code(),
+        Err(e) => fail(EXIT_FAILURE, e.to_string()),
+    }
+}
+
+/// Convert a dataset described by a NUL-terminated JSON object with the fields of a batch
+/// manifest entry, returning the exit status of the `convert` subcommand, or 2 when the
+/// config is invalid.
+///
+/// # Safety
+///
+/// `config_json` must be null or point to a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ad_convert(config_json: *const c_char) -> c_int {
+    // Clear the error of a previous call
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+
+    // Reject a missing or non UTF-8 config
+    if config_json.is_null() {
+        return fail(EXIT_INVALID_CONFIG, "Config is null".to_string());
+    }
+    let Ok(config_json) = CStr::from_ptr(config_json).to_str() else {
+        return fail(EXIT_INVALID_CONFIG, "Config is not valid UTF-8".to_string());
+    };
+
+    // Never let a panic unwind into the calling C code
+    panic::catch_unwind(|| convert_json(config_json))
+        .unwrap_or_else(|_| fail(EXIT_FAILURE, "Conversion panicked".to_string()))
+}
+
+/// Return the message of the last failed `ad_convert` call on the calling thread, or null.
+/// The string stays valid until the next `ad_convert` call on the same thread.
+#[no_mangle]
+pub extern "C" fn ad_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
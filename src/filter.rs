@@ -0,0 +1,102 @@
+use crate::error::Result;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::collections::HashSet;
+use std::path::Path;
+
+// Define the image file extensions converted when none are given
+pub const DEFAULT_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "bmp", "tif", "tiff"];
+
+// Define a struct holding the file extensions and the include and exclude patterns applied during
+// the directory walk
+#[derive(Clone)]
+pub struct PathFilter {
+    extensions: HashSet<String>,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+// Function to compile a list of glob patterns into a single matcher
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    // Create a builder for the set of patterns
+    let mut builder = GlobSetBuilder::new();
+
+    // Add every pattern, keeping "*" from matching across directory separators
+    for pattern in patterns {
+        let glob = globset::GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()?;
+        builder.add(glob);
+    }
+
+    // Compile the set of patterns
+    Ok(builder.build()?)
+}
+
+// Function to get the default image file extensions as owned strings
+pub fn default_extensions() -> Vec<String> {
+    DEFAULT_EXTENSIONS
+        .iter()
+        .map(|ext| ext.to_string())
+        .collect()
+}
+
+impl PathFilter {
+    // Function to create a filter from file extensions, matched case-insensitively with or without
+    // a leading dot, and include and exclude patterns
+    pub fn new(extensions: &[String], include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(PathFilter {
+            extensions: extensions
+                .iter()
+                .map(|ext| ext.trim_start_matches('.').to_ascii_lowercase())
+                .collect(),
+            // Only restrict the walk to included paths when include patterns are given
+            include: (!include.is_empty())
+                .then(|| build_glob_set(include))
+                .transpose()?,
+            exclude: build_glob_set(exclude)?,
+        })
+    }
+
+    // Function to get a copy of the filter accepting the given extensions instead, for sources
+    // reading files other than images
+    pub fn with_extensions(&self, extensions: &[&str]) -> PathFilter {
+        PathFilter {
+            extensions: extensions.iter().map(|ext| ext.to_string()).collect(),
+            ..self.clone()
+        }
+    }
+
+    // Function to check whether a path relative to the split root passes the filter
+    pub fn matches(&self, relative_path: &Path) -> bool {
+        // Reject the path when its extension is not one of the accepted ones, ignoring case
+        let extension = relative_path.extension().and_then(|ext| ext.to_str());
+        if !extension.is_some_and(|ext| self.extensions.contains(&ext.to_ascii_lowercase())) {
+            return false;
+        }
+
+        // Check the include and exclude patterns
+        self.matches_patterns(relative_path)
+    }
+
+    // Function to check whether a path passes the include and exclude patterns, whatever its
+    // extension, for sources such as URL lists whose images may have none
+    pub fn matches_patterns(&self, relative_path: &Path) -> bool {
+        // Reject the path when it is not covered by any include pattern
+        if let Some(include) = &self.include {
+            if !include.is_match(relative_path) {
+                return false;
+            }
+        }
+
+        // Reject the path when any exclude pattern matches it
+        !self.exclude.is_match(relative_path)
+    }
+}
+
+// Function to check that a single glob pattern is valid, used to validate command line values
+pub fn parse_glob(pattern: &str) -> std::result::Result<String, String> {
+    // Compile the pattern and report the error if it is invalid
+    Glob::new(pattern)
+        .map(|_| pattern.to_string())
+        .map_err(|e| e.to_string())
+}
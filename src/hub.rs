@@ -0,0 +1,178 @@
+use crate::error::{Error, Result};
+use crate::metadata::file_sha256;
+use hf_hub::repository::{CommitOperation, RepoTreeEntry};
+use hf_hub::{HFClient, RepoTypeDataset};
+use log::info;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+// Define the folder holding the splits of many imagefolder repositories on the Hub
+const DATA_DIR: &str = "data";
+
+// Function to build a glob matching the extension in any letter case, since the Hub matches the
+// download patterns case-sensitively
+fn extension_glob(extension: &str) -> String {
+    let pattern: String = extension
+        .trim_start_matches('.')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphabetic() {
+                format!("[{}{}]", c.to_ascii_lowercase(), c.to_ascii_uppercase())
+            } else {
+                c.to_string()
+            }
+        })
+        .collect();
+    format!("*.{}", pattern)
+}
+
+// Function to download a dataset repository of the HuggingFace Hub, given as "org/name", into the
+// local Hub cache and return the folder holding its splits; only the files with one of the given
+// extensions are downloaded when any is given
+pub fn download_dataset(
+    repo_id: &str,
+    token: Option<&str>,
+    extensions: &[String],
+    workers: usize,
+) -> Result<PathBuf> {
+    // Connect with the given token, or the one of the environment or the Hub login
+    let mut builder = HFClient::builder();
+    if let Some(token) = token {
+        builder = builder.token(token);
+    }
+    let client = builder.build_sync()?;
+
+    // Download the snapshot of the repository, reusing the files already in the cache
+    let (owner, name) = hf_hub::split_id(repo_id);
+    info!(
+        "Downloading dataset {} from the HuggingFace Hub...",
+        repo_id
+    );
+    let allow_patterns = (!extensions.is_empty())
+        .then(|| extensions.iter().map(|ext| extension_glob(ext)).collect());
+    let snapshot = client
+        .dataset(owner, name)
+        .snapshot_download()
+        .maybe_allow_patterns(allow_patterns)
+        .max_workers(workers)
+        .send()?;
+    info!("Downloaded dataset {} to {:?}", repo_id, snapshot);
+
+    // Use the data folder as the dataset root when the repository keeps its splits there
+    let data_dir = snapshot.join(DATA_DIR);
+    Ok(if data_dir.is_dir() {
+        data_dir
+    } else {
+        snapshot
+    })
+}
+
+// Function to upload the files of a converted dataset to a dataset repository of the HuggingFace
+// Hub, given as "org/name", creating it when missing; every split is committed on its own and the
+// top-level files last, and the LFS files already on the Hub with the same content are skipped so
+// an interrupted upload resumes where it stopped
+pub fn upload_dataset(
+    repo_id: &str,
+    token: Option<&str>,
+    output_dir: &Path,
+    commit_message: &str,
+    private: bool,
+) -> Result<()> {
+    // Connect with the given token, or the one of the environment or the Hub login
+    let mut builder = HFClient::builder();
+    if let Some(token) = token {
+        builder = builder.token(token);
+    }
+    let client = builder.build_sync()?;
+
+    // Create the repository unless it already exists
+    client
+        .create_repository()
+        .repo_id(repo_id)
+        .repo_type(RepoTypeDataset)
+        .private(private)
+        .exist_ok(true)
+        .send()?;
+    let (owner, name) = hf_hub::split_id(repo_id);
+    let repository = client.dataset(owner, name);
+
+    // Index the LFS files already in the repository by path, with their size and hash
+    let uploaded: HashMap<String, (u64, String)> = repository
+        .list_tree()
+        .recursive(true)
+        .send()?
+        .into_iter()
+        .filter_map(|entry| match entry {
+            RepoTreeEntry::File {
+                path,
+                size,
+                lfs: Some(lfs),
+                ..
+            } => Some((path, (size, lfs.sha256?))),
+            _ => None,
+        })
+        .collect();
+
+    // Group the files of the output folder by split folder, skipping those already uploaded
+    let mut split_files: BTreeMap<String, Vec<CommitOperation>> = BTreeMap::new();
+    let mut top_level_files = Vec::new();
+    let mut skipped = 0;
+    for entry in WalkDir::new(output_dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| Error::io("Failed to read", output_dir)(e.into()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let relative = path.strip_prefix(output_dir).unwrap_or(path);
+        let components: Vec<String> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect();
+        let path_in_repo = components.join("/");
+        if let Some((size, sha256)) = uploaded.get(&path_in_repo) {
+            let metadata = entry
+                .metadata()
+                .map_err(|e| Error::io("Failed to read", path)(e.into()))?;
+            if metadata.len() == *size
+                && file_sha256(path).map_err(Error::io("Failed to read", path))? == *sha256
+            {
+                skipped += 1;
+                continue;
+            }
+        }
+        let operation = CommitOperation::add_file(path_in_repo, path);
+        match components.as_slice() {
+            [split, _, ..] => split_files
+                .entry(split.clone())
+                .or_default()
+                .push(operation),
+            _ => top_level_files.push(operation),
+        }
+    }
+    if skipped > 0 {
+        info!("Skipping {} files already on the HuggingFace Hub", skipped);
+    }
+
+    // Commit the splits first and the top-level files describing them last
+    let commits = split_files
+        .into_iter()
+        .map(|(split, operations)| (format!("{} ({})", commit_message, split), operations))
+        .chain(
+            (!top_level_files.is_empty()).then(|| (commit_message.to_string(), top_level_files)),
+        );
+    for (message, operations) in commits {
+        info!(
+            "Uploading {} files to the HuggingFace Hub: {}",
+            operations.len(),
+            message
+        );
+        repository
+            .create_commit()
+            .operations(operations)
+            .commit_message(message)
+            .send()?;
+    }
+    info!("Uploaded dataset {} to the HuggingFace Hub", repo_id);
+    Ok(())
+}
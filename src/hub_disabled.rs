@@ -0,0 +1,29 @@
+use crate::error::{Error, Result};
+use std::path::{Path, PathBuf};
+
+// Function to report that reading datasets from the HuggingFace Hub is unavailable in this build
+pub fn download_dataset(
+    _repo_id: &str,
+    _token: Option<&str>,
+    _extensions: &[String],
+    _workers: usize,
+) -> Result<PathBuf> {
+    Err(Error::FeatureDisabled(
+        "Reading from the HuggingFace Hub",
+        "hub",
+    ))
+}
+
+// Function to report that uploading datasets to the HuggingFace Hub is unavailable in this build
+pub fn upload_dataset(
+    _repo_id: &str,
+    _token: Option<&str>,
+    _output_dir: &Path,
+    _commit_message: &str,
+    _private: bool,
+) -> Result<()> {
+    Err(Error::FeatureDisabled(
+        "Uploading to the HuggingFace Hub",
+        "hub",
+    ))
+}
@@ -0,0 +1,40 @@
+use crate::dataset::ArrowDataset;
+use crate::error::Result;
+use std::path::Path;
+
+// Function to print a summary of every split of a converted dataset
+pub fn inspect_dataset(path: &Path) -> Result<()> {
+    // Open the dataset to locate and read the splits under the given path
+    let dataset = ArrowDataset::open(path)?;
+
+    // Report when no split could be found
+    if dataset.splits().is_empty() {
+        println!("No converted splits found in {:?}", path);
+        return Ok(());
+    }
+
+    // Print the summary of each split in turn
+    for split in dataset.splits() {
+        // Print the general information about the split
+        let info = split.info();
+        println!("Split {:?}", split.dir());
+        println!("  name:    {}", info.dataset_name);
+        println!("  type:    {}", info.dataset_type);
+        println!("  format:  {}", info.format);
+        println!("  samples: {}", info.num_samples);
+        if let Some(seed) = info.seed {
+            println!("  seed:    {}", seed);
+        }
+        println!("  chunks:  {}", split.chunk_paths().len());
+        println!("  bytes:   {}", split.size_bytes());
+
+        // Print each field of the schema read from the first chunk if there is one
+        if let Some(schema) = split.schema()? {
+            println!("  schema:");
+            for field in schema.fields() {
+                println!("    {}: {}", field.name(), field.data_type());
+            }
+        }
+    }
+    Ok(())
+}
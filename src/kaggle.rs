@@ -0,0 +1,132 @@
+use crate::error::{Error, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use log::info;
+use serde::Deserialize;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use ureq::Agent;
+
+// Define the endpoint of the Kaggle API serving the archive of a dataset
+const DOWNLOAD_URL: &str = "https://www.kaggle.com/api/v1/datasets/download";
+
+// Define the file marking a dataset as completely unpacked in the cache
+const COMPLETE_MARKER: &str = ".complete";
+
+// Define the credentials of the Kaggle API, as saved in kaggle.json
+#[derive(Deserialize)]
+struct KaggleCredentials {
+    username: String,
+    key: String,
+}
+
+// Function to get the home folder of the current user
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+// Function to get the folder holding kaggle.json, as the Kaggle CLI finds it
+fn config_dir() -> PathBuf {
+    env::var_os("KAGGLE_CONFIG_DIR")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".kaggle")))
+        .unwrap_or_default()
+}
+
+// Function to read the Kaggle API credentials from the KAGGLE_USERNAME and KAGGLE_KEY environment
+// variables, or else from kaggle.json
+fn read_credentials() -> Result<KaggleCredentials> {
+    if let (Ok(username), Ok(key)) = (env::var("KAGGLE_USERNAME"), env::var("KAGGLE_KEY")) {
+        return Ok(KaggleCredentials { username, key });
+    }
+    let path = config_dir().join("kaggle.json");
+    if !path.is_file() {
+        return Err(Error::KaggleCredentials(path));
+    }
+    let content = fs::read_to_string(&path).map_err(Error::io("Failed to read", &path))?;
+    serde_json::from_str(&content).map_err(Error::json(&path))
+}
+
+// Function to get the folder a dataset is unpacked into, under the user cache folder
+fn cache_dir(slug: &str) -> PathBuf {
+    env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".cache")))
+        .unwrap_or_default()
+        .join("arrow-datasets")
+        .join("kaggle")
+        .join(slug)
+}
+
+// Function to unpack a zip archive read as a stream, entry after entry, into a folder, skipping the
+// entries whose name would leave it
+fn unpack_zip_stream(reader: &mut impl Read, target_dir: &Path) -> io::Result<()> {
+    while let Some(mut entry) = zip::read::read_zipfile_from_stream(reader)? {
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        let path = target_dir.join(name);
+        if entry.is_dir() {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut File::create(&path)?)?;
+    }
+    Ok(())
+}
+
+// Function to download a Kaggle dataset, given by its "owner/name" slug, unpacking its archive
+// while it arrives into the user cache folder, and return the folder holding its splits; a
+// dataset unpacked by an earlier run is reused
+pub fn download_dataset(slug: &str) -> Result<PathBuf> {
+    // Reuse the dataset when an earlier download was unpacked completely
+    let target_dir = cache_dir(slug);
+    if !target_dir.join(COMPLETE_MARKER).is_file() {
+        // Start from an empty folder, dropping the files of an interrupted download
+        if target_dir.exists() {
+            fs::remove_dir_all(&target_dir).map_err(Error::io("Failed to remove", &target_dir))?;
+        }
+        fs::create_dir_all(&target_dir).map_err(Error::io("Failed to create", &target_dir))?;
+
+        // Request the archive with the API credentials
+        let credentials = read_credentials()?;
+        let authorization =
+            STANDARD.encode(format!("{}:{}", credentials.username, credentials.key));
+        let url = format!("{}/{}", DOWNLOAD_URL, slug);
+        info!("Downloading dataset {} from Kaggle...", slug);
+        let response = Agent::new_with_defaults()
+            .get(&url)
+            .header("Authorization", format!("Basic {}", authorization))
+            .call()
+            .map_err(|e| Error::io("Failed to download", Path::new(&url))(e.into_io()))?;
+
+        // Unpack the archive as it is downloaded, then mark the dataset as complete
+        let mut body = BufReader::new(response.into_body().into_reader());
+        unpack_zip_stream(&mut body, &target_dir).map_err(Error::io(
+            "Failed to unpack Kaggle dataset into",
+            &target_dir,
+        ))?;
+        let marker = target_dir.join(COMPLETE_MARKER);
+        File::create(&marker).map_err(Error::io("Failed to create", &marker))?;
+        info!("Unpacked dataset {} to {:?}", slug, target_dir);
+    }
+
+    // Use the single top-level folder as the dataset root when the archive wraps everything in one
+    let entries: Vec<PathBuf> = fs::read_dir(&target_dir)
+        .map_err(Error::io("Failed to read", &target_dir))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| !path.ends_with(COMPLETE_MARKER))
+        .collect();
+    Ok(match entries.as_slice() {
+        [single] if single.is_dir() => single.clone(),
+        _ => target_dir,
+    })
+}
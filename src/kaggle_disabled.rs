@@ -0,0 +1,10 @@
+use crate::error::{Error, Result};
+use std::path::PathBuf;
+
+// Function to report that downloading Kaggle datasets is unavailable in this build
+pub fn download_dataset(_slug: &str) -> Result<PathBuf> {
+    Err(Error::FeatureDisabled(
+        "Downloading Kaggle datasets",
+        "kaggle",
+    ))
+}
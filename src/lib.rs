@@ -0,0 +1,1530 @@
+// Allow the derive macros to refer to this crate by name from inside it
+extern crate self as arrow_datasets;
+
+pub mod batch;
+pub mod cancel;
+pub mod chunk_io;
+pub mod cli;
+pub mod computed;
+mod converter;
+pub mod dataset;
+pub mod dataset_card;
+mod defaults;
+pub mod error;
+pub mod export;
+pub mod ffi;
+pub mod filter;
+#[cfg_attr(not(feature = "hub"), path = "hub_disabled.rs")]
+pub mod hub;
+pub mod inspect;
+#[cfg_attr(not(feature = "kaggle"), path = "kaggle_disabled.rs")]
+pub mod kaggle;
+pub mod logging;
+#[cfg_attr(not(feature = "sqlite"), path = "manifest_db_disabled.rs")]
+pub mod manifest_db;
+pub mod merge;
+pub mod metadata;
+pub mod multi_label;
+pub mod output;
+pub mod profile;
+pub mod progress;
+#[cfg(feature = "python")]
+mod python;
+pub mod reader;
+pub mod record;
+#[cfg_attr(
+    not(any(feature = "azure", feature = "gcs", feature = "s3")),
+    path = "remote_output_disabled.rs"
+)]
+pub mod remote_output;
+pub mod report;
+pub mod reshard;
+mod resume;
+pub mod scan;
+pub mod schema;
+#[cfg_attr(not(feature = "flight"), path = "serve_disabled.rs")]
+pub mod serve;
+pub mod single_file;
+pub mod sink;
+pub mod source;
+pub mod split_config;
+pub mod stdout_output;
+pub mod synset;
+#[cfg(feature = "tfrecord")]
+mod tfrecord;
+pub mod transform;
+pub mod verify;
+#[cfg_attr(not(feature = "watch"), path = "watch_disabled.rs")]
+mod watch;
+
+pub use converter::DatasetConverter;
+pub use dataset::ArrowDataset;
+pub use error::{Error, Result};
+pub use reader::DatasetReader;
+pub use record::ArrowRecord;
+pub use source::{collect_image_paths, read_image_as_bytes};
+
+use cancel::CancellationToken;
+use cli::{ConvertArgs, InputFormat, ProgressFormat};
+use computed::ComputedColumn;
+use export::{ClassIds, ExportFormat, JsonlImages, SplitExports};
+use filter::PathFilter;
+use log::{error, info, warn};
+use manifest_db::ManifestDb;
+use metadata::{
+    save_chunk_index, save_metadata, Attribution, DatasetInfo, FileNameTemplate, SamplingRate,
+    SplitInfo,
+};
+use output::ExistingChunks;
+use profile::{IpcFormat, OutputSettings};
+use progress::{ProgressCallback, SplitProgress};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use remote_output::RemoteOutput;
+use report::{ConvertSummary, ErrorLog, SampleError};
+use resume::ResumePlan;
+use schema::{OutputSchema, SchemaSettings};
+use source::{DataSource, Record, SampleRef, SourceSettings};
+use split_config::SplitOverrides;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::io;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+use stdout_output::{SplitStream, STDOUT_OUTPUT};
+use transform::{Keep, Transform};
+
+// Define a struct to store the settings shared by every chunk of a conversion
+#[derive(Clone)]
+pub struct ChunkSettings {
+    pub chunk_size: usize,
+    pub target_chunk_bytes: Option<u64>,
+    pub file_name_template: FileNameTemplate,
+    pub attribution: Attribution,
+    pub thread_count: usize,
+    pub output: OutputSettings,
+    pub progress_format: ProgressFormat,
+    pub callback: Option<Arc<dyn ProgressCallback>>,
+    pub transforms: Vec<Arc<dyn Transform>>,
+    pub cancel: CancellationToken,
+    pub errors: ErrorLog,
+    pub schema: SchemaSettings,
+    pub computed: Vec<Arc<dyn ComputedColumn>>,
+    pub exports: Vec<ExportFormat>,
+    pub class_ids: ClassIds,
+    pub jsonl_images: JsonlImages,
+    pub export_only: bool,
+    pub manifest_db: bool,
+    pub verify_output: bool,
+    pub stdout: bool,
+    pub remote_output: Option<Arc<RemoteOutput>>,
+}
+
+impl ChunkSettings {
+    // Function to apply the overrides of a split to the settings of the conversion
+    pub fn for_split(&self, overrides: &SplitOverrides) -> ChunkSettings {
+        ChunkSettings {
+            chunk_size: overrides
+                .chunk_size
+                .map_or(self.chunk_size, NonZeroUsize::get),
+            schema: self.schema.with_constants(&overrides.constant_columns),
+            ..self.clone()
+        }
+    }
+}
+
+// Define a struct describing the samples of a split, the source they are loaded from and where
+// they are written
+pub struct SplitJob {
+    pub source: Arc<dyn DataSource>,
+    pub samples: Vec<SampleRef>,
+    pub output_dir: PathBuf,
+    pub dataset_name: String,
+}
+
+// Function to take a sample loaded from the source and run the transforms on it in order, returning
+// None when one of them drops it
+fn transform_sample(
+    loaded: io::Result<Record>,
+    sample: &SampleRef,
+    transforms: &[Arc<dyn Transform>],
+) -> Result<Option<Record>> {
+    // Check that the sample could be loaded from the source
+    let mut record = loaded.map_err(Error::io("Failed to read", &sample.0))?;
+
+    // Apply each transform, stopping as soon as one drops the sample
+    for transform in transforms {
+        if transform.apply(&mut record)? == Keep::No {
+            return Ok(None);
+        }
+    }
+
+    // Return the transformed sample
+    Ok(Some(record))
+}
+
+// Function to process samples in chunks and save them as Arrow files, recording the shuffle seed
+// in the metadata when there is one, and returning the number of rows written for the samples
+pub fn save_to_chunked_arrow(
+    job: SplitJob,
+    settings: &ChunkSettings,
+    seed: Option<u64>,
+    resume: bool,
+    existing: Option<ExistingChunks>,
+) -> Result<usize> {
+    // Unpack the samples, source and destination of the split
+    let SplitJob {
+        source,
+        samples: image_paths,
+        output_dir,
+        dataset_name,
+    } = job;
+    let output_dir = output_dir.as_path();
+    let dataset_name = dataset_name.as_str();
+
+    // Unpack the chunk size and thread count from the settings
+    let chunk_size = settings.chunk_size;
+    let thread_count = settings.thread_count;
+
+    // Calculate the total number of samples from the image paths vector
+    let total_samples = image_paths.len();
+
+    // Count the rows actually written, which excludes images that failed to read
+    let mut written_rows = 0;
+
+    // Count the chunks already present in the split when appending to it
+    let first_chunk = existing.as_ref().map_or(0, |e| e.filenames.len());
+
+    // Plan the samples of every new chunk and count the chunks, including the existing ones
+    let chunks = plan_chunks(
+        &*source,
+        &image_paths,
+        chunk_size,
+        settings.target_chunk_bytes,
+    );
+    let num_chunks = first_chunk + chunks.len();
+
+    // Name every chunk, including the existing ones, after the file name template and the split
+    let split = output_dir.file_name().unwrap_or_default().to_string_lossy();
+    let chunk_files =
+        settings
+            .file_name_template
+            .file_names(&split, num_chunks, settings.output.format);
+
+    // Record the rows, size and digest of every chunk for the index, the split size and the
+    // fingerprint, starting with the existing ones
+    let mut chunk_rows = vec![0; num_chunks];
+    let mut written_files = vec![WrittenFile::default(); num_chunks];
+    if let Some(existing) = &existing {
+        chunk_rows[..first_chunk].copy_from_slice(&existing.chunk_rows);
+        for (written, file_name) in written_files.iter_mut().zip(&existing.filenames) {
+            *written = WrittenFile::read(&output_dir.join(file_name))?;
+        }
+    }
+
+    // Create the shared schema of the chunks with the metadata columns of the source and the sample
+    // column of its kind of samples, wrapped in an Arc for thread safety
+    let mut schema_settings = settings
+        .schema
+        .with_metadata_columns(&source.metadata_columns());
+    schema_settings.sample_kind = source.sample_kind();
+    let schema = Arc::new(OutputSchema::new(&schema_settings, &settings.computed)?);
+
+    // Prepare the exports of the split, refusing samples the export formats have no layout for
+    let exports = Arc::new(SplitExports::new(
+        &settings.exports,
+        settings.output,
+        settings.jsonl_images,
+        schema_settings.sample_kind,
+        output_dir,
+        settings.class_ids.clone(),
+        estimate_output_bytes(&*source, &image_paths),
+        first_chunk,
+    )?);
+
+    // Refuse to append chunks whose columns differ from those of the existing chunks
+    if let Some(existing) = existing.as_ref().filter(|e| !e.filenames.is_empty()) {
+        let first_path = output_dir.join(&existing.filenames[0]);
+        if chunk_io::read_chunk_schema(&first_path)?.fields() != schema.schema().fields() {
+            return Err(Error::ChunkMismatch {
+                kind: "Schema",
+                path: first_path,
+            });
+        }
+    }
+
+    // Start the stream of the split when it is written to stdout, describing the split as listed
+    // since the schema is written before any sample is converted
+    let stream = settings
+        .stdout
+        .then(|| {
+            let info = DatasetInfo {
+                features: Some(schema.features().clone()),
+                attribution: settings.attribution.clone(),
+                ..DatasetInfo::new(
+                    dataset_name,
+                    schema_settings.sample_kind.dataset_type(),
+                    total_samples,
+                    settings.output.format,
+                    seed,
+                    &schema_settings.sample_columns(),
+                )
+            };
+            SplitStream::new(schema.schema(), &info, &settings.output).map(Arc::new)
+        })
+        .transpose()?;
+
+    // Create the manifest database of the split when requested, filled as the chunks finish
+    let manifest_db = settings
+        .manifest_db
+        .then(|| ManifestDb::create(output_dir).map(Arc::new))
+        .transpose()?;
+
+    // Print status message with dataset details
+    info!(
+        "Saving dataset '{}' with {} samples in {} chunks...",
+        dataset_name, total_samples, num_chunks
+    );
+
+    // Track the progress of the new chunks, shared with the worker threads
+    let progress = Arc::new(SplitProgress::new(
+        settings.progress_format,
+        settings.callback.clone(),
+        dataset_name,
+        total_samples,
+        first_chunk,
+        num_chunks,
+    ));
+
+    // Create a channel to signal thread completion
+    let (tx, rx) = mpsc::channel();
+
+    // Create the active thread count with a condition variable signalled when a worker finishes
+    let active_threads = Arc::new((Mutex::new(0), Condvar::new()));
+
+    // Count the chunks handed to worker threads
+    let mut spawned_chunks = 0;
+
+    // Gather the sampling rate shared by the audio samples of the split, starting with the one
+    // recorded for the existing samples when appending
+    let sampling_rate = Arc::new(Mutex::new(
+        existing
+            .as_ref()
+            .map_or(SamplingRate::Unknown, |existing| existing.sampling_rate),
+    ));
+
+    // Iterate over each chunk (with its index) from the image paths
+    for (offset, range) in chunks.into_iter().enumerate() {
+        // Keep the chunks converted for stdout close to the next one to write, so the chunks
+        // finished ahead of it and kept in memory are bounded by the thread count
+        if let Some(stream) = &stream {
+            stream.wait_for_room(first_chunk + offset, thread_count);
+        }
+
+        // Stop handing out chunks once the conversion has been cancelled
+        if settings.cancel.is_cancelled() {
+            break;
+        }
+
+        // Place the new chunk after any existing ones
+        let i = first_chunk + offset;
+
+        // Get the output file name of the current chunk, "data-00000-of-000XX.arrow" by default
+        let file_name = chunk_files[i].clone();
+
+        // Create the full file path in the output directory
+        let file_path = output_dir.join(&file_name);
+
+        // Skip chunks that were already written completely by an interrupted run
+        if resume {
+            if let Some(rows) = resume::valid_chunk_rows(&file_path) {
+                info!("Skipping existing chunk {} -> {:?}", i, file_path);
+                written_rows += rows;
+                chunk_rows[i] = rows;
+                written_files[i] = WrittenFile::read(&file_path)?;
+                exports.skip_chunk(i)?;
+
+                // Leave the sampling rate unrecorded, the samples of the chunk not being read again
+                let mut rate = sampling_rate.lock().unwrap();
+                *rate = rate.merge(SamplingRate::Mixed);
+                progress.chunk_skipped(rows);
+                continue;
+            }
+        }
+
+        // Clone the shared schema for use in the thread
+        let schema_clone = Arc::clone(&schema);
+
+        // Clone the sender for the thread
+        let tx_clone = tx.clone();
+
+        // Clone the active_threads Arc for the thread
+        let active_threads_clone = Arc::clone(&active_threads);
+
+        // Copy the samples of the current chunk to a vector
+        let chunk = image_paths[range].to_vec();
+
+        // Clone the source, progress tracker, error log and dataset name for the thread
+        let source = Arc::clone(&source);
+        let progress = Arc::clone(&progress);
+        let errors = Arc::clone(&settings.errors);
+        let transforms = settings.transforms.clone();
+        let output = settings.output;
+        let exports = Arc::clone(&exports);
+        let export_only = settings.export_only;
+        let manifest_db = manifest_db.clone();
+        let stream = stream.clone();
+        let sampling_rate = Arc::clone(&sampling_rate);
+        let remote_output = settings.remote_output.clone();
+        let cancel = settings.cancel.clone();
+        let dataset_name = dataset_name.to_string();
+
+        // Wait until fewer than thread_count threads are active, then count the new one
+        {
+            let (count, finished) = &*active_threads_clone;
+            let mut count = finished
+                .wait_while(count.lock().unwrap(), |count| *count >= thread_count)
+                .unwrap();
+            *count += 1;
+        }
+
+        // Spawn a new thread to process the current chunk
+        spawned_chunks += 1;
+        thread::spawn(move || {
+            // Report that the chunk is being converted
+            progress.chunk_started(i, chunk.len());
+
+            // Process the chunk by loading and transforming each sample, stopping early when the
+            // conversion is cancelled; skip the samples that are dropped or fail
+            let chunk_data: Vec<(&Path, Record)> = chunk
+                .iter()
+                .take_while(|_| !cancel.is_cancelled())
+                .zip(source.load_many(&chunk))
+                .filter_map(|(sample, loaded)| {
+                    match transform_sample(loaded, sample, &transforms) {
+                        Ok(record) => record.map(|record| (sample.0.as_path(), record)),
+                        Err(e) => {
+                            // Record samples that cannot be loaded or transformed before skipping them
+                            let path = &sample.0;
+                            warn!("Skipping sample {:?}: {}", path, e);
+                            let error = SampleError {
+                                dataset: dataset_name.clone(),
+                                path: path.clone(),
+                                reason: e.to_string(),
+                            };
+                            progress.sample_failed(&error);
+                            errors.lock().unwrap().push(error);
+                            None
+                        }
+                    }
+                })
+                .collect();
+
+            // Gather the sampling rates read from the headers of the loaded audio samples
+            let chunk_rate = chunk_data
+                .iter()
+                .fold(SamplingRate::Unknown, |rate, (_, record)| {
+                    rate.merge(source.sampling_rate(record).into())
+                });
+            let mut rate = sampling_rate.lock().unwrap();
+            *rate = rate.merge(chunk_rate);
+            drop(rate);
+
+            // Abandon the chunk without writing anything when the conversion was cancelled
+            let written = if cancel.is_cancelled() {
+                info!("Abandoned chunk {} after cancellation", i);
+                exports.skip_chunk(i).map(|_| (0, WrittenFile::default()))
+            } else {
+                // Write the samples to the exports written from samples before they are moved into
+                // the batch
+                exports
+                    .export_samples(i, &chunk_data)
+                    .and_then(|mut export_paths| {
+                        // Only build the RecordBatch, with one column per field of the output
+                        // schema, when it is written to the chunk file or exported
+                        let rows = chunk_data.len();
+                        if !export_only || exports.exports_batches() {
+                            // List the samples in the manifest database before they are moved
+                            // into the batch
+                            if let Some(manifest_db) = &manifest_db {
+                                manifest_db.add_chunk(i, &file_name, &chunk_data)?;
+                            }
+                            let batch = schema_clone.to_record_batch(chunk_data)?;
+                            export_paths.extend(exports.export_batch(i, &batch)?);
+
+                            // Append the RecordBatch to the stream of the split in chunk order
+                            // when it is written to stdout, without any chunk file
+                            if let Some(stream) = &stream {
+                                let bytes = batch.get_array_memory_size() as u64;
+                                stream.write_chunk(i, batch)?;
+                                info!("Converted chunk {} for stdout", i);
+                                progress.chunk_done(i, Path::new(STDOUT_OUTPUT), rows, bytes);
+                                return Ok((rows, WrittenFile::default()));
+                            }
+
+                            // Write the RecordBatch to the chunk file through the sink of the
+                            // output profile
+                            if !export_only {
+                                let mut sink =
+                                    sink::create_sink(&file_path, schema_clone.schema(), &output)?;
+                                sink.write_batch(&batch)?;
+                                sink.finalize()?;
+                                let written = WrittenFile::read(&file_path)?;
+                                upload_chunk_files(&remote_output, &file_path, &export_paths)?;
+
+                                // Print a message indicating the chunk has been saved
+                                info!("Saved chunk {} -> {:?}", i, file_path);
+
+                                // Report the completed chunk in the requested progress format
+                                progress.chunk_done(i, &file_path, rows, written.bytes);
+                                return Ok((rows, written));
+                            }
+                        }
+
+                        // Report the first exported file when no chunk file is written, reading
+                        // its size before it is uploaded
+                        let bytes = export_paths[0].metadata().map_or(0, |m| m.len());
+                        upload_chunk_files(&remote_output, &file_path, &export_paths)?;
+                        info!("Exported chunk {} -> {:?}", i, export_paths[0]);
+                        progress.chunk_done(i, &export_paths[0], rows, bytes);
+
+                        // Return the number of written rows, without a chunk file
+                        Ok((rows, WrittenFile::default()))
+                    })
+            };
+
+            // Release the stream to stdout from waiting for the chunk when it failed or was abandoned
+            if let Some(stream) = &stream {
+                if written.is_err() || cancel.is_cancelled() {
+                    stream.abandon_chunk();
+                }
+            }
+
+            // Signal completion by sending the chunk index with the number of written rows and the
+            // size and digest of the chunk file, or the error that stopped the chunk, through the
+            // channel
+            tx_clone.send((i, written)).unwrap();
+
+            // Decrement the active thread count and wake the loop waiting for a free thread
+            let (count, finished) = &*active_threads_clone;
+            *count.lock().unwrap() -= 1;
+            finished.notify_one();
+        });
+    }
+
+    // Drop the original sender so a worker that panics cannot leave the loop below waiting forever
+    drop(tx);
+
+    // Wait for all spawned threads to finish processing by receiving a signal for each chunk,
+    // keeping the first error so the other chunks still complete
+    let mut first_error = None;
+    for _ in 0..spawned_chunks {
+        match rx
+            .recv()
+            .map_err(|_| Error::WorkerFailed)
+            .and_then(|(i, written)| Ok((i, written?)))
+        {
+            Ok((i, (rows, written))) => {
+                written_rows += rows;
+                chunk_rows[i] = rows;
+                written_files[i] = written;
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    // Stop drawing the progress bar of the split
+    progress.finish();
+
+    // Fail the split without writing its metadata when a chunk could not be written
+    if let Some(e) = first_error {
+        return Err(e);
+    }
+
+    // End the stream of a split written to stdout after the chunks converted before any cancelled
+    // one
+    if let Some(stream) = &stream {
+        stream.finish()?;
+    }
+
+    // Leave the metadata untouched when cancelled, removing the new chunks of an append so the
+    // split stays as it was; the chunks of a fresh conversion are kept for --resume
+    if settings.cancel.is_cancelled() {
+        if existing.is_some() {
+            for file_name in &chunk_files[first_chunk..] {
+                let _ = fs::remove_file(output_dir.join(file_name));
+            }
+        }
+        warn!("Conversion of '{}' was cancelled", dataset_name);
+        return Ok(written_rows);
+    }
+
+    // Leave the split without any file when it was streamed to stdout
+    if stream.is_some() {
+        return Ok(written_rows);
+    }
+
+    // Write the files describing the exports of the whole split
+    exports.finish()?;
+
+    // Leave the split without metadata when only the exported files were written
+    if settings.export_only {
+        return Ok(written_rows);
+    }
+
+    // Rename the existing chunks to the new total and count their samples when appending,
+    // in which case no single seed reproduces the sample order anymore
+    let (existing_samples, recorded_seed) = match existing {
+        Some(existing) => {
+            output::renumber_chunks(output_dir, &existing.filenames, &chunk_files)?;
+            (existing.num_samples, None)
+        }
+        None => (0, seed),
+    };
+
+    // Number the samples of the manifest database now that the rows of every chunk are known
+    if let Some(manifest_db) = &manifest_db {
+        manifest_db.finish(&chunk_rows)?;
+    }
+
+    // Save the dataset metadata and state after all chunks are processed, describing every column
+    // as a HuggingFace feature matching the schema of the chunks, with the sampling rate shared by
+    // the audio samples, and the size of the split
+    let split_info = SplitInfo {
+        name: split.to_string(),
+        num_bytes: written_files.iter().map(|written| written.bytes).sum(),
+        num_examples: existing_samples + written_rows,
+        shard_lengths: chunk_rows.clone(),
+    };
+    let sampling_rate = *sampling_rate.lock().unwrap();
+    let metadata = DatasetInfo {
+        features: Some(schema.features().clone().with_sampling_rate(sampling_rate)),
+        splits: Some(BTreeMap::from([(split.to_string(), split_info)])),
+        attribution: settings.attribution.clone(),
+        ..DatasetInfo::new(
+            dataset_name,
+            schema_settings.sample_kind.dataset_type(),
+            existing_samples + written_rows,
+            settings.output.format,
+            recorded_seed,
+            &schema_settings.sample_columns(),
+        )
+    };
+    let digests: Vec<String> = written_files
+        .into_iter()
+        .map(|written| written.sha256)
+        .collect();
+    save_metadata(
+        output_dir,
+        &metadata,
+        &chunk_files,
+        &digests,
+        settings.output.format,
+    )?;
+
+    // Save the row count of every chunk so samples can be looked up by index
+    save_chunk_index(output_dir, &chunk_rows)?;
+    if settings.output.fsync {
+        sink::sync_files(
+            output_dir,
+            &["dataset_info.json", "state.json", "index.json"],
+        )?;
+    }
+
+    // Re-open every chunk of the split to catch truncated or misformatted files before it ships
+    if settings.verify_output {
+        verify::verify_split_output(output_dir, schema.schema(), &settings.output, first_chunk)?;
+    }
+
+    // Return the number of rows written, which excludes the samples that failed or were dropped
+    Ok(written_rows)
+}
+
+// Define the size and SHA-256 digest of a written chunk file, read before it is moved to the object
+// storage output, from which the size and fingerprint of the split are computed
+#[derive(Clone, Default)]
+struct WrittenFile {
+    bytes: u64,
+    sha256: String,
+}
+
+impl WrittenFile {
+    // Function to read the size and digest of the chunk file at the given path
+    fn read(path: &Path) -> Result<Self> {
+        let bytes = path
+            .metadata()
+            .map_err(Error::io("Failed to read", path))?
+            .len();
+        let sha256 = metadata::file_sha256(path).map_err(Error::io("Failed to read", path))?;
+        Ok(WrittenFile { bytes, sha256 })
+    }
+}
+
+// Function to move the chunk file and exported shards of a finished chunk to the object storage
+// output, if any, leaving the exports that keep growing with the split, such as LMDB environments
+// and Lance datasets, to be uploaded with the metadata of the split
+fn upload_chunk_files(
+    remote_output: &Option<Arc<RemoteOutput>>,
+    file_path: &Path,
+    export_paths: &[PathBuf],
+) -> Result<()> {
+    let Some(remote_output) = remote_output else {
+        return Ok(());
+    };
+    for path in export_paths.iter().map(PathBuf::as_path).chain([file_path]) {
+        if path.is_file() {
+            remote_output.upload_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+// Function to estimate the size in bytes of the Arrow data produced for a sample
+fn estimate_sample_bytes(source: &dyn DataSource, sample: &SampleRef) -> u64 {
+    // Ask the source for the sample size without loading it
+    let image_bytes = source.size_hint(sample);
+
+    // Add the label bytes and the offsets of both variable-length columns
+    image_bytes + sample.1.len() as u64 + 8
+}
+
+// Function to estimate the size in bytes of the Arrow data produced for the given samples
+fn estimate_output_bytes(source: &dyn DataSource, image_paths: &[SampleRef]) -> u64 {
+    image_paths
+        .iter()
+        .map(|sample| estimate_sample_bytes(source, sample))
+        .sum()
+}
+
+// Function to split the samples into the ranges of their chunks: runs of at most chunk_size
+// samples that, with a target chunk size, also end before their estimated size exceeds the target,
+// whichever limit is reached first. The chunks are planned before any of them is written, so every
+// file name carries the final number of chunks; a sample larger than the target gets a chunk of its
+// own
+fn plan_chunks(
+    source: &dyn DataSource,
+    image_paths: &[SampleRef],
+    chunk_size: usize,
+    target_chunk_bytes: Option<u64>,
+) -> Vec<Range<usize>> {
+    let Some(target_chunk_bytes) = target_chunk_bytes else {
+        return (0..image_paths.len())
+            .step_by(chunk_size)
+            .map(|start| start..(start + chunk_size).min(image_paths.len()))
+            .collect();
+    };
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut chunk_bytes = 0;
+    for (i, sample) in image_paths.iter().enumerate() {
+        let sample_bytes = estimate_sample_bytes(source, sample);
+        if i - start == chunk_size || (i > start && chunk_bytes + sample_bytes > target_chunk_bytes)
+        {
+            chunks.push(start..i);
+            start = i;
+            chunk_bytes = 0;
+        }
+        chunk_bytes += sample_bytes;
+    }
+    if start < image_paths.len() {
+        chunks.push(start..image_paths.len());
+    }
+    chunks
+}
+
+// Function to report what converting a split would produce without writing anything
+fn report_planned_split(
+    split: &str,
+    source: &dyn DataSource,
+    image_paths: &[SampleRef],
+    chunk_size: usize,
+    target_chunk_bytes: Option<u64>,
+) {
+    // Calculate the number of samples and chunks of the split
+    let total_samples = image_paths.len();
+    let num_chunks = plan_chunks(source, image_paths, chunk_size, target_chunk_bytes).len();
+
+    // Estimate the total size of the chunk files
+    let estimated_bytes = estimate_output_bytes(source, image_paths);
+
+    // Print the planned output of the split
+    println!(
+        "Split '{}': {} samples in {} chunks, ~{} bytes",
+        split, total_samples, num_chunks, estimated_bytes
+    );
+}
+
+// Function to find the splits of the images of a path list: the top-level folders of the dataset
+// root holding them, warning about the listed paths outside of every split folder
+fn listed_splits(dataset_path: &Path, paths: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut splits: Vec<(String, PathBuf)> = Vec::new();
+    let mut outside = 0;
+    for path in paths {
+        // Take the first folder of the path within the dataset root, if the path is below one
+        let relative = path.strip_prefix(dataset_path).ok();
+        let folder = relative
+            .filter(|relative| relative.components().count() > 1)
+            .and_then(|relative| relative.components().next())
+            .and_then(|component| component.as_os_str().to_str());
+        let Some(folder) = folder else {
+            outside += 1;
+            continue;
+        };
+        if !splits.iter().any(|(name, _)| name == folder) {
+            splits.push((folder.to_string(), dataset_path.join(folder)));
+        }
+    }
+    if outside > 0 {
+        warn!(
+            "{} listed paths are not in a split folder of {:?} and are skipped",
+            outside, dataset_path
+        );
+    }
+
+    // Sort the splits by name so they are processed in a stable order
+    splits.sort();
+    splits
+}
+
+// Function to discover the splits directly under the dataset root: every subdirectory, and for
+// archive and manifest inputs every such file, named after its file stem
+pub fn discover_splits(dataset_path: &Path, format: InputFormat) -> Result<Vec<(String, PathBuf)>> {
+    // List the splits of object storage inputs from the key prefixes under the URL
+    if let Some(url) = source::remote_url(dataset_path) {
+        return source::discover_remote_splits(url);
+    }
+
+    // Name the splits of COCO and VOC datasets after their annotation and image set files, the
+    // splits of Parquet and TFRecord datasets after their folders or shard names, and the splits of
+    // LMDB datasets after the folders holding their databases, instead
+    match format {
+        InputFormat::Coco => return source::discover_coco_splits(dataset_path),
+        InputFormat::Voc => return source::discover_voc_splits(dataset_path),
+        InputFormat::Parquet => return source::discover_parquet_splits(dataset_path),
+        InputFormat::Lmdb => return source::discover_lmdb_splits(dataset_path),
+        InputFormat::Tfrecord => return source::discover_tfrecord_splits(dataset_path),
+        _ => {}
+    }
+
+    // Collect every direct subdirectory, or archive, of the dataset root with its name
+    let mut splits: Vec<(String, PathBuf)> = fs::read_dir(dataset_path)
+        .map_err(Error::io("Failed to read", dataset_path))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            // Use the directory name or the archive stem as the split name
+            let name = if path.is_dir() {
+                // Skip folders holding no archive or manifest, such as the images of a manifest
+                if !matches!(
+                    format,
+                    InputFormat::Folder
+                        | InputFormat::Text
+                        | InputFormat::Audio
+                        | InputFormat::Numpy
+                        | InputFormat::Paired
+                        | InputFormat::Video
+                ) && source::find_input_files(format, &path).is_empty()
+                {
+                    return None;
+                }
+                path.file_name()?
+            } else if path.is_file() && source::is_input_file(format, &path) {
+                path.file_stem()?
+            } else {
+                return None;
+            };
+            Some((name.to_str()?.to_string(), path))
+        })
+        .collect();
+
+    // Sort the splits by name so they are processed in a stable order
+    splits.sort();
+
+    // Return the discovered splits
+    Ok(splits)
+}
+
+// Function to convert every split of a dataset into Arrow files, notifying the optional callback
+// of the progress, adding the given computed columns, stopping early when the token is cancelled,
+// and returning the sample counts
+pub(crate) fn run_convert(
+    args: &ConvertArgs,
+    callback: Option<Arc<dyn ProgressCallback>>,
+    transforms: Vec<Arc<dyn Transform>>,
+    computed: Vec<Arc<dyn ComputedColumn>>,
+    cancel: CancellationToken,
+) -> Result<ConvertSummary> {
+    // Resolve the thread count and chunk size, deriving defaults from the machine resources
+    let thread_count = args
+        .threads
+        .map_or_else(defaults::default_threads, NonZeroUsize::get);
+    let chunk_size = args.chunk_size.map_or_else(
+        || defaults::default_chunk_size(thread_count),
+        NonZeroUsize::get,
+    );
+
+    // Define the input dataset path, downloading Hub and Kaggle datasets into the local cache first;
+    // image folders only fetch the images with a converted extension from the Hub
+    let downloaded = if let Some(repo_id) = source::hub_repo_id(&args.input) {
+        let extensions = match args.input_format {
+            InputFormat::Folder => args.extensions.as_slice(),
+            _ => &[],
+        };
+        Some(hub::download_dataset(
+            repo_id,
+            args.hub_token.as_deref(),
+            extensions,
+            thread_count,
+        )?)
+    } else if let Some(slug) = source::kaggle_slug(&args.input) {
+        Some(kaggle::download_dataset(slug)?)
+    } else {
+        None
+    };
+    let dataset_path = downloaded.as_deref().unwrap_or(&args.input);
+
+    // Refuse to watch object storage, which sends no filesystem notifications
+    if args.watch && source::remote_url(dataset_path).is_some() {
+        return Err(Error::Unsupported(
+            "Watching object storage inputs".to_string(),
+        ));
+    }
+
+    // Stage the output in a local folder when it is stored in object storage, moving every file
+    // there once it is finished, which leaves no earlier chunks to append to or resume from
+    let remote_output = source::remote_url(&args.output)
+        .map(RemoteOutput::open)
+        .transpose()?
+        .map(Arc::new);
+    if remote_output.is_some() {
+        if args.append || args.resume || args.watch {
+            return Err(Error::Unsupported(
+                "Appending to, resuming or watching object storage outputs".to_string(),
+            ));
+        }
+        if args.push_to_hub.is_some() {
+            return Err(Error::Unsupported(
+                "Uploading object storage outputs to the HuggingFace Hub".to_string(),
+            ));
+        }
+        if args.dataset_card {
+            return Err(Error::Unsupported(
+                "Writing dataset cards of object storage outputs".to_string(),
+            ));
+        }
+        if args.verify_output {
+            return Err(Error::Unsupported(
+                "Verifying the chunks of object storage outputs".to_string(),
+            ));
+        }
+    }
+
+    // Refuse to write single files for the conversions that keep adding to the chunks of a split,
+    // that export files into the split directory removed once its single file is written, or that
+    // move the chunks elsewhere as soon as they are written
+    if args.single_file
+        && (args.append
+            || args.resume
+            || args.watch
+            || !args.exports.is_empty()
+            || args.export_only
+            || args.manifest_db
+            || remote_output.is_some())
+    {
+        return Err(Error::Unsupported(
+            "Writing single files when appending, resuming, watching, exporting, writing a manifest database or writing to object storage".to_string(),
+        ));
+    }
+
+    // Stream the chunks to stdout as they are converted when requested, which writes no file to
+    // append to, resume from or gather afterwards
+    let stdout = stdout_output::is_stdout_output(&args.output);
+    if stdout {
+        if args.append
+            || args.resume
+            || args.watch
+            || args.single_file
+            || !args.exports.is_empty()
+            || args.export_only
+            || args.manifest_only
+            || args.manifest_db
+            || args.dataset_card
+            || args.dataset_dict
+            || args.verify_output
+            || args.push_to_hub.is_some()
+        {
+            return Err(Error::Unsupported(
+                "Appending, resuming, watching, writing single files, exports, manifests, dataset cards, dataset dicts, output verification or uploads to the HuggingFace Hub when writing to stdout".to_string(),
+            ));
+        }
+        if matches!(args.progress_format, ProgressFormat::Json) {
+            return Err(Error::Unsupported(
+                "Printing JSON progress records when writing to stdout".to_string(),
+            ));
+        }
+    }
+
+    // Define the output path for the Arrow dataset
+    let output_path = remote_output
+        .as_deref()
+        .map(RemoteOutput::staging_dir)
+        .unwrap_or(args.output.as_path());
+
+    // Read the per-split overrides from the config file, if one is given
+    let split_config = args
+        .config
+        .as_deref()
+        .map(split_config::read_split_config)
+        .transpose()?
+        .unwrap_or_default();
+
+    // Read the images to convert from the path list, only taken by image folder inputs
+    let listed_paths = match &args.paths_from {
+        Some(_) if args.input_format != InputFormat::Folder => {
+            return Err(Error::Unsupported(
+                "Reading a path list with inputs other than image folders".to_string(),
+            ))
+        }
+        Some(path) => Some(Arc::new(source::read_path_list(path, dataset_path)?)),
+        None => None,
+    };
+
+    // Read the names of the integer labels, or of the classes of multi-hot labels
+    let label_names = match &args.label_names {
+        Some(path) => source::read_label_names(path)?,
+        None => Vec::new(),
+    };
+
+    // Name the WordNet ID labels in a column placed before the other computed columns
+    let mut computed = computed;
+    if let Some(synset_map) = &args.synset_map {
+        let synsets = synset::read_synset_map(synset_map)?;
+        computed.insert(0, Arc::new(synset::class_name_column(synsets)));
+    }
+
+    // Split delimited labels into a list or multi-hot column placed first among the computed
+    // columns, multi-hot vectors needing the classes they are made of
+    if args.multi_hot && (args.label_delimiter.is_none() || label_names.is_empty()) {
+        return Err(Error::Unsupported(
+            "Multi-hot labels without a label delimiter and label names".to_string(),
+        ));
+    }
+    if let Some(delimiter) = &args.label_delimiter {
+        let labels: Arc<dyn ComputedColumn> = if args.multi_hot {
+            Arc::new(multi_label::multi_hot_column(
+                delimiter.clone(),
+                &label_names,
+            ))
+        } else {
+            Arc::new(multi_label::labels_column(delimiter.clone()))
+        };
+        computed.insert(0, labels);
+    }
+
+    // Group the settings shared by every chunk of the conversion, taking the schema from the
+    // config file when it has one
+    let mut settings = ChunkSettings {
+        chunk_size,
+        target_chunk_bytes: args.target_chunk_bytes.map(NonZeroU64::get),
+        file_name_template: args.file_name_template.clone(),
+        attribution: split_config.attribution(),
+        thread_count,
+        output: OutputSettings {
+            fsync: args.fsync,
+            ..profile::resolve_output_settings(
+                args.profile,
+                args.format,
+                args.compression,
+                args.row_group_size,
+                args.ipc_format
+                    .or(args.dataset_dict.then_some(IpcFormat::Stream)),
+            )
+        },
+        progress_format: progress::resolve_format(args.progress_format),
+        callback,
+        transforms,
+        cancel,
+        errors: ErrorLog::default(),
+        schema: split_config
+            .schema
+            .clone()
+            .unwrap_or_else(|| args.schema.clone()),
+        computed,
+        exports: args.exports.clone(),
+        class_ids: ClassIds::default(),
+        jsonl_images: args.jsonl_images,
+        export_only: args.export_only,
+        manifest_db: args.manifest_db,
+        verify_output: args.verify_output,
+        stdout,
+        remote_output: remote_output.clone(),
+    };
+
+    // Refuse chunk file names that would be read back in another format
+    settings
+        .file_name_template
+        .check_format(settings.output.format)?;
+
+    // Refuse to list the splits for datasets.load_from_disk when it cannot read their chunks, which
+    // it memory-maps as Arrow IPC streams
+    if args.dataset_dict
+        && !matches!(
+            (settings.output.format, settings.output.ipc_format),
+            (chunk_io::ChunkFormat::Arrow, IpcFormat::Stream)
+        )
+    {
+        return Err(Error::Unsupported(
+            "Loading chunks other than Arrow IPC streams with datasets.load_from_disk".to_string(),
+        ));
+    }
+
+    // Compile the extensions and the include and exclude patterns applied during the directory walk,
+    // and set how URL lists are downloaded
+    let source_settings = SourceSettings {
+        filter: PathFilter::new(&args.extensions, &args.include, &args.exclude)?,
+        download_concurrency: args.download_concurrency.get(),
+        download_timeout: Duration::from_secs(args.download_timeout),
+        label_names,
+        video_frames: args.video_frames.map(NonZeroUsize::get),
+        captions: args.captions,
+        label_hierarchy: args.label_hierarchy,
+        pair_folders: args.pair_folders.clone(),
+        hdf5_images: args.hdf5_images.clone(),
+        hdf5_labels: args.hdf5_labels.clone(),
+        hdf5_columns: args.hdf5_columns.clone(),
+        listed_paths,
+    };
+
+    // Use the explicitly declared splits, or the top-level folders holding the listed images, or
+    // else discover them from the top-level input folders
+    let splits = if !args.splits.is_empty() {
+        args.splits.clone()
+    } else if let Some(paths) = &source_settings.listed_paths {
+        listed_splits(dataset_path, paths)
+    } else {
+        discover_splits(dataset_path, args.input_format)?
+    };
+
+    // Refuse to stream several splits to stdout, which holds a single Arrow IPC stream
+    if stdout && splits.len() != 1 {
+        return Err(Error::Unsupported(format!(
+            "Writing the {} splits of {:?} to stdout",
+            splits.len(),
+            dataset_path
+        )));
+    }
+
+    // Warn about overrides for splits that are not converted, which usually indicates a typo
+    for split in split_config.splits.keys() {
+        if !splits.iter().any(|(name, _)| name == split) {
+            warn!("Config file overrides unknown split '{}'", split);
+        }
+    }
+
+    // Name the classes of class label ids and of the integer labels of exports after the label
+    // names, refusing labels found in a split that are not among them for class label ids, or else
+    // after the classes already numbered in the output when appending followed by the other labels
+    // found in every split, sorted, so the same label has the same id in every split and run
+    let export_class_ids = settings
+        .exports
+        .iter()
+        .any(|format| format.stores_class_ids());
+    if (settings.schema.class_labels || export_class_ids) && !args.dry_run && !args.manifest_only {
+        let mut labels = BTreeSet::new();
+        for (split, split_path) in &splits {
+            let source = source::open_source(args.input_format, split_path, &source_settings)?;
+            let scan_progress = progress::scan_bar(settings.progress_format, split);
+            labels.extend(
+                source
+                    .list(&scan_progress)?
+                    .into_iter()
+                    .map(|(_, label)| label),
+            );
+            scan_progress.finish_and_clear();
+        }
+        let class_names = match &args.label_names {
+            Some(path) => {
+                let unknown: Vec<String> = labels
+                    .into_iter()
+                    .filter(|label| !source_settings.label_names.contains(label))
+                    .collect();
+                if settings.schema.class_labels && !unknown.is_empty() {
+                    return Err(Error::UnknownLabels {
+                        path: path.clone(),
+                        labels: unknown,
+                    });
+                }
+                source_settings.label_names.clone()
+            }
+            None => {
+                let mut names = Vec::new();
+                let mut numbered = HashSet::new();
+                if args.append {
+                    for (split, _) in &splits {
+                        for name in output::read_class_names(&output_path.join(split))? {
+                            if numbered.insert(name.clone()) {
+                                names.push(name);
+                            }
+                        }
+                    }
+                }
+                names.extend(labels.into_iter().filter(|label| !numbered.contains(label)));
+                names
+            }
+        };
+        settings.class_ids = export::class_ids(&class_names);
+        if settings.schema.class_labels {
+            settings.schema.class_names = class_names;
+        }
+    }
+
+    // Check the output schema of every split before converting any of them
+    for (split, _) in &splits {
+        let split_settings = settings.for_split(&split_config.overrides(split));
+        OutputSchema::new(&split_settings.schema, &split_settings.computed)?;
+    }
+
+    // Track the number of samples found in a dry run, or written to the chunks otherwise
+    let mut total_samples = 0;
+
+    // Start watching before the initial scan so no file added during the conversion is missed
+    let watcher = (args.watch && !args.dry_run)
+        .then(|| watch::start_watcher(&splits))
+        .transpose()?;
+
+    // Track the images scanned in each split so watch mode only converts new ones
+    let mut known_paths: Vec<HashSet<PathBuf>> = Vec::new();
+
+    // Only scan and report the planned output when running in dry-run mode
+    if args.dry_run {
+        for (split, split_path) in &splits {
+            // Print a message indicating scanning of the split
+            info!("Scanning {} dataset...", split);
+
+            // List the samples of the split
+            let source = source::open_source(args.input_format, split_path, &source_settings)?;
+            let scan_progress = progress::scan_bar(settings.progress_format, split);
+            let mut image_paths = source.list(&scan_progress)?;
+            scan_progress.finish_and_clear();
+
+            // Shuffle the samples like the conversion does, unless the config keeps the split in
+            // sorted order, then cap them at the maximum sample count, so the chunks are planned
+            // over the same samples
+            let overrides = split_config.overrides(split);
+            if overrides.shuffle.unwrap_or(true) {
+                let seed = args.seed.unwrap_or_else(rand::random);
+                image_paths.shuffle(&mut StdRng::seed_from_u64(seed));
+            }
+            if let Some(max_samples) = args.max_samples {
+                image_paths.truncate(max_samples.get());
+            }
+
+            // Report the planned chunks of the split with its own chunk size
+            let split_settings = settings.for_split(&overrides);
+            report_planned_split(
+                split,
+                &*source,
+                &image_paths,
+                split_settings.chunk_size,
+                split_settings.target_chunk_bytes,
+            );
+
+            // Accumulate the number of samples of the split
+            total_samples += image_paths.len();
+        }
+        return Ok(ConvertSummary {
+            samples: total_samples,
+            failed_samples: 0,
+            cancelled: false,
+        });
+    }
+
+    // Refuse to mix new chunks with the files of an earlier run unless explicitly requested
+    let non_empty = match &remote_output {
+        Some(remote_output) => remote_output.is_non_empty()?,
+        None => !stdout && output::is_non_empty_dir(output_path),
+    };
+    if non_empty && !(args.force || args.append || args.resume) {
+        return Err(Error::OutputNotEmpty(args.output.clone()));
+    }
+
+    // Create the output directory if it does not exist, unless the chunks are streamed to stdout
+    if !stdout {
+        fs::create_dir_all(output_path).map_err(Error::io("Failed to create", output_path))?;
+    }
+
+    // Only write the manifest of every split, without reading any sample, in manifest-only mode
+    if args.manifest_only {
+        let mut scans = BTreeMap::new();
+        for (split, split_path) in &splits {
+            // Print a message indicating scanning of the split
+            info!("Scanning {} dataset...", split);
+
+            // List the samples of the split, capped at the maximum sample count
+            let source = source::open_source(args.input_format, split_path, &source_settings)?;
+            let scan_progress = progress::scan_bar(settings.progress_format, split);
+            let mut image_paths = source.list(&scan_progress)?;
+            scan_progress.finish_and_clear();
+            if let Some(max_samples) = args.max_samples {
+                image_paths.truncate(max_samples.get());
+            }
+
+            // Write the manifest of the split and keep its counts for the summary
+            let scan = scan::write_split_manifest(output_path, split, &*source, &image_paths)?;
+            info!(
+                "Split '{}': {} samples in {} classes, {} bytes",
+                split,
+                scan.samples,
+                scan.classes.len(),
+                scan.bytes
+            );
+            total_samples += scan.samples;
+            scans.insert(split.clone(), scan);
+        }
+        scan::write_scan_summary(output_path, &scans)?;
+
+        // Move the manifests and their summary to the object storage output
+        if let Some(remote_output) = &remote_output {
+            remote_output.upload_dir(output_path)?;
+            remote_output.remove_staging_dir();
+        }
+        return Ok(ConvertSummary {
+            samples: total_samples,
+            failed_samples: 0,
+            cancelled: false,
+        });
+    }
+
+    // Convert each split into its own output folder
+    let mut card_classes = BTreeSet::new();
+    let mut converted_splits = Vec::new();
+    for (split, split_path) in &splits {
+        // Stop before starting another split once the conversion has been cancelled
+        if settings.cancel.is_cancelled() {
+            break;
+        }
+
+        // Apply the overrides of the split from the config file
+        let overrides = split_config.overrides(split);
+        let split_settings = settings.for_split(&overrides);
+        let shuffle = overrides.shuffle.unwrap_or(true);
+
+        // Print a message indicating scanning of the split
+        info!("Scanning {} dataset...", split);
+
+        // List the samples of the split while counting them on a spinner
+        let source = source::open_source(args.input_format, split_path, &source_settings)?;
+        let scan_progress = progress::scan_bar(settings.progress_format, split);
+        let mut image_paths = source.list(&scan_progress)?;
+        scan_progress.finish_and_clear();
+
+        // Remember the classes of the split for the dataset card
+        if args.dataset_card {
+            card_classes.extend(image_paths.iter().map(|(_, label)| label.clone()));
+        }
+
+        // Remember the scanned images when watching for new ones
+        if watcher.is_some() {
+            known_paths.push(image_paths.iter().map(|(path, _)| path.clone()).collect());
+        }
+
+        // Create the output directory for the split, unless its chunks are streamed to stdout
+        let split_output = output_path.join(split);
+        if !stdout {
+            fs::create_dir_all(&split_output)
+                .map_err(Error::io("Failed to create", &split_output))?;
+        }
+
+        // Remove the files of an earlier run when overwriting
+        if args.force && !stdout {
+            output::clear_split_output(&split_output, &split_settings.file_name_template)?;
+            if let Some(remote_output) = &remote_output {
+                remote_output.clear_split(split, &split_settings.file_name_template)?;
+            }
+        }
+
+        // Read the chunks already in the split when appending to it
+        let existing = if args.append {
+            output::read_existing_chunks(&split_output)?
+        } else {
+            None
+        };
+
+        // Refuse to append chunks in a different format than the existing ones
+        if let Some(existing) = &existing {
+            if existing.format != settings.output.format {
+                return Err(Error::FormatMismatch(split_output));
+            }
+        }
+
+        // Calculate the number of samples kept after applying the maximum sample count
+        let num_samples = args
+            .max_samples
+            .map_or(image_paths.len(), |max| image_paths.len().min(max.get()));
+
+        // Decide how to convert the split, reusing the recorded seed when resuming so chunks line up,
+        // or drawing a fresh seed without any resume file when streaming to stdout
+        let plan = if stdout {
+            ResumePlan::Fresh(args.seed.unwrap_or_else(rand::random))
+        } else {
+            resume::prepare_resume_state(
+                &split_output,
+                args.resume,
+                num_samples,
+                split_settings.chunk_size,
+                split_settings.target_chunk_bytes,
+                &split_settings.file_name_template,
+                args.seed,
+            )?
+        };
+        let (seed, resuming) = match plan {
+            ResumePlan::Fresh(seed) => (seed, false),
+            ResumePlan::Resume(seed) => (seed, true),
+            ResumePlan::Complete => {
+                total_samples += num_samples;
+                converted_splits.push(split.clone());
+                continue;
+            }
+        };
+
+        // Shuffle the image paths using a random number generator seeded for this split, unless
+        // the config keeps it in sorted order
+        if shuffle {
+            info!("Shuffling {} dataset...", split);
+            image_paths.shuffle(&mut StdRng::seed_from_u64(seed));
+        }
+
+        // Keep only the first samples after shuffling so the subset is representative
+        image_paths.truncate(num_samples);
+
+        // Print a message indicating saving of the split
+        info!("Saving {} dataset...", split);
+
+        // Process and save the split in chunks
+        let job = SplitJob {
+            source,
+            samples: image_paths,
+            output_dir: split_output.clone(),
+            dataset_name: format!("{}-{}", args.name, split),
+        };
+        total_samples += save_to_chunked_arrow(
+            job,
+            &split_settings,
+            shuffle.then_some(seed),
+            resuming,
+            existing,
+        )?;
+
+        // Keep the resume file of a cancelled split so it can be resumed later
+        if settings.cancel.is_cancelled() {
+            break;
+        }
+
+        // Remove the resume file now that the split is complete
+        resume::clear_resume_state(&split_output);
+        converted_splits.push(split.clone());
+
+        // Gather the chunks of the split into a single file when requested
+        if args.single_file {
+            single_file::write_single_file(&split_output, &settings.output)?;
+        }
+
+        // Move the metadata of the split and its growing exports to the object storage output
+        if let Some(remote_output) = &remote_output {
+            remote_output.upload_dir(&split_output)?;
+        }
+    }
+
+    // List the converted splits for datasets.load_from_disk once every split is complete
+    if args.dataset_dict && !settings.cancel.is_cancelled() {
+        metadata::save_dataset_dict(output_path, &converted_splits)?;
+    }
+
+    // Write the report listing every sample that failed to convert, which are only logged when
+    // streaming to stdout
+    let failed_samples = if stdout {
+        settings.errors.lock().unwrap().len()
+    } else {
+        report::write_error_report(output_path, &settings.errors)?
+    };
+
+    // Move the error report and the files left by a cancelled split to the object storage output
+    if let Some(remote_output) = &remote_output {
+        remote_output.upload_dir(output_path)?;
+        remote_output.remove_staging_dir();
+    }
+
+    // Describe the converted dataset in a dataset card once every split is complete
+    if args.dataset_card && !settings.cancel.is_cancelled() {
+        let details = dataset_card::CardDetails {
+            name: &args.name,
+            attribution: &settings.attribution,
+            classes: &card_classes,
+        };
+        let card_path = dataset_card::write_dataset_card(output_path, &details)?;
+        info!("Dataset card saved in {:?}", card_path);
+    }
+
+    // Print a final message indicating that the dataset has been saved, warning about failures
+    if settings.cancel.is_cancelled() {
+        warn!(
+            "Conversion into {:?} was cancelled, run it again with --resume to continue",
+            args.output
+        );
+    } else if failed_samples > 0 && total_samples == 0 {
+        error!(
+            "No sample could be converted into {:?}, all {} samples failed, see conversion_errors.json",
+            args.output, failed_samples
+        );
+    } else if failed_samples > 0 {
+        warn!(
+            "Dataset saved in {:?} but {} samples failed, see conversion_errors.json",
+            args.output, failed_samples
+        );
+    } else {
+        info!("Dataset saved successfully in {:?}", args.output);
+    }
+
+    // Upload the output to the Hub once every split is converted
+    if let Some(repo_id) = args
+        .push_to_hub
+        .as_deref()
+        .filter(|_| !settings.cancel.is_cancelled())
+    {
+        hub::upload_dataset(
+            repo_id,
+            args.hub_token.as_deref(),
+            output_path,
+            &args.hub_commit_message,
+            args.hub_private,
+        )?;
+    }
+
+    // Keep appending new images as they appear when watching, until cancelled
+    if let Some(watcher) = watcher.filter(|_| !settings.cancel.is_cancelled()) {
+        watch::watch_and_append(
+            watcher,
+            args,
+            &settings,
+            &split_config,
+            &source_settings,
+            &splits,
+            known_paths,
+        )?;
+    }
+
+    // Return the number of samples converted and failed
+    Ok(ConvertSummary {
+        samples: total_samples,
+        failed_samples,
+        cancelled: settings.cancel.is_cancelled(),
+    })
+}
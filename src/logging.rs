@@ -0,0 +1,66 @@
+use crate::cli::LogFormat;
+use crate::error::{Error, Result};
+use crate::progress;
+use env_logger::{Builder, Target};
+use indicatif_log_bridge::LogWrapper;
+use log::LevelFilter;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+// Function to map the verbose and quiet counters to a log level, starting from info
+fn level_filter(verbose: u8, quiet: u8) -> LevelFilter {
+    match (verbose, quiet) {
+        (0, 0) => LevelFilter::Info,
+        (1, 0) => LevelFilter::Debug,
+        (_, 0) => LevelFilter::Trace,
+        (0, 1) => LevelFilter::Warn,
+        (0, 2) => LevelFilter::Error,
+        _ => LevelFilter::Off,
+    }
+}
+
+// Function to set up the global logger, which RUST_LOG can still refine
+pub fn init_logging(
+    verbose: u8,
+    quiet: u8,
+    format: LogFormat,
+    log_file: Option<&Path>,
+) -> Result<()> {
+    // Create a builder with the requested level, letting RUST_LOG override it
+    let mut builder = Builder::new();
+    builder
+        .filter_level(level_filter(verbose, quiet))
+        .parse_default_env();
+
+    // Emit one JSON object per line when machine-parseable logs are requested
+    if let LogFormat::Json = format {
+        builder.format(|buf, record| {
+            // Build the JSON object describing the log record
+            let line = serde_json::json!({
+                "timestamp": buf.timestamp_millis().to_string(),
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+
+            // Write the object followed by a newline
+            writeln!(buf, "{}", line)
+        });
+    }
+
+    // Send the logs to a file instead of stderr when requested
+    if let Some(log_file) = log_file {
+        let file = File::create(log_file).map_err(Error::io("Failed to create", log_file))?;
+        builder.target(Target::Pipe(Box::new(file)));
+    }
+
+    // Build the logger and remember its level before handing it over
+    let logger = builder.build();
+    let max_level = logger.filter();
+
+    // Install the logger behind the progress bars so log lines are printed above them
+    LogWrapper::new(progress::multi_progress().clone(), logger).try_init()?;
+    log::set_max_level(max_level);
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use crate::error::{Error, Result};
+use crate::source::Record;
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+// Define the name of the SQLite database listing the samples of a split
+pub const MANIFEST_DB_FILE_NAME: &str = "manifest.db";
+
+// Define the SQLite database listing every sample of a split with the chunk file and row holding
+// it, filled by the chunk workers as they finish and indexed once the split is complete
+pub struct ManifestDb {
+    connection: Mutex<Connection>,
+}
+
+impl ManifestDb {
+    // Function to create the database of a split, replacing one left by an earlier run, with a
+    // temporary table collecting the samples of the chunks in the order they finish
+    pub fn create(output_dir: &Path) -> Result<Self> {
+        let path = output_dir.join(MANIFEST_DB_FILE_NAME);
+        if path.exists() {
+            fs::remove_file(&path).map_err(Error::io("Failed to remove", &path))?;
+        }
+        let connection = Connection::open(&path)?;
+        connection.execute_batch(
+            "PRAGMA synchronous = OFF;
+             CREATE TEMP TABLE chunk_samples (
+                 chunk INTEGER NOT NULL,
+                 chunk_file TEXT NOT NULL,
+                 row_offset INTEGER NOT NULL,
+                 label TEXT NOT NULL,
+                 byte_size INTEGER NOT NULL,
+                 sha256 TEXT NOT NULL
+             );",
+        )?;
+        Ok(ManifestDb {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    // Function to add the samples of the chunk of the given index, in row order, hashing their
+    // bytes before taking the connection so the workers only wait for each other to insert
+    pub fn add_chunk(
+        &self,
+        index: usize,
+        chunk_file: &str,
+        samples: &[(&Path, Record)],
+    ) -> Result<()> {
+        let digests: Vec<String> = samples
+            .iter()
+            .map(|(_, record)| {
+                Sha256::digest(&record.bytes)
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect()
+            })
+            .collect();
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        {
+            let mut insert = transaction.prepare_cached(
+                "INSERT INTO chunk_samples (chunk, chunk_file, row_offset, label, byte_size, sha256)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for (row, ((_, record), digest)) in samples.iter().zip(&digests).enumerate() {
+                insert.execute(params![
+                    index as i64,
+                    chunk_file,
+                    row as i64,
+                    record.label,
+                    record.bytes.len() as i64,
+                    digest
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    // Function to write the samples table once every chunk is written, numbering the samples from
+    // the row counts of the chunks in chunk order, and to index it by label and digest
+    pub fn finish(&self, chunk_rows: &[usize]) -> Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(
+            "CREATE TEMP TABLE chunk_offsets (
+                 chunk INTEGER PRIMARY KEY,
+                 first_sample INTEGER NOT NULL
+             );",
+        )?;
+        {
+            let mut insert = transaction
+                .prepare("INSERT INTO chunk_offsets (chunk, first_sample) VALUES (?1, ?2)")?;
+            let mut first_sample = 0;
+            for (chunk, rows) in chunk_rows.iter().enumerate() {
+                insert.execute(params![chunk as i64, first_sample as i64])?;
+                first_sample += rows;
+            }
+        }
+        transaction.execute_batch(
+            "CREATE TABLE samples (
+                 id INTEGER PRIMARY KEY,
+                 chunk_file TEXT NOT NULL,
+                 row_offset INTEGER NOT NULL,
+                 label TEXT NOT NULL,
+                 byte_size INTEGER NOT NULL,
+                 sha256 TEXT NOT NULL
+             );
+             INSERT INTO samples (id, chunk_file, row_offset, label, byte_size, sha256)
+                 SELECT first_sample + row_offset, chunk_file, row_offset, label, byte_size, sha256
+                 FROM chunk_samples JOIN chunk_offsets USING (chunk)
+                 ORDER BY 1;
+             CREATE INDEX samples_label ON samples (label);
+             CREATE INDEX samples_sha256 ON samples (sha256);
+             DROP TABLE chunk_samples;
+             DROP TABLE chunk_offsets;",
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use crate::error::{Error, Result};
+use crate::source::Record;
+use std::path::Path;
+
+// Define the name of the SQLite database listing the samples of a split
+pub const MANIFEST_DB_FILE_NAME: &str = "manifest.db";
+
+// Define the SQLite database listing the samples of a split, which this build cannot write
+pub enum ManifestDb {}
+
+impl ManifestDb {
+    // Function to report that writing the database is unavailable in this build
+    pub fn create(_output_dir: &Path) -> Result<Self> {
+        Err(Error::FeatureDisabled(
+            "Writing a manifest database",
+            "sqlite",
+        ))
+    }
+
+    // Function to add the samples of the chunk of the given index, in row order
+    pub fn add_chunk(
+        &self,
+        _index: usize,
+        _chunk_file: &str,
+        _samples: &[(&Path, Record)],
+    ) -> Result<()> {
+        match *self {}
+    }
+
+    // Function to write the samples table once every chunk is written
+    pub fn finish(&self, _chunk_rows: &[usize]) -> Result<()> {
+        match *self {}
+    }
+}
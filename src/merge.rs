@@ -0,0 +1,118 @@
+use crate::chunk_io::{read_chunk_schema, ChunkFormat};
+use crate::dataset::DatasetSplit;
+use crate::error::{Error, Result};
+use crate::metadata::{
+    chunk_digests, save_chunk_index, save_metadata, DatasetInfo, FileNameTemplate,
+};
+use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Function to merge several converted splits into a single split directory
+pub fn merge_datasets(
+    inputs: &[PathBuf],
+    output_dir: &Path,
+    dataset_name: Option<&str>,
+) -> Result<()> {
+    // Open every input split to read its metadata and state
+    let splits = inputs
+        .iter()
+        .map(DatasetSplit::open)
+        .collect::<Result<Vec<_>>>()?;
+
+    // Collect the chunk paths, the rows of every chunk and the sample counts of every split
+    let mut chunk_paths = Vec::new();
+    let mut chunk_rows = Vec::new();
+    let mut total_samples = 0;
+    for split in &splits {
+        total_samples += split.num_samples();
+        chunk_rows.extend(split.chunk_rows()?);
+        chunk_paths.extend(split.chunk_paths());
+    }
+
+    // Use the format of the first chunk for the merged split
+    let format = chunk_paths
+        .first()
+        .map_or(ChunkFormat::Arrow, |path| ChunkFormat::from_path(path));
+
+    // Make sure every chunk shares the same format and schema before copying anything
+    let mut expected_schema = None;
+    for chunk_path in &chunk_paths {
+        // Refuse to mix Arrow and Parquet chunks in one split
+        if ChunkFormat::from_path(chunk_path) != format {
+            return Err(Error::ChunkMismatch {
+                kind: "Format",
+                path: chunk_path.clone(),
+            });
+        }
+
+        // Read the schema of the chunk
+        let schema = read_chunk_schema(chunk_path)?;
+
+        // Compare the schema with the one of the first chunk
+        match &expected_schema {
+            None => expected_schema = Some(schema),
+            Some(expected) if *expected != schema => {
+                return Err(Error::ChunkMismatch {
+                    kind: "Schema",
+                    path: chunk_path.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    // Resolve the dataset name, falling back to the name of the first input
+    let dataset_name = match dataset_name {
+        Some(name) => name.to_string(),
+        None => splits[0].info().dataset_name.clone(),
+    };
+
+    // Create the output directory if it does not exist
+    fs::create_dir_all(output_dir).map_err(Error::io("Failed to create", output_dir))?;
+
+    // Calculate the number of chunks in the merged split
+    let num_chunks = chunk_paths.len();
+
+    // Print status message with the merge details
+    info!(
+        "Merging {} splits into '{}' with {} samples in {} chunks...",
+        inputs.len(),
+        dataset_name,
+        total_samples,
+        num_chunks
+    );
+
+    // Copy every chunk into the output directory under its renumbered name
+    let chunk_files = FileNameTemplate::default().file_names("", num_chunks, format);
+    for (chunk_path, file_name) in chunk_paths.iter().zip(&chunk_files) {
+        // Create the full path of the renumbered chunk
+        let file_path = output_dir.join(file_name);
+
+        // Copy the chunk file to its new location
+        fs::copy(chunk_path, &file_path).map_err(Error::io("Failed to copy", chunk_path))?;
+
+        // Print a message indicating the chunk has been copied
+        info!("Copied chunk {:?} -> {:?}", chunk_path, file_path);
+    }
+
+    // Save the metadata and state describing the merged split, which no single seed reproduces
+    let first_info = splits[0].info();
+    let metadata = DatasetInfo {
+        features: first_info.features.clone(),
+        attribution: first_info.attribution.clone(),
+        ..DatasetInfo::new(
+            &dataset_name,
+            &first_info.dataset_type,
+            total_samples,
+            format,
+            None,
+            &first_info.columns.clone().unwrap_or_default(),
+        )
+    };
+    let digests = chunk_digests(output_dir, &chunk_files)?;
+    save_metadata(output_dir, &metadata, &chunk_files, &digests, format)?;
+
+    // Save the row count of every chunk so samples can be looked up by index
+    save_chunk_index(output_dir, &chunk_rows)
+}
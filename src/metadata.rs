@@ -0,0 +1,654 @@
+use crate::chunk_io::{count_chunk_rows, ChunkFormat};
+use crate::error::{Error, Result};
+use crate::schema::SampleColumns;
+use log::info;
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+// Define the description of a column in the features of a dataset, named by its "_type" as in
+// the dataset_info.json files of HuggingFace datasets
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "_type")]
+pub enum Feature {
+    Image,
+    Audio {
+        sampling_rate: Option<u32>,
+    },
+    ClassLabel {
+        names: Vec<String>,
+    },
+    Value {
+        dtype: String,
+    },
+    Sequence {
+        feature: Box<Feature>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        length: Option<i32>,
+    },
+}
+
+// Define the features of a dataset as the description of every column, in column order
+#[derive(Clone, Default)]
+pub struct Features(pub Vec<(String, Feature)>);
+
+impl Features {
+    // Function to get the description of a column, if it has one
+    pub fn get(&self, column: &str) -> Option<&Feature> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, feature)| feature)
+    }
+
+    // Function to get the sampling rate recorded in the Audio feature, None without such a feature
+    // and Some(None) when the audio samples do not share one
+    fn audio_sampling_rate(&self) -> Option<Option<u32>> {
+        self.0.iter().find_map(|(_, feature)| match feature {
+            Feature::Audio { sampling_rate } => Some(*sampling_rate),
+            _ => None,
+        })
+    }
+
+    // Function to record the sampling rate shared by the audio samples in the Audio feature
+    pub fn with_sampling_rate(mut self, rate: SamplingRate) -> Self {
+        for (_, feature) in &mut self.0 {
+            if let Feature::Audio { sampling_rate } = feature {
+                *sampling_rate = rate.rate();
+            }
+        }
+        self
+    }
+}
+
+// Define the sampling rate shared by the audio samples of a split, unknown before the first one is
+// read and mixed once two of them differ
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum SamplingRate {
+    #[default]
+    Unknown,
+    Shared(u32),
+    Mixed,
+}
+
+impl SamplingRate {
+    // Function to combine the rates of two groups of samples
+    pub fn merge(self, other: SamplingRate) -> SamplingRate {
+        match (self, other) {
+            (SamplingRate::Unknown, rate) | (rate, SamplingRate::Unknown) => rate,
+            (SamplingRate::Shared(a), SamplingRate::Shared(b)) if a == b => self,
+            _ => SamplingRate::Mixed,
+        }
+    }
+
+    // Function to get the rate recorded in the Audio feature, None unless every sample shares it
+    pub fn rate(self) -> Option<u32> {
+        match self {
+            SamplingRate::Shared(rate) => Some(rate),
+            _ => None,
+        }
+    }
+}
+
+impl From<Option<u32>> for SamplingRate {
+    fn from(rate: Option<u32>) -> Self {
+        rate.map_or(SamplingRate::Unknown, SamplingRate::Shared)
+    }
+}
+
+impl Serialize for Features {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        // Write the columns as a JSON object keeping their order
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, feature) in &self.0 {
+            map.serialize_entry(name, feature)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Features {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        // Read the columns of the JSON object in the order they are written
+        struct FeaturesVisitor;
+        impl<'de> Visitor<'de> for FeaturesVisitor {
+            type Value = Features;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of column names to features")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<Features, A::Error> {
+                let mut features = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    features.push(entry);
+                }
+                Ok(Features(features))
+            }
+        }
+        deserializer.deserialize_map(FeaturesVisitor)
+    }
+}
+
+// Define the size of a split as listed in the splits of the dataset info of HuggingFace datasets,
+// with the number of rows of every chunk
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SplitInfo {
+    pub name: String,
+    pub num_bytes: u64,
+    pub num_examples: usize,
+    pub shard_lengths: Vec<usize>,
+}
+
+// Define the attribution of a dataset, read from the config file and recorded in its dataset info
+// under the keys of HuggingFace datasets so it is credited when shared
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Attribution {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+}
+
+// Define a struct to store dataset metadata
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DatasetInfo {
+    pub dataset_name: String,
+    pub dataset_type: String,
+    pub num_samples: usize,
+    pub format: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<SampleColumns>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub features: Option<Features>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub splits: Option<BTreeMap<String, SplitInfo>>,
+    #[serde(flatten)]
+    pub attribution: Attribution,
+}
+
+impl DatasetInfo {
+    // Function to describe a split of the given type, samples and chunk format, recording the names
+    // of the image and label columns only when they were renamed
+    pub fn new(
+        dataset_name: &str,
+        dataset_type: &str,
+        num_samples: usize,
+        format: ChunkFormat,
+        seed: Option<u64>,
+        columns: &SampleColumns,
+    ) -> Self {
+        DatasetInfo {
+            dataset_name: dataset_name.to_string(),
+            dataset_type: dataset_type.to_string(),
+            num_samples,
+            format: format.extension().to_string(),
+            seed,
+            columns: (*columns != SampleColumns::default()).then(|| columns.clone()),
+            features: None,
+            splits: None,
+            attribution: Attribution::default(),
+        }
+    }
+
+    // Function to get the sampling rate shared by the audio samples of the split, mixed when its
+    // Audio feature records none although it has samples
+    pub fn sampling_rate(&self) -> SamplingRate {
+        match self
+            .features
+            .as_ref()
+            .and_then(Features::audio_sampling_rate)
+        {
+            Some(Some(rate)) => SamplingRate::Shared(rate),
+            Some(None) if self.num_samples > 0 => SamplingRate::Mixed,
+            _ => SamplingRate::Unknown,
+        }
+    }
+
+    // Function to get the class names of the label column when it holds class label ids
+    pub fn class_names(&self) -> Option<&[String]> {
+        let label = self.columns.clone().unwrap_or_default().label;
+        match self.features.as_ref()?.get(&label)? {
+            Feature::ClassLabel { names } => Some(names),
+            _ => None,
+        }
+    }
+}
+
+// Define a struct describing a single data file entry in state.json
+#[derive(Serialize, Deserialize)]
+pub struct DataFile {
+    pub filename: String,
+}
+
+// Define a struct to store the dataset state listing all chunk files, along with the split and the
+// output format read by datasets.load_from_disk, which formats nothing by default
+#[derive(Serialize, Deserialize)]
+pub struct DatasetState {
+    #[serde(rename = "_data_files")]
+    pub data_files: Vec<DataFile>,
+    #[serde(rename = "_type")]
+    pub data_type: String,
+    #[serde(
+        rename = "_fingerprint",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fingerprint: Option<String>,
+    #[serde(rename = "_format_columns", default)]
+    pub format_columns: Option<Vec<String>>,
+    #[serde(rename = "_format_kwargs", default)]
+    pub format_kwargs: serde_json::Map<String, serde_json::Value>,
+    #[serde(rename = "_format_type", default)]
+    pub format_type: Option<String>,
+    #[serde(rename = "_output_all_columns", default)]
+    pub output_all_columns: bool,
+    #[serde(rename = "_split", default)]
+    pub split: Option<String>,
+}
+
+// Define the name of the file listing the splits of a dataset saved like a DatasetDict
+pub const DATASET_DICT_FILE_NAME: &str = "dataset_dict.json";
+
+// Define the content of dataset_dict.json, the splits in the order they are loaded
+#[derive(Default, Serialize, Deserialize)]
+struct DatasetDict {
+    splits: Vec<String>,
+}
+
+// Function to save the dataset_dict.json listing the given splits at the root of the output, in
+// the layout of DatasetDict.save_to_disk, keeping the splits listed by an earlier run first
+pub fn save_dataset_dict(output_dir: &Path, splits: &[String]) -> Result<()> {
+    let path = output_dir.join(DATASET_DICT_FILE_NAME);
+    let mut dict = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).map_err(Error::json(&path))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => DatasetDict::default(),
+        Err(e) => return Err(Error::io("Failed to read", &path)(e)),
+    };
+    for split in splits {
+        if !dict.splits.contains(split) {
+            dict.splits.push(split.clone());
+        }
+    }
+    let json = serde_json::to_string_pretty(&dict).expect("Failed to serialize dataset_dict.json");
+    fs::write(&path, json).map_err(Error::io("Failed to write", &path))
+}
+
+// Define the name of the file recording the number of rows of every chunk
+const CHUNK_INDEX_FILE_NAME: &str = "index.json";
+
+// Define a struct storing the number of rows of every chunk in order, from which the chunk and
+// row of any global sample index can be computed
+#[derive(Serialize, Deserialize)]
+pub struct ChunkIndex {
+    pub chunk_rows: Vec<usize>,
+}
+
+// Define the default template of the chunk file names, such as "data-00000-of-00042.arrow"
+pub const DEFAULT_FILE_NAME_TEMPLATE: &str = "data-{index:05}-of-{num_chunks:05}.{ext}";
+
+// Define a piece of a file name template, either text copied as is or a placeholder replaced by
+// the split name, the chunk index or count padded with zeros to a width, or the chunk extension
+#[derive(Clone, Debug, PartialEq)]
+enum TemplatePart {
+    Text(String),
+    Split,
+    Index(usize),
+    NumChunks(usize),
+    Extension,
+}
+
+// Define a template of the chunk file names made of the {split}, {index}, {num_chunks} and {ext}
+// placeholders, where {index:05} pads the number with zeros to 5 digits
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct FileNameTemplate {
+    template: String,
+    parts: Vec<TemplatePart>,
+}
+
+impl FileNameTemplate {
+    // Function to parse a template, refusing unknown placeholders, path separators and templates
+    // without an {index} placeholder that would give every chunk the same name
+    pub fn parse(template: &str) -> std::result::Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(TemplatePart::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("unclosed placeholder in '{}'", template))?;
+            let placeholder = &rest[start + 1..start + end];
+            let (name, width) = match placeholder.split_once(':') {
+                Some((name, width)) => (
+                    name,
+                    Some(
+                        width
+                            .parse::<usize>()
+                            .map_err(|_| format!("invalid width in {{{}}}", placeholder))?,
+                    ),
+                ),
+                None => (placeholder, None),
+            };
+            parts.push(match (name, width) {
+                ("split", None) => TemplatePart::Split,
+                ("ext", None) => TemplatePart::Extension,
+                ("index", width) => TemplatePart::Index(width.unwrap_or(0)),
+                ("num_chunks", width) => TemplatePart::NumChunks(width.unwrap_or(0)),
+                _ => return Err(format!("unknown placeholder {{{}}}", placeholder)),
+            });
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Text(rest.to_string()));
+        }
+        if parts
+            .iter()
+            .any(|part| matches!(part, TemplatePart::Text(text) if text.contains(['/', '\\', '}'])))
+        {
+            return Err(format!("'{}' must be a file name", template));
+        }
+        if !parts
+            .iter()
+            .any(|part| matches!(part, TemplatePart::Index(_)))
+        {
+            return Err(format!("'{}' has no {{index}} placeholder", template));
+        }
+        Ok(FileNameTemplate {
+            template: template.to_string(),
+            parts,
+        })
+    }
+
+    // Function to get the template as it was written
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    // Function to build the file name of the chunk of the given index among the given number of
+    // chunks of a split
+    pub fn file_name(
+        &self,
+        split: &str,
+        index: usize,
+        num_chunks: usize,
+        format: ChunkFormat,
+    ) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => text.clone(),
+                TemplatePart::Split => split.to_string(),
+                TemplatePart::Index(width) => format!("{:0width$}", index, width = width),
+                TemplatePart::NumChunks(width) => {
+                    format!("{:0width$}", num_chunks, width = width)
+                }
+                TemplatePart::Extension => format.extension().to_string(),
+            })
+            .collect()
+    }
+
+    // Function to build the file names of every chunk of a split
+    pub fn file_names(&self, split: &str, num_chunks: usize, format: ChunkFormat) -> Vec<String> {
+        (0..num_chunks)
+            .map(|i| self.file_name(split, i, num_chunks, format))
+            .collect()
+    }
+
+    // Function to check whether a file name is the name of a chunk of the given split under this
+    // template, in either chunk format and for any chunk index and count
+    pub fn matches(&self, split: &str, file_name: &str) -> bool {
+        matches_parts(&self.parts, split, file_name)
+    }
+
+    // Function to refuse a template whose names end with the extension of another chunk format,
+    // which would be read back in the wrong format
+    pub fn check_format(&self, format: ChunkFormat) -> Result<()> {
+        let name = self.file_name("", 0, 1, format);
+        if ChunkFormat::from_path(Path::new(&name)) != format
+            || !name.ends_with(&format!(".{}", format.extension()))
+        {
+            return Err(Error::Unsupported(format!(
+                "Writing .{} chunks with the file name template '{}'",
+                format.extension(),
+                self.template
+            )));
+        }
+        Ok(())
+    }
+}
+
+// Function to match the rest of a file name against the remaining parts of a template, trying every
+// length of the digits of the chunk index and count from their padded width
+fn matches_parts(parts: &[TemplatePart], split: &str, rest: &str) -> bool {
+    let Some((part, parts)) = parts.split_first() else {
+        return rest.is_empty();
+    };
+    match part {
+        TemplatePart::Text(text) => rest
+            .strip_prefix(text.as_str())
+            .is_some_and(|rest| matches_parts(parts, split, rest)),
+        TemplatePart::Split => rest
+            .strip_prefix(split)
+            .is_some_and(|rest| matches_parts(parts, split, rest)),
+        TemplatePart::Extension => [ChunkFormat::Arrow, ChunkFormat::Parquet]
+            .iter()
+            .filter_map(|format| rest.strip_prefix(format.extension()))
+            .any(|rest| matches_parts(parts, split, rest)),
+        TemplatePart::Index(width) | TemplatePart::NumChunks(width) => {
+            let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            ((*width).max(1)..=digits).any(|len| matches_parts(parts, split, &rest[len..]))
+        }
+    }
+}
+
+impl Default for FileNameTemplate {
+    fn default() -> Self {
+        FileNameTemplate::parse(DEFAULT_FILE_NAME_TEMPLATE).expect("Invalid default template")
+    }
+}
+
+impl TryFrom<String> for FileNameTemplate {
+    type Error = String;
+
+    fn try_from(template: String) -> std::result::Result<Self, String> {
+        FileNameTemplate::parse(&template)
+    }
+}
+
+// Function to hash a file with SHA-256, as hex digits
+pub fn file_sha256(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex_digest(&hasher.finalize()))
+}
+
+// Function to write a digest as hex digits
+fn hex_digest(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Function to hash the given chunk files of a split with SHA-256, in order
+pub fn chunk_digests(output_dir: &Path, chunk_files: &[String]) -> Result<Vec<String>> {
+    chunk_files
+        .iter()
+        .map(|file_name| {
+            let path = output_dir.join(file_name);
+            file_sha256(&path).map_err(Error::io("Failed to read", &path))
+        })
+        .collect()
+}
+
+// Function to compute the fingerprint of a split from its dataset info, which holds its schema and
+// the settings it was converted with, and the names and SHA-256 digests of its chunk files, so the
+// same conversion always gives the same fingerprint; it is written as the 16 hex digits of the
+// fingerprints of HuggingFace datasets
+fn fingerprint(metadata: &DatasetInfo, chunk_files: &[String], chunk_digests: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(metadata).expect("Failed to serialize metadata"));
+    for (file_name, digest) in chunk_files.iter().zip(chunk_digests) {
+        hasher.update(format!("\n{}\0{}", file_name, digest));
+    }
+    hex_digest(&hasher.finalize()[..8])
+}
+
+// Function to save the dataset metadata and the state information listing the given chunk files
+// in the given format, fingerprinted with their SHA-256 digests
+pub fn save_metadata(
+    output_dir: &Path,
+    metadata: &DatasetInfo,
+    chunk_files: &[String],
+    chunk_digests: &[String],
+    format: ChunkFormat,
+) -> Result<()> {
+    // Serialize the metadata struct into a pretty JSON string
+    let metadata_json =
+        serde_json::to_string_pretty(metadata).expect("Failed to serialize metadata");
+
+    // Create the full path for the metadata file "dataset_info.json"
+    let metadata_path = output_dir.join("dataset_info.json");
+
+    // Create the metadata file
+    let mut file =
+        File::create(&metadata_path).map_err(Error::io("Failed to create", &metadata_path))?;
+
+    // Write the JSON metadata into the file
+    file.write_all(metadata_json.as_bytes())
+        .map_err(Error::io("Failed to write", &metadata_path))?;
+
+    // Create the state information with data file names and type
+    let state = DatasetState {
+        data_files: chunk_files
+            .iter()
+            .map(|filename| DataFile {
+                filename: filename.clone(),
+            })
+            .collect(),
+        data_type: format.extension().to_string(),
+        fingerprint: Some(fingerprint(metadata, chunk_files, chunk_digests)),
+        format_columns: None,
+        format_kwargs: serde_json::Map::new(),
+        format_type: None,
+        output_all_columns: false,
+        split: output_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string()),
+    };
+
+    // Serialize the state into a pretty string
+    let state_json = serde_json::to_string_pretty(&state).expect("Failed to serialize state.json");
+
+    // Create the full path for the state file "state.json"
+    let state_path = output_dir.join("state.json");
+
+    // Create the state file
+    let mut file = File::create(&state_path).map_err(Error::io("Failed to create", &state_path))?;
+
+    // Write the JSON state into the file
+    file.write_all(state_json.as_bytes())
+        .map_err(Error::io("Failed to write", &state_path))?;
+
+    // Print a message indicating that metadata and state.json have been saved successfully
+    info!("Metadata and state.json saved in {:?}", output_dir);
+    Ok(())
+}
+
+// Function to read the dataset metadata stored in a split directory
+pub fn read_dataset_info(split_dir: &Path) -> Result<DatasetInfo> {
+    // Read the whole "dataset_info.json" file into a string
+    let path = split_dir.join("dataset_info.json");
+    let content = fs::read_to_string(&path).map_err(Error::io("Failed to read", &path))?;
+
+    // Parse the JSON content into a DatasetInfo struct
+    serde_json::from_str(&content).map_err(Error::json(&path))
+}
+
+// Function to read the dataset state stored in a split directory
+pub fn read_dataset_state(split_dir: &Path) -> Result<DatasetState> {
+    // Read the whole "state.json" file into a string
+    let path = split_dir.join("state.json");
+    let content = fs::read_to_string(&path).map_err(Error::io("Failed to read", &path))?;
+
+    // Parse the JSON content into a DatasetState struct
+    serde_json::from_str(&content).map_err(Error::json(&path))
+}
+
+// Function to save the number of rows of every chunk of a split
+pub fn save_chunk_index(output_dir: &Path, chunk_rows: &[usize]) -> Result<()> {
+    // Serialize the row counts into a compact JSON string
+    let index = ChunkIndex {
+        chunk_rows: chunk_rows.to_vec(),
+    };
+    let index_json = serde_json::to_string(&index).expect("Failed to serialize index.json");
+
+    // Write the index next to the state file
+    let index_path = output_dir.join(CHUNK_INDEX_FILE_NAME);
+    fs::write(&index_path, index_json).map_err(Error::io("Failed to write", &index_path))
+}
+
+// Function to read the number of rows of every chunk listed in the state of a split, counting
+// them from the chunk files when the split has no index or an outdated one
+pub fn read_chunk_rows(split_dir: &Path, state: &DatasetState) -> Result<Vec<usize>> {
+    // Use the recorded index when it covers every chunk of the state
+    let index_path = split_dir.join(CHUNK_INDEX_FILE_NAME);
+    if let Ok(content) = fs::read_to_string(&index_path) {
+        let index: ChunkIndex = serde_json::from_str(&content).map_err(Error::json(&index_path))?;
+        if index.chunk_rows.len() == state.data_files.len() {
+            return Ok(index.chunk_rows);
+        }
+    }
+
+    // Otherwise decode every chunk to count its rows
+    state
+        .data_files
+        .iter()
+        .map(|data_file| count_chunk_rows(&split_dir.join(&data_file.filename)))
+        .collect()
+}
+
+// Function to locate the split directories of a converted dataset
+pub fn find_split_dirs(path: &Path) -> Result<Vec<PathBuf>> {
+    // Treat the path itself as a split when it directly contains a state file
+    if path.join("state.json").is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    // Otherwise collect every direct subdirectory that contains a state file
+    let mut splits: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(Error::io("Failed to read", path))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|dir| dir.join("state.json").is_file())
+        .collect();
+
+    // Sort the splits by path so the output order is stable
+    splits.sort();
+
+    // Return the discovered split directories
+    Ok(splits)
+}
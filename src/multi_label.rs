@@ -0,0 +1,78 @@
+use crate::computed::{per_sample, ComputedColumn, ComputedSample};
+use arrow::array::{ArrayRef, FixedSizeListBuilder, UInt8Builder};
+use arrow::datatypes::{DataType, Field};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Define the name of the column holding the labels of every sample of a multi-label dataset
+pub const LABELS_COLUMN: &str = "labels";
+
+// Function to split the label of a sample into its labels, without the surrounding whitespace and
+// the empty ones
+fn split_labels<'a>(label: &'a str, delimiter: &'a str) -> impl Iterator<Item = &'a str> {
+    label
+        .split(delimiter)
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+}
+
+// Function to define the "labels" column holding the list of labels found by splitting the label
+// of every sample at the delimiter
+pub fn labels_column(delimiter: String) -> impl ComputedColumn {
+    per_sample(LABELS_COLUMN, move |sample: &ComputedSample<'_>| {
+        split_labels(&sample.record.label, &delimiter)
+            .map(str::to_string)
+            .collect::<Vec<String>>()
+    })
+}
+
+// Define a computed column holding one flag per class, set for the classes found by splitting the
+// label of every sample at the delimiter
+struct MultiHot {
+    delimiter: String,
+    classes: HashMap<String, usize>,
+    class_count: usize,
+}
+
+impl ComputedColumn for MultiHot {
+    fn field(&self) -> Field {
+        Field::new(
+            LABELS_COLUMN,
+            DataType::FixedSizeList(
+                Arc::new(Field::new_list_field(DataType::UInt8, true)),
+                self.class_count as i32,
+            ),
+            false,
+        )
+    }
+
+    fn compute(&self, samples: &[ComputedSample<'_>]) -> ArrayRef {
+        // Set the flags of the known classes of every sample, ignoring the unknown ones
+        let mut builder = FixedSizeListBuilder::new(UInt8Builder::new(), self.class_count as i32);
+        for sample in samples {
+            let mut flags = vec![0u8; self.class_count];
+            for label in split_labels(&sample.record.label, &self.delimiter) {
+                if let Some(&index) = self.classes.get(label) {
+                    flags[index] = 1;
+                }
+            }
+            builder.values().append_slice(&flags);
+            builder.append(true);
+        }
+        Arc::new(builder.finish())
+    }
+}
+
+// Function to define the "labels" column as a multi-hot vector over the given classes, in order,
+// instead of a list of labels
+pub fn multi_hot_column(delimiter: String, classes: &[String]) -> impl ComputedColumn {
+    MultiHot {
+        delimiter,
+        classes: classes
+            .iter()
+            .enumerate()
+            .map(|(index, class)| (class.clone(), index))
+            .collect(),
+        class_count: classes.len(),
+    }
+}
@@ -0,0 +1,150 @@
+use crate::chunk_io::ChunkFormat;
+use crate::error::{Error, Result};
+use crate::export;
+use crate::manifest_db::MANIFEST_DB_FILE_NAME;
+use crate::metadata::{
+    read_chunk_rows, read_dataset_info, read_dataset_state, FileNameTemplate, SamplingRate,
+};
+use crate::sink;
+use std::fs;
+use std::path::Path;
+
+// Define a struct describing the chunks already present in a split directory
+pub struct ExistingChunks {
+    pub filenames: Vec<String>,
+    pub chunk_rows: Vec<usize>,
+    pub format: ChunkFormat,
+    pub num_samples: usize,
+    pub sampling_rate: SamplingRate,
+}
+
+// Function to check whether a directory exists and contains at least one entry
+pub fn is_non_empty_dir(path: &Path) -> bool {
+    // Treat a missing or unreadable directory as empty
+    fs::read_dir(path)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false)
+}
+
+// Function to check whether an entry of the directory of the given split, named by the given file
+// name, is a chunk named after the given template, a metadata or exported file written by the
+// converter, or the temporary file of a chunk left by an interrupted run
+pub fn is_output_file(file_name: &str, split: &str, template: &FileNameTemplate) -> bool {
+    if let Some(file_name) = file_name.strip_suffix(sink::TEMP_FILE_SUFFIX) {
+        return is_output_file(file_name, split, template);
+    }
+    let is_chunk = template.matches(split, file_name);
+    let is_metadata = matches!(
+        file_name,
+        "dataset_info.json" | "state.json" | "index.json" | "resume.json" | MANIFEST_DB_FILE_NAME
+    );
+    is_chunk || is_metadata || export::is_export_file(file_name)
+}
+
+// Function to remove the chunk and metadata files left in a split directory by an earlier run,
+// whose chunks are those listed in its state or, when it was interrupted before writing one, those
+// named after the given template
+pub fn clear_split_output(split_output: &Path, template: &FileNameTemplate) -> Result<()> {
+    // Name the split after its directory, as the chunk names do
+    let split = split_output
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    // Remove the chunks listed in the state first, whose names may follow another template
+    if let Ok(state) = read_dataset_state(split_output) {
+        for data_file in state.data_files {
+            let path = split_output.join(&data_file.filename);
+            for path in [sink::temp_path(&path), path] {
+                if path.is_file() {
+                    fs::remove_file(&path).map_err(Error::io("Failed to remove", &path))?;
+                }
+            }
+        }
+    }
+
+    // Read the directory entries, treating a missing directory as already clear
+    let Ok(entries) = fs::read_dir(split_output) else {
+        return Ok(());
+    };
+
+    // Remove every chunk file, exported file and metadata file written by the converter
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        // Get the file name of the entry as a string
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        // Remove the file if it was written by the converter or one of its exports
+        let path = entry.path();
+        if path.is_dir() && export::is_export_file(&file_name) {
+            fs::remove_dir_all(&path).map_err(Error::io("Failed to remove", &path))?;
+        } else if is_output_file(&file_name, &split, template) {
+            fs::remove_file(&path).map_err(Error::io("Failed to remove", &path))?;
+        }
+    }
+    Ok(())
+}
+
+// Function to read the class names already numbered in a converted split, from the ClassLabel
+// feature of its metadata or else from the exports storing class ids, or none when it has neither
+pub fn read_class_names(split_output: &Path) -> Result<Vec<String>> {
+    if split_output.join("dataset_info.json").is_file() {
+        if let Some(names) = read_dataset_info(split_output)?.class_names() {
+            return Ok(names.to_vec());
+        }
+    }
+    export::read_class_names(split_output)
+}
+
+// Function to read the chunks of a previously converted split, if there is one
+pub fn read_existing_chunks(split_output: &Path) -> Result<Option<ExistingChunks>> {
+    // A split without a state file has not been converted yet
+    if !split_output.join("state.json").is_file() {
+        return Ok(None);
+    }
+
+    // Read the metadata and the state of the split
+    let info = read_dataset_info(split_output)?;
+    let state = read_dataset_state(split_output)?;
+
+    // Read the number of rows of every existing chunk
+    let chunk_rows = read_chunk_rows(split_output, &state)?;
+
+    // Detect the chunk format from the recorded data type
+    let format = match state.data_type.as_str() {
+        "parquet" => ChunkFormat::Parquet,
+        _ => ChunkFormat::Arrow,
+    };
+
+    // Return the chunk file names, their row counts and format, and the sample count
+    Ok(Some(ExistingChunks {
+        format,
+        chunk_rows,
+        filenames: state
+            .data_files
+            .into_iter()
+            .map(|data_file| data_file.filename)
+            .collect(),
+        num_samples: info.num_samples,
+        sampling_rate: info.sampling_rate(),
+    }))
+}
+
+// Function to rename existing chunks to their names among the new chunks of the split, whose
+// suffix or template may differ
+pub fn renumber_chunks(
+    split_output: &Path,
+    filenames: &[String],
+    new_names: &[String],
+) -> Result<()> {
+    // Rename each existing chunk to the name of its position within the new total
+    for (filename, new_name) in filenames.iter().zip(new_names) {
+        // Rename the file only when its name actually changes
+        if filename != new_name {
+            let path = split_output.join(filename);
+            fs::rename(&path, split_output.join(new_name))
+                .map_err(Error::io("Failed to rename", &path))?;
+        }
+    }
+    Ok(())
+}
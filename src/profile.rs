@@ -0,0 +1,88 @@
+use crate::chunk_io::ChunkFormat;
+use crate::cli::Profile;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::num::NonZeroUsize;
+
+// Define the compression codecs that can be applied to chunk data
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// No compression
+    None,
+    /// Snappy, fast to decode, for Parquet chunks
+    Snappy,
+    /// Zstd, the smallest chunks
+    Zstd,
+    /// LZ4, the fastest to decode
+    Lz4,
+}
+
+// Define the layouts of Arrow IPC chunk files
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpcFormat {
+    /// The IPC file format, with a footer indexing the record batches for random access
+    File,
+    /// The IPC streaming format of the cache files of HuggingFace datasets, without a footer
+    Stream,
+}
+
+// Define a struct to store how chunk files are laid out on disk
+#[derive(Clone, Copy)]
+pub struct OutputSettings {
+    pub format: ChunkFormat,
+    pub compression: Compression,
+    pub row_group_size: Option<usize>,
+    pub ipc_format: IpcFormat,
+    pub fsync: bool,
+}
+
+// Function to resolve a named profile into the output settings it bundles
+pub fn output_settings(profile: Profile) -> OutputSettings {
+    match profile {
+        // Uncompressed Arrow IPC chunks that HuggingFace datasets can memory-map
+        Profile::Hf => OutputSettings {
+            format: ChunkFormat::Arrow,
+            compression: Compression::None,
+            row_group_size: None,
+            ipc_format: IpcFormat::File,
+            fsync: false,
+        },
+        // Snappy-compressed Parquet chunks readable by most data tools
+        Profile::Parquet => OutputSettings {
+            format: ChunkFormat::Parquet,
+            compression: Compression::Snappy,
+            row_group_size: None,
+            ipc_format: IpcFormat::File,
+            fsync: false,
+        },
+        // Zstd-compressed Arrow IPC chunks trading load speed for disk space
+        Profile::Compact => OutputSettings {
+            format: ChunkFormat::Arrow,
+            compression: Compression::Zstd,
+            row_group_size: None,
+            ipc_format: IpcFormat::File,
+            fsync: false,
+        },
+    }
+}
+
+// Function to resolve the output settings of a profile, with the chunk format, compression,
+// Parquet row group size and Arrow IPC layout given on their own replacing those of the profile
+pub fn resolve_output_settings(
+    profile: Profile,
+    format: Option<ChunkFormat>,
+    compression: Option<Compression>,
+    row_group_size: Option<NonZeroUsize>,
+    ipc_format: Option<IpcFormat>,
+) -> OutputSettings {
+    let settings = output_settings(profile);
+    OutputSettings {
+        format: format.unwrap_or(settings.format),
+        compression: compression.unwrap_or(settings.compression),
+        row_group_size: row_group_size.map(NonZeroUsize::get),
+        ipc_format: ipc_format.unwrap_or(settings.ipc_format),
+        ..settings
+    }
+}
@@ -0,0 +1,207 @@
+use crate::cli::ProgressFormat;
+use crate::report::SampleError;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+// Define the shared container drawing every progress bar, also used by the logger
+static MULTI_PROGRESS: OnceLock<MultiProgress> = OnceLock::new();
+
+// Define a struct describing a completed chunk in machine-readable progress output
+#[derive(Serialize)]
+struct ChunkProgress<'a> {
+    event: &'static str,
+    dataset: &'a str,
+    chunk: usize,
+    num_chunks: usize,
+    file: &'a Path,
+    samples: usize,
+    bytes: u64,
+    elapsed_secs: f64,
+}
+
+// Define the hooks a program embedding the library can implement to follow a conversion, called
+// from the worker threads
+pub trait ProgressCallback: Send + Sync {
+    // Function called when a worker starts converting a chunk
+    fn on_chunk_start(&self, _dataset: &str, _chunk: usize, _num_chunks: usize, _samples: usize) {}
+
+    // Function called once a chunk file has been written
+    fn on_chunk_done(
+        &self,
+        _dataset: &str,
+        _chunk: usize,
+        _num_chunks: usize,
+        _file: &Path,
+        _samples: usize,
+    ) {
+    }
+
+    // Function called when a sample cannot be converted and is skipped
+    fn on_sample_error(&self, _error: &SampleError) {}
+}
+
+// Define a struct tracking and reporting the progress of writing the chunks of a split
+pub struct SplitProgress {
+    format: ProgressFormat,
+    callback: Option<Arc<dyn ProgressCallback>>,
+    dataset: String,
+    num_chunks: usize,
+    started: Instant,
+    bar: ProgressBar,
+    chunks_done: AtomicUsize,
+    bytes_written: AtomicU64,
+}
+
+// Function to get the container drawing every progress bar
+pub fn multi_progress() -> &'static MultiProgress {
+    MULTI_PROGRESS.get_or_init(MultiProgress::new)
+}
+
+// Function to resolve the automatic progress format to bars on a terminal and nothing otherwise
+pub fn resolve_format(format: ProgressFormat) -> ProgressFormat {
+    match format {
+        ProgressFormat::Auto if io::stderr().is_terminal() => ProgressFormat::Bar,
+        ProgressFormat::Auto => ProgressFormat::None,
+        format => format,
+    }
+}
+
+// Function to create the spinner counting the images found while scanning a split
+pub fn scan_bar(format: ProgressFormat, split: &str) -> ProgressBar {
+    // Use a hidden bar, on which every update is a no-op, unless bars are requested
+    let ProgressFormat::Bar = format else {
+        return ProgressBar::hidden();
+    };
+
+    // Create a spinner showing the number of images found so far
+    let bar = multi_progress().add(ProgressBar::new_spinner());
+    bar.set_style(
+        ProgressStyle::with_template("{spinner} Scanning {prefix}: {human_pos} images")
+            .expect("Invalid progress template"),
+    );
+    bar.set_prefix(split.to_string());
+    bar.enable_steady_tick(Duration::from_millis(100));
+
+    // Return the spinner
+    bar
+}
+
+impl SplitProgress {
+    // Function to start tracking a split with the given number of new samples, existing chunks and
+    // total chunks
+    pub fn new(
+        format: ProgressFormat,
+        callback: Option<Arc<dyn ProgressCallback>>,
+        dataset_name: &str,
+        total_samples: usize,
+        existing_chunks: usize,
+        num_chunks: usize,
+    ) -> Self {
+        // Create a bar counting samples when bars are requested, and a hidden one otherwise
+        let bar = match format {
+            ProgressFormat::Bar => {
+                let bar = multi_progress().add(ProgressBar::new(total_samples as u64));
+                bar.set_style(
+                    ProgressStyle::with_template(
+                        "{prefix} [{bar:30}] {human_pos}/{human_len} samples, {per_sec}, {msg}, ETA {eta}",
+                    )
+                    .expect("Invalid progress template")
+                    .progress_chars("=> "),
+                );
+                bar.set_prefix(dataset_name.to_string());
+                bar
+            }
+            _ => ProgressBar::hidden(),
+        };
+
+        // Return the progress tracker
+        SplitProgress {
+            format,
+            callback,
+            dataset: dataset_name.to_string(),
+            num_chunks,
+            started: Instant::now(),
+            bar,
+            chunks_done: AtomicUsize::new(existing_chunks),
+            bytes_written: AtomicU64::new(0),
+        }
+    }
+
+    // Function to record a chunk kept from an earlier run
+    pub fn chunk_skipped(&self, samples: usize) {
+        self.bar.inc(samples as u64);
+        self.chunks_done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Function to report that a worker started converting a chunk
+    pub fn chunk_started(&self, chunk: usize, samples: usize) {
+        if let Some(callback) = &self.callback {
+            callback.on_chunk_start(&self.dataset, chunk, self.num_chunks, samples);
+        }
+    }
+
+    // Function to report a sample that could not be converted
+    pub fn sample_failed(&self, error: &SampleError) {
+        if let Some(callback) = &self.callback {
+            callback.on_sample_error(error);
+        }
+    }
+
+    // Function to record a completed chunk of the given size in bytes and report it in the
+    // requested format, the size being read before the file is moved to an object storage output
+    pub fn chunk_done(&self, chunk: usize, file: &Path, samples: usize, bytes: u64) {
+        // Notify the embedding program first
+        if let Some(callback) = &self.callback {
+            callback.on_chunk_done(&self.dataset, chunk, self.num_chunks, file, samples);
+        }
+
+        // Update the totals shared between the worker threads
+        let chunks_done = self.chunks_done.fetch_add(1, Ordering::Relaxed) + 1;
+        let bytes_written = self.bytes_written.fetch_add(bytes, Ordering::Relaxed) + bytes;
+
+        // Calculate the elapsed time since the split started
+        let elapsed_secs = self.started.elapsed().as_secs_f64();
+
+        match self.format {
+            ProgressFormat::Bar => {
+                // Show the chunk count and the write throughput next to the bar
+                let megabytes_per_sec = bytes_written as f64 / 1e6 / elapsed_secs.max(1e-9);
+                self.bar.set_message(format!(
+                    "{}/{} chunks, {:.1} MB/s",
+                    chunks_done, self.num_chunks, megabytes_per_sec
+                ));
+                self.bar.inc(samples as u64);
+            }
+            ProgressFormat::Json => {
+                // Build the progress record
+                let progress = ChunkProgress {
+                    event: "chunk_done",
+                    dataset: &self.dataset,
+                    chunk,
+                    num_chunks: self.num_chunks,
+                    file,
+                    samples,
+                    bytes,
+                    elapsed_secs,
+                };
+
+                // Print the record as a single line so concurrent workers never interleave
+                println!(
+                    "{}",
+                    serde_json::to_string(&progress).expect("Failed to serialize progress")
+                );
+            }
+            _ => {}
+        }
+    }
+
+    // Function to stop drawing the bar once the split is complete
+    pub fn finish(&self) {
+        self.bar.finish();
+    }
+}
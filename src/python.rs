@@ -0,0 +1,200 @@
+use crate::cli::{InputFormat, Profile};
+use crate::{DatasetConverter, DatasetReader, Error};
+use clap::ValueEnum;
+use pyo3::exceptions::{PyIndexError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// Define a converted split opened from Python, supporting len(), indexing and iteration
+#[pyclass(name = "Dataset", frozen)]
+struct PyDataset {
+    reader: Arc<DatasetReader>,
+}
+
+// Define the iterator returned when iterating over a dataset from Python
+#[pyclass(name = "DatasetIterator")]
+struct PyDatasetIterator {
+    reader: Arc<DatasetReader>,
+    next: usize,
+}
+
+impl From<Error> for PyErr {
+    fn from(error: Error) -> PyErr {
+        match error {
+            Error::IndexOutOfRange { .. } => PyIndexError::new_err(error.to_string()),
+            _ => PyRuntimeError::new_err(error.to_string()),
+        }
+    }
+}
+
+// Function to read one sample without holding the GIL, returning it as a (bytes, str) tuple
+fn get_sample<'py>(
+    py: Python<'py>,
+    reader: &DatasetReader,
+    index: usize,
+) -> PyResult<(Bound<'py, PyBytes>, String)> {
+    let (image, label) = py.detach(|| reader.get(index))?;
+    Ok((PyBytes::new(py, &image), label))
+}
+
+#[pymethods]
+impl PyDataset {
+    // Function to open a converted split directory
+    #[new]
+    fn new(path: PathBuf) -> PyResult<Self> {
+        Ok(PyDataset {
+            reader: Arc::new(DatasetReader::open(path)?),
+        })
+    }
+
+    // Function to get the dataset name recorded in the metadata
+    #[getter]
+    fn name(&self) -> &str {
+        self.reader.dataset_name()
+    }
+
+    fn __len__(&self) -> usize {
+        self.reader.len()
+    }
+
+    // Function to read a sample by index, counting negative indices from the end like a list
+    fn __getitem__<'py>(
+        &self,
+        py: Python<'py>,
+        index: isize,
+    ) -> PyResult<(Bound<'py, PyBytes>, String)> {
+        let index = if index < 0 {
+            index + self.reader.len() as isize
+        } else {
+            index
+        };
+        let index = usize::try_from(index)
+            .map_err(|_| PyIndexError::new_err("Sample index is out of range"))?;
+        get_sample(py, &self.reader, index)
+    }
+
+    fn __iter__(&self) -> PyDatasetIterator {
+        PyDatasetIterator {
+            reader: Arc::clone(&self.reader),
+            next: 0,
+        }
+    }
+}
+
+#[pymethods]
+impl PyDatasetIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    // Function to read the next sample, which stays cheap since its chunk remains cached
+    fn __next__<'py>(
+        &mut self,
+        py: Python<'py>,
+    ) -> PyResult<Option<(Bound<'py, PyBytes>, String)>> {
+        if self.next >= self.reader.len() {
+            return Ok(None);
+        }
+        let sample = get_sample(py, &self.reader, self.next)?;
+        self.next += 1;
+        Ok(Some(sample))
+    }
+}
+
+// Function to convert an image folder dataset, releasing the GIL while it runs and returning a
+// dict with the number of converted and failed samples
+#[pyfunction]
+#[pyo3(signature = (
+    input,
+    output = None,
+    name = None,
+    profile = "hf",
+    input_format = "folder",
+    chunk_size = None,
+    threads = None,
+    seed = None,
+    max_samples = None,
+    extensions = None,
+    include = Vec::new(),
+    exclude = Vec::new(),
+    force = false,
+    append = false,
+    resume = false,
+))]
+#[allow(clippy::too_many_arguments)]
+fn convert<'py>(
+    py: Python<'py>,
+    input: PathBuf,
+    output: Option<PathBuf>,
+    name: Option<String>,
+    profile: &str,
+    input_format: &str,
+    chunk_size: Option<usize>,
+    threads: Option<usize>,
+    seed: Option<u64>,
+    max_samples: Option<usize>,
+    extensions: Option<Vec<String>>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    force: bool,
+    append: bool,
+    resume: bool,
+) -> PyResult<Bound<'py, PyDict>> {
+    // Configure the converter with the given settings, keeping the defaults of the others
+    let profile = Profile::from_str(profile, true).map_err(PyValueError::new_err)?;
+    let input_format = InputFormat::from_str(input_format, true).map_err(PyValueError::new_err)?;
+    let mut converter = DatasetConverter::new(input)
+        .profile(profile)
+        .input_format(input_format)
+        .force(force)
+        .append(append)
+        .resume(resume);
+    if let Some(output) = output {
+        converter = converter.output(output);
+    }
+    if let Some(name) = name {
+        converter = converter.name(name);
+    }
+    if let Some(chunk_size) = chunk_size {
+        converter = converter.chunk_size(chunk_size);
+    }
+    if let Some(threads) = threads {
+        converter = converter.threads(threads);
+    }
+    if let Some(seed) = seed {
+        converter = converter.shuffle(seed);
+    }
+    if let Some(max_samples) = max_samples {
+        converter = converter.max_samples(max_samples);
+    }
+    if let Some(extensions) = extensions {
+        converter = converter.extensions(extensions);
+    }
+    for pattern in include {
+        converter = converter.include(pattern);
+    }
+    for pattern in exclude {
+        converter = converter.exclude(pattern);
+    }
+
+    // Run the conversion without holding the GIL so other Python threads keep running
+    let summary = py.detach(|| converter.run())?;
+
+    // Return the summary as a dict
+    let result = PyDict::new(py);
+    result.set_item("samples", summary.samples)?;
+    result.set_item("failed_samples", summary.failed_samples)?;
+    result.set_item("cancelled", summary.cancelled)?;
+    Ok(result)
+}
+
+// Function to define the "arrow_datasets" Python module
+#[pymodule]
+fn arrow_datasets(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(convert, module)?)?;
+    module.add_class::<PyDataset>()?;
+    module.add_class::<PyDatasetIterator>()?;
+    Ok(())
+}
@@ -0,0 +1,326 @@
+use crate::chunk_io::{read_chunk_batches, ChunkBatches};
+use crate::error::{Error, Result};
+use crate::metadata::{read_chunk_rows, read_dataset_info, read_dataset_state};
+use crate::schema::{pack_frames, SampleColumns};
+use arrow::array::{
+    Array, ArrayAccessor, BinaryArray, DictionaryArray, Int64Array, LargeBinaryArray, ListArray,
+    StringArray, StructArray, TypedDictionaryArray,
+};
+use arrow::compute::concat_batches;
+use arrow::datatypes::Int32Type;
+use arrow::record_batch::RecordBatch;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Define a sample read back from a converted split as its image bytes and label
+pub type Sample = (Vec<u8>, String);
+
+// Define a reader over the chunk files of a converted split, in the order listed by state.json
+pub struct DatasetReader {
+    dataset_name: String,
+    num_samples: usize,
+    columns: SampleColumns,
+    class_names: Vec<String>,
+    chunk_paths: Vec<PathBuf>,
+    chunk_offsets: Vec<usize>,
+    cached_chunk: Mutex<Option<(usize, RecordBatch)>>,
+}
+
+// Define an iterator yielding the samples of a split lazily, opening one chunk file at a time
+pub struct Samples<'a> {
+    columns: &'a SampleColumns,
+    class_names: &'a [String],
+    chunk_paths: std::slice::Iter<'a, PathBuf>,
+    current_path: Option<&'a Path>,
+    batches: Option<ChunkBatches>,
+    batch: Option<RecordBatch>,
+    row: usize,
+}
+
+impl DatasetReader {
+    // Function to open a converted split directory by reading its metadata and state files
+    pub fn open(split_dir: impl AsRef<Path>) -> Result<Self> {
+        // Read the metadata and the state of the split
+        let split_dir = split_dir.as_ref();
+        let info = read_dataset_info(split_dir)?;
+        let state = read_dataset_state(split_dir)?;
+
+        // Compute the global index of the first sample of every chunk from the chunk index, with
+        // the total number of rows as the last entry
+        let mut chunk_offsets = vec![0];
+        for rows in read_chunk_rows(split_dir, &state)? {
+            chunk_offsets.push(chunk_offsets[chunk_offsets.len() - 1] + rows);
+        }
+
+        // Return a reader over the chunk files listed in the state
+        let class_names = info.class_names().map(<[String]>::to_vec);
+        Ok(DatasetReader {
+            dataset_name: info.dataset_name,
+            num_samples: info.num_samples,
+            class_names: class_names.unwrap_or_default(),
+            columns: info.columns.unwrap_or_default(),
+            chunk_paths: state
+                .data_files
+                .iter()
+                .map(|data_file| split_dir.join(&data_file.filename))
+                .collect(),
+            chunk_offsets,
+            cached_chunk: Mutex::new(None),
+        })
+    }
+
+    // Function to get the dataset name recorded in the metadata
+    pub fn dataset_name(&self) -> &str {
+        &self.dataset_name
+    }
+
+    // Function to get the number of samples recorded in the metadata
+    pub fn len(&self) -> usize {
+        self.num_samples
+    }
+
+    // Function to check whether the split contains no samples
+    pub fn is_empty(&self) -> bool {
+        self.num_samples == 0
+    }
+
+    // Function to get the paths of the chunk files in order
+    pub fn chunk_paths(&self) -> &[PathBuf] {
+        &self.chunk_paths
+    }
+
+    // Function to read the sample at the given global index, decoding only the chunk that holds it
+    // and keeping that chunk in memory so neighbouring lookups are served without reading again
+    pub fn get(&self, index: usize) -> Result<Sample> {
+        // Locate the chunk holding the sample and the row of the sample within it
+        let len = self.chunk_offsets[self.chunk_offsets.len() - 1];
+        if index >= len {
+            return Err(Error::IndexOutOfRange { index, len });
+        }
+        let chunk = self
+            .chunk_offsets
+            .partition_point(|&offset| offset <= index)
+            - 1;
+        let row = index - self.chunk_offsets[chunk];
+
+        // Decode the chunk unless it is the one read by the previous lookup
+        let mut cached_chunk = self.cached_chunk.lock().unwrap();
+        if !matches!(&*cached_chunk, Some((cached, _)) if *cached == chunk) {
+            *cached_chunk = Some((chunk, read_whole_chunk(&self.chunk_paths[chunk])?));
+        }
+        let (_, batch) = cached_chunk.as_ref().expect("Chunk was just cached");
+
+        // Copy the image bytes and the label of the row
+        let (images, labels) = sample_columns(
+            batch,
+            &self.columns,
+            &self.class_names,
+            &self.chunk_paths[chunk],
+        )?;
+        Ok((images.value(row), labels.value(row).to_string()))
+    }
+
+    // Function to iterate over the samples of the split without loading whole chunks in memory
+    pub fn samples(&self) -> Samples<'_> {
+        Samples {
+            columns: &self.columns,
+            class_names: &self.class_names,
+            chunk_paths: self.chunk_paths.iter(),
+            current_path: None,
+            batches: None,
+            batch: None,
+            row: 0,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a DatasetReader {
+    type Item = Result<Sample>;
+    type IntoIter = Samples<'a>;
+
+    fn into_iter(self) -> Samples<'a> {
+        self.samples()
+    }
+}
+
+// Define the image column of a chunk, stored with 32-bit or 64-bit offsets, or the text column of
+// a chunk converted from documents, or the frame lists of a chunk converted from videos
+enum ImageColumn<'b> {
+    Binary(&'b BinaryArray),
+    LargeBinary(&'b LargeBinaryArray),
+    Text(&'b StringArray),
+    Frames(&'b ListArray),
+}
+
+impl ImageColumn<'_> {
+    // Function to copy the image bytes of a row, packing the frames of a video as they were loaded
+    fn value(&self, row: usize) -> Vec<u8> {
+        match self {
+            ImageColumn::Binary(images) => images.value(row).to_vec(),
+            ImageColumn::LargeBinary(images) => images.value(row).to_vec(),
+            ImageColumn::Text(texts) => texts.value(row).as_bytes().to_vec(),
+            ImageColumn::Frames(frames) => {
+                let frames = frames.value(row);
+                let frames: Vec<Vec<u8>> = frames
+                    .as_any()
+                    .downcast_ref::<BinaryArray>()
+                    .map(|frames| frames.iter().flatten().map(<[u8]>::to_vec).collect())
+                    .unwrap_or_default();
+                pack_frames(&frames)
+            }
+        }
+    }
+}
+
+// Define the label column of a chunk, stored as UTF-8 strings, dictionary-encoded or as the ids of
+// the class names recorded in the metadata
+enum LabelColumn<'b> {
+    Utf8(&'b StringArray),
+    Dictionary(TypedDictionaryArray<'b, Int32Type, StringArray>),
+    ClassLabel(&'b Int64Array, &'b [String]),
+}
+
+impl LabelColumn<'_> {
+    // Function to get the label of a row, empty for ids without a class name
+    fn value(&self, row: usize) -> &str {
+        match self {
+            LabelColumn::Utf8(labels) => labels.value(row),
+            LabelColumn::Dictionary(labels) => labels.value(row),
+            LabelColumn::ClassLabel(ids, names) => usize::try_from(ids.value(row))
+                .ok()
+                .and_then(|id| names.get(id))
+                .map_or("", String::as_str),
+        }
+    }
+}
+
+// Function to get the image and label columns of a record batch read from a chunk
+fn sample_columns<'b>(
+    batch: &'b RecordBatch,
+    columns: &SampleColumns,
+    class_names: &'b [String],
+    path: &Path,
+) -> Result<(ImageColumn<'b>, LabelColumn<'b>)> {
+    // Look up the image column as binary data of either offset size, or as the strings of a text
+    // dataset or the frame lists of a video dataset, or as the bytes of Image structs
+    let images = batch.column_by_name(&columns.image).and_then(|column| {
+        let column = column.as_any();
+        column
+            .downcast_ref::<BinaryArray>()
+            .map(ImageColumn::Binary)
+            .or_else(|| {
+                column
+                    .downcast_ref::<LargeBinaryArray>()
+                    .map(ImageColumn::LargeBinary)
+            })
+            .or_else(|| column.downcast_ref::<StringArray>().map(ImageColumn::Text))
+            .or_else(|| column.downcast_ref::<ListArray>().map(ImageColumn::Frames))
+            .or_else(|| {
+                column
+                    .downcast_ref::<StructArray>()?
+                    .column_by_name("bytes")?
+                    .as_any()
+                    .downcast_ref::<BinaryArray>()
+                    .map(ImageColumn::Binary)
+            })
+    });
+
+    // Look up the label column as UTF-8 strings, or as a dictionary of them, or as class label ids
+    let labels = batch.column_by_name(&columns.label).and_then(|column| {
+        column
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .map(LabelColumn::Utf8)
+            .or_else(|| {
+                column
+                    .as_any()
+                    .downcast_ref::<DictionaryArray<Int32Type>>()?
+                    .downcast_dict::<StringArray>()
+                    .map(LabelColumn::Dictionary)
+            })
+            .or_else(|| {
+                column
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .map(|ids| LabelColumn::ClassLabel(ids, class_names))
+            })
+    });
+
+    // Fail when the chunk was not written with the expected schema
+    match (images, labels) {
+        (Some(images), Some(labels)) => Ok((images, labels)),
+        _ => Err(Error::ChunkMismatch {
+            kind: "Schema",
+            path: path.to_path_buf(),
+        }),
+    }
+}
+
+// Function to decode every record batch of a chunk file into a single batch
+pub(crate) fn read_whole_chunk(path: &Path) -> Result<RecordBatch> {
+    // Decode the record batches of the chunk
+    let batches = read_chunk_batches(path)?.collect::<std::result::Result<Vec<_>, _>>()?;
+
+    // Concatenate them, an empty chunk having no batch to take the schema from
+    match batches.first() {
+        Some(first) => Ok(concat_batches(&first.schema(), &batches)?),
+        None => Err(Error::ChunkMismatch {
+            kind: "Row count",
+            path: path.to_path_buf(),
+        }),
+    }
+}
+
+impl Samples<'_> {
+    // Function to read the sample at the current row of the current batch
+    fn current_sample(&self, batch: &RecordBatch) -> Result<Sample> {
+        // Get the image and label columns of the batch
+        let path = self.current_path.unwrap_or(Path::new(""));
+        let (images, labels) = sample_columns(batch, self.columns, self.class_names, path)?;
+
+        // Copy the image bytes and the label of the row
+        Ok((images.value(self.row), labels.value(self.row).to_string()))
+    }
+}
+
+impl Iterator for Samples<'_> {
+    type Item = Result<Sample>;
+
+    fn next(&mut self) -> Option<Result<Sample>> {
+        loop {
+            // Yield the next row of the current batch while there is one
+            if let Some(batch) = &self.batch {
+                if self.row < batch.num_rows() {
+                    let sample = self.current_sample(batch);
+                    self.row += 1;
+                    return Some(sample);
+                }
+            }
+
+            // Decode the next batch of the current chunk file
+            if let Some(batches) = &mut self.batches {
+                match batches.next() {
+                    Some(Ok(batch)) => {
+                        self.batch = Some(batch);
+                        self.row = 0;
+                        continue;
+                    }
+                    Some(Err(e)) => {
+                        self.batches = None;
+                        return Some(Err(e.into()));
+                    }
+                    None => self.batches = None,
+                }
+            }
+
+            // Open the next chunk file, stopping after the last one
+            let path = self.chunk_paths.next()?;
+            self.current_path = Some(path);
+            self.batch = None;
+            match read_chunk_batches(path) {
+                Ok(batches) => self.batches = Some(batches),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::error::Result;
+use arrow::array::{
+    BinaryArray, BooleanArray, ListBuilder, PrimitiveArray, StringArray, StringBuilder,
+};
+use arrow::datatypes::{
+    DataType, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type,
+    UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow::record_batch::RecordBatch;
+use std::sync::Arc;
+
+pub use arrow::array::ArrayRef;
+pub use arrow::datatypes::{Field, Schema, SchemaRef};
+pub use arrow_datasets_derive::ArrowRecord;
+
+// Define the interface of a struct mapping to one row of a record batch, usually implemented with
+// #[derive(ArrowRecord)] which turns each named field into a column of the same name
+pub trait ArrowRecord: Sized {
+    // Function to list the fields of the schema in column order
+    fn fields() -> Vec<Field>;
+
+    // Function to build one column per field from the given records
+    fn columns(records: &[Self]) -> Vec<ArrayRef>;
+
+    // Function to build the schema of the record
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(Self::fields()))
+    }
+
+    // Function to build a record batch holding the given records
+    fn to_record_batch(records: &[Self]) -> Result<RecordBatch> {
+        Ok(RecordBatch::try_new(
+            Self::schema(),
+            Self::columns(records),
+        )?)
+    }
+}
+
+// Define the interface of a Rust type that can be stored in a column of a record
+pub trait ArrowField {
+    // Function to get the Arrow data type of the column
+    fn data_type() -> DataType;
+
+    // Function to tell whether the column may contain nulls
+    fn nullable() -> bool {
+        false
+    }
+
+    // Function to build the column from the values of every record
+    fn build_column<'a>(values: impl Iterator<Item = &'a Self>) -> ArrayRef
+    where
+        Self: 'a;
+}
+
+// Define the row written to the chunk files for every converted sample
+#[derive(ArrowRecord)]
+pub struct ImageRecord {
+    pub image: Vec<u8>,
+    pub label: String,
+}
+
+// Function to build the field of a column holding values of the given type
+pub fn field<T: ArrowField>(name: &str) -> Field {
+    Field::new(name, T::data_type(), T::nullable())
+}
+
+impl ArrowField for Vec<u8> {
+    fn data_type() -> DataType {
+        DataType::Binary
+    }
+
+    fn build_column<'a>(values: impl Iterator<Item = &'a Self>) -> ArrayRef {
+        Arc::new(BinaryArray::from_iter_values(values))
+    }
+}
+
+impl ArrowField for Option<Vec<u8>> {
+    fn data_type() -> DataType {
+        DataType::Binary
+    }
+
+    fn nullable() -> bool {
+        true
+    }
+
+    fn build_column<'a>(values: impl Iterator<Item = &'a Self>) -> ArrayRef {
+        Arc::new(values.map(Option::as_deref).collect::<BinaryArray>())
+    }
+}
+
+impl ArrowField for String {
+    fn data_type() -> DataType {
+        DataType::Utf8
+    }
+
+    fn build_column<'a>(values: impl Iterator<Item = &'a Self>) -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(values))
+    }
+}
+
+impl ArrowField for Option<String> {
+    fn data_type() -> DataType {
+        DataType::Utf8
+    }
+
+    fn nullable() -> bool {
+        true
+    }
+
+    fn build_column<'a>(values: impl Iterator<Item = &'a Self>) -> ArrayRef {
+        Arc::new(values.map(Option::as_deref).collect::<StringArray>())
+    }
+}
+
+impl ArrowField for Vec<String> {
+    fn data_type() -> DataType {
+        DataType::List(Arc::new(Field::new_list_field(DataType::Utf8, true)))
+    }
+
+    fn build_column<'a>(values: impl Iterator<Item = &'a Self>) -> ArrayRef {
+        let mut builder = ListBuilder::new(StringBuilder::new());
+        for list in values {
+            builder.append_value(list.iter().map(Some));
+        }
+        Arc::new(builder.finish())
+    }
+}
+
+impl ArrowField for bool {
+    fn data_type() -> DataType {
+        DataType::Boolean
+    }
+
+    fn build_column<'a>(values: impl Iterator<Item = &'a Self>) -> ArrayRef {
+        Arc::new(values.map(|value| Some(*value)).collect::<BooleanArray>())
+    }
+}
+
+impl ArrowField for Option<bool> {
+    fn data_type() -> DataType {
+        DataType::Boolean
+    }
+
+    fn nullable() -> bool {
+        true
+    }
+
+    fn build_column<'a>(values: impl Iterator<Item = &'a Self>) -> ArrayRef {
+        Arc::new(values.copied().collect::<BooleanArray>())
+    }
+}
+
+// Implement ArrowField for a primitive type and its optional variant
+macro_rules! primitive_field {
+    ($native:ty, $arrow:ty, $data_type:expr) => {
+        impl ArrowField for $native {
+            fn data_type() -> DataType {
+                $data_type
+            }
+
+            fn build_column<'a>(values: impl Iterator<Item = &'a Self>) -> ArrayRef {
+                Arc::new(PrimitiveArray::<$arrow>::from_iter_values(values.copied()))
+            }
+        }
+
+        impl ArrowField for Option<$native> {
+            fn data_type() -> DataType {
+                $data_type
+            }
+
+            fn nullable() -> bool {
+                true
+            }
+
+            fn build_column<'a>(values: impl Iterator<Item = &'a Self>) -> ArrayRef {
+                Arc::new(values.copied().collect::<PrimitiveArray<$arrow>>())
+            }
+        }
+    };
+}
+
+primitive_field!(u8, UInt8Type, DataType::UInt8);
+primitive_field!(u16, UInt16Type, DataType::UInt16);
+primitive_field!(u32, UInt32Type, DataType::UInt32);
+primitive_field!(u64, UInt64Type, DataType::UInt64);
+primitive_field!(i8, Int8Type, DataType::Int8);
+primitive_field!(i16, Int16Type, DataType::Int16);
+primitive_field!(i32, Int32Type, DataType::Int32);
+primitive_field!(i64, Int64Type, DataType::Int64);
+primitive_field!(f32, Float32Type, DataType::Float32);
+primitive_field!(f64, Float64Type, DataType::Float64);
@@ -0,0 +1,144 @@
+use crate::error::{Error, Result};
+use crate::metadata::FileNameTemplate;
+use crate::output;
+use crate::source::{create_runtime, open_store, split_url};
+use futures::TryStreamExt;
+use log::info;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, ObjectStoreExt, WriteMultipart};
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use walkdir::WalkDir;
+
+// Define the size of the parts of the multipart uploads, above the 5 MiB minimum of S3
+const PART_SIZE: usize = 16 * 1024 * 1024;
+
+// Define how many parts of a file are uploaded at the same time
+const CONCURRENT_PARTS: usize = 4;
+
+// Define an output stored under an object storage prefix, whose files are written to a local
+// staging folder and moved to the object storage as soon as they are finished, so the local disk
+// only holds the chunks being written
+pub struct RemoteOutput {
+    store: Arc<dyn ObjectStore>,
+    base_url: String,
+    prefix: ObjectPath,
+    staging_dir: PathBuf,
+    runtime: Runtime,
+}
+
+impl RemoteOutput {
+    // Function to open the output stored under an object storage URL such as "s3://bucket/prefix",
+    // staged in a folder of the temporary directory named after the process
+    pub fn open(url: &str) -> Result<Self> {
+        let (base_url, prefix) = split_url(url);
+        Ok(RemoteOutput {
+            store: open_store(base_url)?,
+            base_url: base_url.to_string(),
+            prefix: ObjectPath::from(prefix),
+            staging_dir: env::temp_dir()
+                .join("arrow-datasets")
+                .join(format!("output-{}", process::id())),
+            runtime: create_runtime(url)?,
+        })
+    }
+
+    // Function to get the local folder the files are written to before being uploaded
+    pub fn staging_dir(&self) -> &Path {
+        &self.staging_dir
+    }
+
+    // Function to get the key of the object a staged file is uploaded to
+    fn object_key(&self, path: &Path) -> ObjectPath {
+        let relative = path.strip_prefix(&self.staging_dir).unwrap_or(path);
+        relative
+            .components()
+            .fold(self.prefix.clone(), |key, component| {
+                key.join(component.as_os_str().to_string_lossy().as_ref())
+            })
+    }
+
+    // Function to check whether any object is stored under the output prefix
+    pub fn is_non_empty(&self) -> Result<bool> {
+        let mut objects = self.store.list(Some(&self.prefix));
+        Ok(self.runtime.block_on(objects.try_next())?.is_some())
+    }
+
+    // Function to delete the chunk, metadata and exported objects left under the prefix of a
+    // split by an earlier run, its chunks named after the given template
+    pub fn clear_split(&self, split: &str, template: &FileNameTemplate) -> Result<()> {
+        let split_prefix = self.prefix.clone().join(split);
+        self.runtime.block_on(async {
+            let objects: Vec<_> = self.store.list(Some(&split_prefix)).try_collect().await?;
+            for object in objects {
+                let is_output = object
+                    .location
+                    .prefix_match(&split_prefix)
+                    .and_then(|mut parts| parts.next())
+                    .is_some_and(|name| output::is_output_file(name.as_ref(), split, template));
+                if is_output {
+                    self.store.delete(&object.location).await?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    // Function to upload a staged file in parts, several at a time, aborting the upload when the
+    // file cannot be read
+    async fn upload(&self, path: &Path, key: &ObjectPath) -> Result<()> {
+        let mut file = File::open(path).map_err(Error::io("Failed to open", path))?;
+        let mut upload =
+            WriteMultipart::new_with_chunk_size(self.store.put_multipart(key).await?, PART_SIZE);
+        let mut buffer = vec![0u8; PART_SIZE];
+        loop {
+            let read = match file.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) => {
+                    upload.abort().await?;
+                    return Err(Error::io("Failed to read", path)(e));
+                }
+            };
+            upload.wait_for_capacity(CONCURRENT_PARTS).await?;
+            upload.write(&buffer[..read]);
+        }
+        upload.finish().await?;
+        Ok(())
+    }
+
+    // Function to move a finished file of the staging folder to the object storage
+    pub fn upload_file(&self, path: &Path) -> Result<()> {
+        let key = self.object_key(path);
+        self.runtime.block_on(self.upload(path, &key))?;
+        fs::remove_file(path).map_err(Error::io("Failed to remove", path))?;
+        info!("Uploaded {:?} -> {}/{}", path, self.base_url, key);
+        Ok(())
+    }
+
+    // Function to move every file left in a folder of the staging folder to the object storage,
+    // such as the metadata of a split or the exports growing with it
+    pub fn upload_dir(&self, dir: &Path) -> Result<()> {
+        let files: Vec<PathBuf> = WalkDir::new(dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .collect();
+        for path in files {
+            self.upload_file(&path)?;
+        }
+        Ok(())
+    }
+
+    // Function to remove the staging folder once every file has been uploaded
+    pub fn remove_staging_dir(&self) {
+        let _ = fs::remove_dir_all(&self.staging_dir);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::error::Result;
+use crate::metadata::FileNameTemplate;
+use crate::source;
+use std::path::Path;
+
+// Define an output stored in object storage, which this build cannot write to
+pub enum RemoteOutput {}
+
+impl RemoteOutput {
+    // Function to report that writing to object storage is unavailable in this build
+    pub fn open(url: &str) -> Result<Self> {
+        Err(source::remote_disabled(url))
+    }
+
+    // Function to get the local folder the files are written to before being uploaded
+    pub fn staging_dir(&self) -> &Path {
+        match *self {}
+    }
+
+    // Function to check whether any object is stored under the output prefix
+    pub fn is_non_empty(&self) -> Result<bool> {
+        match *self {}
+    }
+
+    // Function to delete the objects left under the prefix of a split by an earlier run
+    pub fn clear_split(&self, _split: &str, _template: &FileNameTemplate) -> Result<()> {
+        match *self {}
+    }
+
+    // Function to move a finished file of the staging folder to the object storage
+    pub fn upload_file(&self, _path: &Path) -> Result<()> {
+        match *self {}
+    }
+
+    // Function to move every file left in a folder of the staging folder to the object storage
+    pub fn upload_dir(&self, _dir: &Path) -> Result<()> {
+        match *self {}
+    }
+
+    // Function to remove the staging folder once every file has been uploaded
+    pub fn remove_staging_dir(&self) {
+        match *self {}
+    }
+}
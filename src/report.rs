@@ -0,0 +1,77 @@
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Define the exit code of a run that could not complete
+pub const EXIT_FAILURE: i32 = 1;
+
+// Define the exit code of a run that completed but could not convert every sample
+pub const EXIT_PARTIAL_FAILURE: i32 = 3;
+
+// Define the exit code of a run that completed without converting any sample, every sample it
+// found having failed
+pub const EXIT_TOTAL_FAILURE: i32 = 4;
+
+// Define the exit code of a run stopped by Ctrl-C, following the 128 + SIGINT shell convention
+pub const EXIT_CANCELLED: i32 = 130;
+
+// Define the name of the report listing the samples that failed to convert
+const ERROR_REPORT_FILE_NAME: &str = "conversion_errors.json";
+
+// Define a struct describing a sample that could not be converted
+#[derive(Serialize)]
+pub struct SampleError {
+    pub dataset: String,
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+// Define a shared list of sample errors that worker threads append to
+pub type ErrorLog = Arc<Mutex<Vec<SampleError>>>;
+
+// Define a struct summarizing the outcome of a conversion
+pub struct ConvertSummary {
+    pub samples: usize,
+    pub failed_samples: usize,
+    pub cancelled: bool,
+}
+
+impl ConvertSummary {
+    // Function to check whether samples failed while none was written
+    pub fn is_total_failure(&self) -> bool {
+        self.samples == 0 && self.failed_samples > 0
+    }
+
+    // Function to get the exit code of the conversion, telling a cancelled run, a run that wrote no
+    // sample because every one failed and a run that skipped some of them apart
+    pub fn exit_code(&self) -> i32 {
+        if self.cancelled {
+            EXIT_CANCELLED
+        } else if self.is_total_failure() {
+            EXIT_TOTAL_FAILURE
+        } else if self.failed_samples > 0 {
+            EXIT_PARTIAL_FAILURE
+        } else {
+            0
+        }
+    }
+}
+
+// Function to write the list of failed samples into the output directory
+pub fn write_error_report(output_dir: &Path, errors: &ErrorLog) -> Result<usize> {
+    // Lock the error list while it is serialized
+    let errors = errors.lock().unwrap();
+
+    // Serialize the errors into a pretty JSON array
+    let report_json =
+        serde_json::to_string_pretty(&*errors).expect("Failed to serialize error report");
+
+    // Write the report, which is an empty array when every sample was converted
+    let report_path = output_dir.join(ERROR_REPORT_FILE_NAME);
+    fs::write(&report_path, report_json).map_err(Error::io("Failed to write", &report_path))?;
+
+    // Return the number of failed samples
+    Ok(errors.len())
+}
@@ -0,0 +1,186 @@
+use crate::chunk_io::ChunkFormat;
+use crate::cli::Profile;
+use crate::dataset::{ArrowDataset, DatasetSplit};
+use crate::error::{Error, Result};
+use crate::metadata::{
+    chunk_digests, save_chunk_index, save_metadata, DatasetInfo, FileNameTemplate,
+};
+use crate::output::clear_split_output;
+use crate::profile::output_settings;
+use crate::reader::read_whole_chunk;
+use crate::sink;
+use arrow::array::UInt32Array;
+use arrow::compute::{concat_batches, take_record_batch};
+use arrow::record_batch::RecordBatch;
+use log::info;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{rng, Rng, SeedableRng};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Define how the samples of a split are laid out again by the reshard subcommand
+pub struct ReshardSettings {
+    pub chunk_size: Option<usize>,
+    pub shuffle: bool,
+    pub seed: Option<u64>,
+    pub profile: Option<Profile>,
+}
+
+// Function to rewrite every split of a converted dataset with a new chunk size or sample order,
+// into the output directory for a single split or into one subdirectory per split otherwise
+pub fn reshard_dataset(path: &Path, output_dir: &Path, settings: &ReshardSettings) -> Result<()> {
+    // Open the dataset to locate and read the splits under the given path
+    let dataset = ArrowDataset::open(path)?;
+    let single_split = path.join("state.json").is_file();
+
+    // Rewrite each split in turn
+    for split in dataset.splits() {
+        let split_output = if single_split {
+            output_dir.to_path_buf()
+        } else {
+            output_dir.join(split.name())
+        };
+        reshard_split(split, &split_output, settings)?;
+    }
+    Ok(())
+}
+
+// Function to rewrite one converted split into the given directory
+fn reshard_split(
+    split: &DatasetSplit,
+    split_output: &Path,
+    settings: &ReshardSettings,
+) -> Result<()> {
+    // Refuse to overwrite the chunks being read
+    if split_output.exists()
+        && fs::canonicalize(split_output).ok() == fs::canonicalize(split.dir()).ok()
+    {
+        return Err(Error::Unsupported(
+            "Resharding a split into its own directory".to_string(),
+        ));
+    }
+
+    // Keep the chunk size and chunk format of the split unless new ones are requested
+    let chunk_rows = split.chunk_rows()?;
+    let chunk_size = settings
+        .chunk_size
+        .or_else(|| chunk_rows.first().copied())
+        .unwrap_or(1)
+        .max(1);
+    let output = output_settings(settings.profile.unwrap_or(match split.format() {
+        ChunkFormat::Arrow => Profile::Hf,
+        ChunkFormat::Parquet => Profile::Parquet,
+    }));
+
+    // Number every sample by its chunk and row, shuffling the order when requested
+    let mut rows: Vec<(usize, usize)> = chunk_rows
+        .iter()
+        .enumerate()
+        .flat_map(|(chunk, &count)| (0..count).map(move |row| (chunk, row)))
+        .collect();
+    let shuffle = settings.shuffle || settings.seed.is_some();
+    if shuffle {
+        let seed = settings.seed.unwrap_or_else(|| rng().random());
+        info!("Shuffling {:?} with seed {}...", split.dir(), seed);
+        rows.shuffle(&mut StdRng::seed_from_u64(seed));
+    }
+
+    // Start from a clean output directory
+    fs::create_dir_all(split_output).map_err(Error::io("Failed to create", split_output))?;
+    clear_split_output(split_output, &FileNameTemplate::default())?;
+
+    // Print status message with the new layout
+    let num_samples = rows.len();
+    let num_chunks = num_samples.div_ceil(chunk_size);
+    info!(
+        "Resharding {:?} with {} samples into {} chunks of up to {} samples...",
+        split.dir(),
+        num_samples,
+        num_chunks,
+        chunk_size
+    );
+
+    // Write every new chunk from the rows of the chunks holding its samples, keeping the last
+    // decoded chunk so samples that stay in order are read once
+    let chunk_paths = split.chunk_paths();
+    let mut cached_chunk: Option<(usize, RecordBatch)> = None;
+    let mut new_chunk_rows = Vec::with_capacity(num_chunks);
+    let chunk_files = FileNameTemplate::default().file_names("", num_chunks, output.format);
+    for (i, chunk) in rows.chunks(chunk_size).enumerate() {
+        let batch = gather_rows(chunk, &chunk_paths, &mut cached_chunk)?;
+        let file_path = split_output.join(&chunk_files[i]);
+        let mut sink = sink::create_sink(&file_path, &batch.schema(), &output)?;
+        sink.write_batch(&batch)?;
+        sink.finalize()?;
+        new_chunk_rows.push(batch.num_rows());
+        info!("Saved chunk {} -> {:?}", i, file_path);
+    }
+
+    // Save the metadata and state describing the new chunks, keeping the recorded seed only when
+    // the sample order did not change
+    let info = split.info();
+    let metadata = DatasetInfo {
+        features: info.features.clone(),
+        attribution: info.attribution.clone(),
+        ..DatasetInfo::new(
+            &info.dataset_name,
+            &info.dataset_type,
+            num_samples,
+            output.format,
+            if shuffle { None } else { info.seed },
+            &info.columns.clone().unwrap_or_default(),
+        )
+    };
+    let digests = chunk_digests(split_output, &chunk_files)?;
+    save_metadata(
+        split_output,
+        &metadata,
+        &chunk_files,
+        &digests,
+        output.format,
+    )?;
+
+    // Save the row count of every chunk so samples can be looked up by index
+    save_chunk_index(split_output, &new_chunk_rows)
+}
+
+// Function to gather the given rows, as chunk and row numbers, into one record batch in order
+fn gather_rows(
+    rows: &[(usize, usize)],
+    chunk_paths: &[PathBuf],
+    cached_chunk: &mut Option<(usize, RecordBatch)>,
+) -> Result<RecordBatch> {
+    // Visit the rows grouped by chunk, remembering where each one goes in the new chunk
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    order.sort_by_key(|&position| rows[position]);
+
+    // Take the rows of every chunk in turn, decoding each chunk once
+    let mut parts = Vec::new();
+    let mut gathered = vec![0u32; rows.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let chunk = rows[order[start]].0;
+        let end = start + order[start..].partition_point(|&position| rows[position].0 == chunk);
+        if !matches!(cached_chunk, Some((cached, _)) if *cached == chunk) {
+            *cached_chunk = Some((chunk, read_whole_chunk(&chunk_paths[chunk])?));
+        }
+        let (_, batch) = cached_chunk.as_ref().expect("Chunk was just cached");
+        let indices: UInt32Array = order[start..end]
+            .iter()
+            .map(|&position| rows[position].1 as u32)
+            .collect();
+        parts.push(take_record_batch(batch, &indices)?);
+
+        // Record the row of the gathered batch holding each sample
+        for (offset, &position) in order[start..end].iter().enumerate() {
+            gathered[position] = (start + offset) as u32;
+        }
+        start = end;
+    }
+
+    // Put the gathered rows back in the order of the new chunk
+    let schema = parts[0].schema();
+    let batch = concat_batches(&schema, &parts)?;
+    Ok(take_record_batch(&batch, &UInt32Array::from(gathered))?)
+}
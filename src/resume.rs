@@ -0,0 +1,140 @@
+use crate::chunk_io;
+use crate::error::{Error, Result};
+use crate::metadata::{FileNameTemplate, DEFAULT_FILE_NAME_TEMPLATE};
+use log::info;
+use rand::{rng, Rng};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+// Define the name of the file recording an in-progress conversion of a split
+const RESUME_FILE_NAME: &str = "resume.json";
+
+// Define a struct to store what is needed to reproduce the chunk layout of a split
+#[derive(Serialize, Deserialize)]
+struct ResumeState {
+    seed: u64,
+    num_samples: usize,
+    chunk_size: usize,
+    #[serde(default)]
+    target_chunk_bytes: Option<u64>,
+    #[serde(default)]
+    file_name_template: Option<String>,
+}
+
+// Define how a split should be converted given what already exists in its output directory
+pub enum ResumePlan {
+    // Convert every chunk from scratch, shuffling with the given seed
+    Fresh(u64),
+    // Reuse valid existing chunks, shuffling with the recorded seed
+    Resume(u64),
+    // The split was already converted completely and can be skipped
+    Complete,
+}
+
+// Function to decide how to convert a split, reusing the recorded shuffle seed when resuming
+pub fn prepare_resume_state(
+    split_output: &Path,
+    resume: bool,
+    num_samples: usize,
+    chunk_size: usize,
+    target_chunk_bytes: Option<u64>,
+    file_name_template: &FileNameTemplate,
+    seed: Option<u64>,
+) -> Result<ResumePlan> {
+    // Create the full path of the resume file
+    let resume_path = split_output.join(RESUME_FILE_NAME);
+
+    // Reuse the recorded seed when resuming an interrupted conversion
+    if resume {
+        if let Ok(content) = fs::read_to_string(&resume_path) {
+            // Parse the recorded state
+            let state: ResumeState =
+                serde_json::from_str(&content).map_err(Error::json(&resume_path))?;
+
+            // Refuse to resume when an explicit seed differs from the recorded one
+            if seed.is_some_and(|seed| seed != state.seed) {
+                return Err(Error::Resume {
+                    path: split_output.to_path_buf(),
+                    reason: format!("it was started with seed {}", state.seed),
+                });
+            }
+
+            // Refuse to resume when the chunk layout would no longer match
+            if state.num_samples != num_samples || state.chunk_size != chunk_size {
+                return Err(Error::Resume {
+                    path: split_output.to_path_buf(),
+                    reason: format!(
+                        "it was started with {} samples and chunk size {}, now {} samples and chunk size {}",
+                        state.num_samples, state.chunk_size, num_samples, chunk_size
+                    ),
+                });
+            }
+            if state.target_chunk_bytes != target_chunk_bytes {
+                return Err(Error::Resume {
+                    path: split_output.to_path_buf(),
+                    reason: format!(
+                        "it was started with target chunk size {:?}, now {:?}",
+                        state.target_chunk_bytes, target_chunk_bytes
+                    ),
+                });
+            }
+
+            // Refuse to resume when the existing chunks were named after another template
+            let recorded_template = state
+                .file_name_template
+                .as_deref()
+                .unwrap_or(DEFAULT_FILE_NAME_TEMPLATE);
+            if recorded_template != file_name_template.as_str() {
+                return Err(Error::Resume {
+                    path: split_output.to_path_buf(),
+                    reason: format!(
+                        "it was started with file name template '{}', now '{}'",
+                        recorded_template,
+                        file_name_template.as_str()
+                    ),
+                });
+            }
+
+            // Print a message indicating the conversion is resumed
+            info!("Resuming conversion in {:?}", split_output);
+
+            // Return the recorded seed so the samples are shuffled in the same order
+            return Ok(ResumePlan::Resume(state.seed));
+        }
+
+        // Without a resume file, a written state means the split finished in an earlier run
+        if split_output.join("state.json").is_file() {
+            info!("Split in {:?} is already complete", split_output);
+            return Ok(ResumePlan::Complete);
+        }
+    }
+
+    // Use the requested seed or draw a fresh one for this conversion
+    let state = ResumeState {
+        seed: seed.unwrap_or_else(|| rng().random()),
+        num_samples,
+        chunk_size,
+        target_chunk_bytes,
+        file_name_template: Some(file_name_template.as_str().to_string()),
+    };
+
+    // Record the state so an interrupted conversion can be resumed later
+    let state_json = serde_json::to_string_pretty(&state).expect("Failed to serialize resume.json");
+    fs::write(&resume_path, state_json).map_err(Error::io("Failed to write", &resume_path))?;
+
+    // Return the new seed
+    Ok(ResumePlan::Fresh(state.seed))
+}
+
+// Function to remove the resume file once a split has been fully converted
+pub fn clear_resume_state(split_output: &Path) {
+    // Ignore a missing file since there is nothing left to resume either way
+    let _ = fs::remove_file(split_output.join(RESUME_FILE_NAME));
+}
+
+// Function to count the rows of a chunk file if it exists and can be read completely
+pub fn valid_chunk_rows(chunk_path: &Path) -> Option<usize> {
+    // Decode every record batch, treating a missing or truncated file as invalid
+    chunk_io::count_chunk_rows(chunk_path).ok()
+}
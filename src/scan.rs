@@ -0,0 +1,61 @@
+use crate::error::{Error, Result};
+use crate::source::{DataSource, SampleRef};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// Define the name of the summary of the scanned splits written next to their manifests
+const SCAN_SUMMARY_FILE_NAME: &str = "scan_summary.json";
+
+// Define a struct summarizing the samples found in a split without reading them
+#[derive(Serialize)]
+pub struct SplitScan {
+    pub samples: usize,
+    pub bytes: u64,
+    pub classes: BTreeMap<String, usize>,
+}
+
+// Function to write the manifest of a split, one row per sample with its path, label and size as
+// reported by the source without reading it, named after the split so the manifests of every split
+// can be converted later as a CSV input
+pub fn write_split_manifest(
+    output_dir: &Path,
+    split: &str,
+    source: &dyn DataSource,
+    samples: &[SampleRef],
+) -> Result<SplitScan> {
+    // Write the rows in the listing order while counting the samples of every class
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["path", "label", "size"])
+        .expect("Failed to serialize manifest");
+    let mut scan = SplitScan {
+        samples: samples.len(),
+        bytes: 0,
+        classes: BTreeMap::new(),
+    };
+    for sample in samples {
+        let (path, label) = sample;
+        let size = source.size_hint(sample);
+        writer
+            .write_record([&*path.to_string_lossy(), label, &size.to_string()])
+            .expect("Failed to serialize manifest");
+        scan.bytes += size;
+        *scan.classes.entry(label.clone()).or_default() += 1;
+    }
+    let manifest = writer.into_inner().expect("Failed to serialize manifest");
+
+    // Save the manifest as a CSV file named after the split
+    let manifest_path = output_dir.join(format!("{}.csv", split));
+    fs::write(&manifest_path, manifest).map_err(Error::io("Failed to write", &manifest_path))?;
+    Ok(scan)
+}
+
+// Function to write the sample count, total size and class counts of every scanned split
+pub fn write_scan_summary(output_dir: &Path, splits: &BTreeMap<String, SplitScan>) -> Result<()> {
+    let summary_json =
+        serde_json::to_string_pretty(splits).expect("Failed to serialize scan summary");
+    let summary_path = output_dir.join(SCAN_SUMMARY_FILE_NAME);
+    fs::write(&summary_path, summary_json).map_err(Error::io("Failed to write", &summary_path))
+}
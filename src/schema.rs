@@ -0,0 +1,623 @@
+use crate::computed::{BuiltinColumn, ComputedColumn, ComputedSample};
+use crate::error::{Error, Result};
+use crate::metadata::{Feature, Features};
+use crate::record::{ArrowRecord, ImageRecord};
+use crate::source::Record;
+use arrow::array::{
+    ArrayRef, BinaryArray, BinaryBuilder, BooleanArray, DictionaryArray, FixedSizeListBuilder,
+    Float64Array, Float64Builder, Int64Array, Int64Builder, LargeBinaryArray, ListBuilder,
+    StringArray, StringBuilder, StructArray,
+};
+use arrow::datatypes::{DataType, Field, Fields, Int32Type, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+// Define the number of coordinates of a bounding box, stored as [x, y, width, height]
+const BBOX_COORDINATES: i32 = 4;
+
+// Define the key of the schema metadata from which HuggingFace datasets reads the features
+const HUGGINGFACE_METADATA_KEY: &str = "huggingface";
+
+// Define the data types an extra nullable column can be declared with, the list types being read
+// from JSON arrays in the sample fields
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    Utf8,
+    Binary,
+    Int64,
+    Float64,
+    Bool,
+    Utf8List,
+    Int64List,
+    Float64List,
+    BboxList,
+}
+
+impl ColumnType {
+    // Function to get the Arrow data type of columns of this type
+    fn data_type(self) -> DataType {
+        match self {
+            ColumnType::Utf8 => DataType::Utf8,
+            ColumnType::Binary => DataType::Binary,
+            ColumnType::Int64 => DataType::Int64,
+            ColumnType::Float64 => DataType::Float64,
+            ColumnType::Bool => DataType::Boolean,
+            ColumnType::Utf8List => list_type(DataType::Utf8),
+            ColumnType::Int64List => list_type(DataType::Int64),
+            ColumnType::Float64List => list_type(DataType::Float64),
+            ColumnType::BboxList => list_type(DataType::FixedSizeList(
+                Arc::new(Field::new_list_field(DataType::Float64, true)),
+                BBOX_COORDINATES,
+            )),
+        }
+    }
+
+    // Function to build a column of this type from the metadata values of the samples, leaving a
+    // row null when its sample has no value or one that does not parse as the type
+    fn build_column<'a>(self, values: impl Iterator<Item = Option<&'a str>>) -> ArrayRef {
+        match self {
+            ColumnType::Utf8 => Arc::new(values.collect::<StringArray>()),
+            ColumnType::Binary => Arc::new(
+                values
+                    .map(|value| value.map(str::as_bytes))
+                    .collect::<BinaryArray>(),
+            ),
+            ColumnType::Int64 => Arc::new(
+                values
+                    .map(|value| value.and_then(|value| value.parse().ok()))
+                    .collect::<Int64Array>(),
+            ),
+            ColumnType::Float64 => Arc::new(
+                values
+                    .map(|value| value.and_then(|value| value.parse().ok()))
+                    .collect::<Float64Array>(),
+            ),
+            ColumnType::Bool => Arc::new(
+                values
+                    .map(|value| value.and_then(|value| value.parse().ok()))
+                    .collect::<BooleanArray>(),
+            ),
+            ColumnType::Utf8List => {
+                let mut builder = ListBuilder::new(StringBuilder::new());
+                for list in values.map(parse_list::<String>) {
+                    builder.append_option(list.map(|list| list.into_iter().map(Some)));
+                }
+                Arc::new(builder.finish())
+            }
+            ColumnType::Int64List => {
+                let mut builder = ListBuilder::new(Int64Builder::new());
+                for list in values.map(parse_list::<i64>) {
+                    builder.append_option(list.map(|list| list.into_iter().map(Some)));
+                }
+                Arc::new(builder.finish())
+            }
+            ColumnType::Float64List => {
+                let mut builder = ListBuilder::new(Float64Builder::new());
+                for list in values.map(parse_list::<f64>) {
+                    builder.append_option(list.map(|list| list.into_iter().map(Some)));
+                }
+                Arc::new(builder.finish())
+            }
+            ColumnType::BboxList => {
+                let boxes = FixedSizeListBuilder::new(Float64Builder::new(), BBOX_COORDINATES);
+                let mut builder = ListBuilder::new(boxes);
+                for list in values.map(parse_list::<[f64; BBOX_COORDINATES as usize]>) {
+                    match list {
+                        Some(list) => {
+                            for bbox in list {
+                                builder.values().values().append_slice(&bbox);
+                                builder.values().append(true);
+                            }
+                            builder.append(true);
+                        }
+                        None => builder.append_null(),
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+        }
+    }
+}
+
+// Function to get the children of an Image or Audio struct as laid out by the Image and Audio
+// features of HuggingFace, the encoded bytes and the name of the file they were read from
+fn image_struct_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("bytes", DataType::Binary, true),
+        Field::new("path", DataType::Utf8, true),
+    ])
+}
+
+// Function to get the type of a nullable list column holding nullable items of the given type
+fn list_type(item_type: DataType) -> DataType {
+    DataType::List(Arc::new(Field::new_list_field(item_type, true)))
+}
+
+// Function to parse the JSON array stored in a sample field, or None when it holds something else
+fn parse_list<T: DeserializeOwned>(value: Option<&str>) -> Option<Vec<T>> {
+    serde_json::from_str(value?).ok()
+}
+
+// Define the kinds of samples a source can produce, which set the default name and the type of the
+// sample column
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum SampleKind {
+    #[default]
+    Image,
+    Text,
+    Audio,
+    Video,
+    VideoFrames,
+    Paired,
+    Array,
+}
+
+impl SampleKind {
+    // Function to get the default name of the sample column for this kind of samples
+    fn column_name(self) -> &'static str {
+        match self {
+            SampleKind::Image => "image",
+            SampleKind::Text => "text",
+            SampleKind::Audio => "audio",
+            SampleKind::Video => "video",
+            SampleKind::VideoFrames => "frames",
+            SampleKind::Paired => "input",
+            SampleKind::Array => "array",
+        }
+    }
+
+    // Function to get the type of dataset recorded in the metadata, named after the HuggingFace
+    // builder loading this kind of samples
+    pub fn dataset_type(self) -> &'static str {
+        match self {
+            SampleKind::Text => "text",
+            SampleKind::Audio => "audiofolder",
+            SampleKind::Video | SampleKind::VideoFrames => "videofolder",
+            SampleKind::Image | SampleKind::Paired | SampleKind::Array => "imagefolder",
+        }
+    }
+}
+
+// Define the name of the column holding the target image of paired samples, placed right after
+// their input image
+const TARGET_COLUMN: &str = "target";
+
+// Function to pack the frames of a video, or the input and target images of a pair, into the bytes
+// of a single record, each frame preceded by
+// its length as a little-endian 32-bit integer
+pub fn pack_frames(frames: &[Vec<u8>]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(frames.iter().map(|frame| 4 + frame.len()).sum());
+    for frame in frames {
+        bytes.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(frame);
+    }
+    bytes
+}
+
+// Function to split the bytes of a record packed by pack_frames back into its frames, ignoring a
+// truncated last frame
+pub fn unpack_frames(mut bytes: &[u8]) -> Vec<&[u8]> {
+    let mut frames = Vec::new();
+    while let Some((length, rest)) = bytes.split_first_chunk::<4>() {
+        let length = u32::from_le_bytes(*length) as usize;
+        let Some((frame, rest)) = rest.split_at_checked(length) else {
+            break;
+        };
+        frames.push(frame);
+        bytes = rest;
+    }
+    frames
+}
+
+// Define the names of the image and label columns, recorded in the metadata when renamed so the
+// samples can be read back
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampleColumns {
+    pub image: String,
+    pub label: String,
+}
+
+impl Default for SampleColumns {
+    fn default() -> Self {
+        SampleColumns {
+            image: "image".to_string(),
+            label: "label".to_string(),
+        }
+    }
+}
+
+// Define the settings shaping the columns of the chunk files, read from the [schema] section of a
+// config file or set through the converter builder
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchemaSettings {
+    pub image_column: Option<String>,
+    pub label_column: Option<String>,
+    pub large_binary: bool,
+    pub dictionary_labels: bool,
+    pub class_labels: bool,
+    pub image_struct: bool,
+    pub nullable_columns: BTreeMap<String, ColumnType>,
+    pub constant_columns: BTreeMap<String, String>,
+    pub computed_columns: Vec<BuiltinColumn>,
+    #[serde(skip)]
+    pub sample_kind: SampleKind,
+    #[serde(skip)]
+    pub class_names: Vec<String>,
+}
+
+impl SchemaSettings {
+    // Function to get the names of the image and label columns, using the defaults when not renamed
+    // and naming the sample column after the kind of samples of the source
+    pub fn sample_columns(&self) -> SampleColumns {
+        let default = SampleColumns::default();
+        SampleColumns {
+            image: self
+                .image_column
+                .clone()
+                .unwrap_or_else(|| self.sample_kind.column_name().to_string()),
+            label: self.label_column.clone().unwrap_or(default.label),
+        }
+    }
+
+    // Function to check whether the samples are stored as structs of their bytes and file name,
+    // which images are when requested and audio files always are
+    fn struct_samples(&self) -> bool {
+        self.image_struct || self.sample_kind == SampleKind::Audio
+    }
+
+    // Function to describe the sample and label columns as HuggingFace features when they are
+    // stored as Image or Audio structs or as class label ids, or None when they are plain values,
+    // the sampling rate of Audio features being recorded in the metadata of the split once every
+    // sample is read
+    pub fn features(&self) -> Option<Features> {
+        let columns = self.sample_columns();
+        let mut features = Vec::new();
+        if self.sample_kind == SampleKind::Audio {
+            let sampling_rate = None;
+            features.push((columns.image, Feature::Audio { sampling_rate }));
+        } else if self.image_struct {
+            features.push((columns.image, Feature::Image));
+        }
+        if self.class_labels {
+            let names = self.class_names.clone();
+            features.push((columns.label, Feature::ClassLabel { names }));
+        }
+        (!features.is_empty()).then_some(Features(features))
+    }
+
+    // Function to add the constant columns of a split, which replace shared ones of the same name
+    pub fn with_constants(&self, constants: &BTreeMap<String, String>) -> SchemaSettings {
+        let mut settings = self.clone();
+        settings.constant_columns.extend(constants.clone());
+        settings
+    }
+
+    // Function to add the metadata columns found by a source as nullable columns, keeping the type
+    // of those declared explicitly
+    pub fn with_metadata_columns(&self, columns: &BTreeMap<String, ColumnType>) -> SchemaSettings {
+        let mut settings = self.clone();
+        for (name, column_type) in columns {
+            settings
+                .nullable_columns
+                .entry(name.clone())
+                .or_insert(*column_type);
+        }
+        settings
+    }
+}
+
+// Function to name the dtype of the HuggingFace Value feature read from the given Arrow type
+fn value_dtype(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Boolean => "bool".to_string(),
+        DataType::Utf8 | DataType::Utf8View => "string".to_string(),
+        DataType::LargeUtf8 => "large_string".to_string(),
+        DataType::Binary | DataType::BinaryView => "binary".to_string(),
+        DataType::LargeBinary => "large_binary".to_string(),
+        DataType::Dictionary(_, value_type) => value_dtype(value_type),
+        // Numbers are named like their Arrow type, such as int64 or float32
+        data_type => data_type.to_string().to_lowercase(),
+    }
+}
+
+// Function to describe a column of the given Arrow type as a HuggingFace feature, lists becoming
+// sequences of the feature of their items, with a length when it is fixed
+fn arrow_feature(data_type: &DataType) -> Feature {
+    match data_type {
+        DataType::List(item) | DataType::LargeList(item) => Feature::Sequence {
+            feature: Box::new(arrow_feature(item.data_type())),
+            length: None,
+        },
+        DataType::FixedSizeList(item, length) => Feature::Sequence {
+            feature: Box::new(arrow_feature(item.data_type())),
+            length: Some(*length),
+        },
+        data_type => Feature::Value {
+            dtype: value_dtype(data_type),
+        },
+    }
+}
+
+// Define the schema of the chunk files of a split, built from the schema settings
+pub struct OutputSchema {
+    settings: SchemaSettings,
+    computed: Vec<Arc<dyn ComputedColumn>>,
+    class_ids: HashMap<String, i64>,
+    schema: SchemaRef,
+    features: Features,
+}
+
+impl OutputSchema {
+    // Function to build the schema with the image and label columns first, then the nullable
+    // columns, the constant columns and the computed columns, the built-in ones requested in the
+    // settings before the given ones, refusing settings that define a column twice
+    pub fn new(settings: &SchemaSettings, computed: &[Arc<dyn ComputedColumn>]) -> Result<Self> {
+        // Refuse to store the labels both as class label ids and dictionary-encoded
+        if settings.class_labels && settings.dictionary_labels {
+            return Err(Error::Unsupported(
+                "Class labels with dictionary-encoded labels".to_string(),
+            ));
+        }
+
+        // Refuse to store as Image structs anything but images, whose bytes HuggingFace reads with
+        // 32-bit offsets
+        if settings.image_struct && settings.sample_kind != SampleKind::Image {
+            return Err(Error::Unsupported(
+                "Image structs with samples other than images".to_string(),
+            ));
+        }
+        if settings.struct_samples() && settings.large_binary {
+            return Err(Error::Unsupported(
+                "Image and Audio structs with LargeBinary samples".to_string(),
+            ));
+        }
+
+        // Gather the computed columns in column order
+        let computed: Vec<Arc<dyn ComputedColumn>> = settings
+            .computed_columns
+            .iter()
+            .map(|builtin| builtin.column())
+            .chain(computed.iter().cloned())
+            .collect();
+
+        // Choose the type of the image column, UTF-8 strings for the documents of text sources
+        let columns = settings.sample_columns();
+        let image_type = if settings.sample_kind == SampleKind::Text {
+            DataType::Utf8
+        } else if settings.sample_kind == SampleKind::VideoFrames {
+            list_type(DataType::Binary)
+        } else if settings.large_binary {
+            DataType::LargeBinary
+        } else {
+            DataType::Binary
+        };
+
+        // List every field in column order, the target image of paired samples following the input,
+        // Image and Audio structs being nullable like the features of HuggingFace
+        let mut fields = if settings.struct_samples() {
+            vec![Field::new(
+                &columns.image,
+                DataType::Struct(image_struct_fields()),
+                true,
+            )]
+        } else {
+            vec![Field::new(&columns.image, image_type.clone(), false)]
+        };
+        if settings.sample_kind == SampleKind::Paired {
+            fields.push(Field::new(TARGET_COLUMN, image_type, false));
+        }
+        let label_type = if settings.class_labels {
+            DataType::Int64
+        } else if settings.dictionary_labels {
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+        } else {
+            DataType::Utf8
+        };
+        fields.push(Field::new(&columns.label, label_type, false));
+        for (name, column_type) in &settings.nullable_columns {
+            fields.push(Field::new(name, column_type.data_type(), true));
+        }
+        for name in settings.constant_columns.keys() {
+            fields.push(Field::new(name, DataType::Utf8, false));
+        }
+        fields.extend(computed.iter().map(|column| column.field()));
+
+        // Refuse column names used by more than one field
+        let mut names = HashSet::new();
+        for field in &fields {
+            if !names.insert(field.name()) {
+                return Err(Error::DuplicateColumn(field.name().clone()));
+            }
+        }
+
+        // Describe every column as a HuggingFace feature, the Image and Audio structs and class label
+        // ids as such and the other columns after their Arrow type
+        let described = settings.features().unwrap_or_default();
+        let features = Features(
+            fields
+                .iter()
+                .map(|field| {
+                    let feature = described
+                        .get(field.name())
+                        .cloned()
+                        .unwrap_or_else(|| arrow_feature(field.data_type()));
+                    (field.name().clone(), feature)
+                })
+                .collect(),
+        );
+
+        // Record the features in the schema metadata, where HuggingFace datasets reads them when
+        // loading the chunk files
+        let metadata = serde_json::json!({ "info": { "features": features } });
+        let schema = Schema::new(fields).with_metadata(HashMap::from([(
+            HUGGINGFACE_METADATA_KEY.to_string(),
+            metadata.to_string(),
+        )]));
+
+        // Return the schema along with the settings needed to fill it
+        Ok(OutputSchema {
+            settings: settings.clone(),
+            computed,
+            class_ids: settings
+                .class_names
+                .iter()
+                .enumerate()
+                .map(|(id, name)| (name.clone(), id as i64))
+                .collect(),
+            schema: Arc::new(schema),
+            features,
+        })
+    }
+
+    // Function to get the Arrow schema of the chunk files
+    pub fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    // Function to get the HuggingFace features describing every column of the chunk files
+    pub fn features(&self) -> &Features {
+        &self.features
+    }
+
+    // Function to build a record batch holding the given samples under this schema, with the
+    // path each one was loaded from
+    pub fn to_record_batch(&self, samples: Vec<(&Path, Record)>) -> Result<RecordBatch> {
+        // Compute the derived columns while the samples are still whole
+        let computed_samples: Vec<ComputedSample<'_>> = samples
+            .iter()
+            .map(|(path, record)| ComputedSample { path, record })
+            .collect();
+        let computed: Vec<ArrayRef> = self
+            .computed
+            .iter()
+            .map(|column| column.compute(&computed_samples))
+            .collect();
+
+        // Fill the nullable columns from the metadata of the same name, leaving the rest null
+        let nullable: Vec<ArrayRef> = self
+            .settings
+            .nullable_columns
+            .iter()
+            .map(|(name, column_type)| {
+                column_type.build_column(
+                    samples
+                        .iter()
+                        .map(|(_, record)| record.metadata.get(name).map(String::as_str)),
+                )
+            })
+            .collect();
+
+        // Name the samples of Image and Audio structs after the files they were loaded from
+        let file_names: Vec<String> = if self.settings.struct_samples() {
+            samples
+                .iter()
+                .map(|(path, _)| {
+                    path.file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into()
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // Move the image bytes and labels into the records of the chunk
+        let records: Vec<ImageRecord> = samples
+            .into_iter()
+            .map(|(_, record)| ImageRecord {
+                image: record.bytes,
+                label: record.label,
+            })
+            .collect();
+
+        // Build the image and label columns from the records, with 64-bit offsets for the images
+        // when requested, with the documents as strings for text sources, or with the frames of
+        // every video as a list, or with the input and target images of pairs; the labels are
+        // stored as the ids of their class names, -1 for unknown labels, or dictionary-encoded,
+        // storing every distinct label of the chunk once, when requested
+        let labels = || -> ArrayRef {
+            let labels = records.iter().map(|r| r.label.as_str());
+            if self.settings.class_labels {
+                Arc::new(Int64Array::from_iter_values(
+                    labels.map(|label| self.class_ids.get(label).copied().unwrap_or(-1)),
+                ))
+            } else if self.settings.dictionary_labels {
+                Arc::new(labels.collect::<DictionaryArray<Int32Type>>())
+            } else {
+                Arc::new(StringArray::from_iter_values(labels))
+            }
+        };
+        let mut columns = match self.settings.sample_kind {
+            SampleKind::Text => {
+                let texts = StringArray::from_iter_values(
+                    records
+                        .iter()
+                        .map(|r| String::from_utf8_lossy(&r.image).into_owned()),
+                );
+                vec![Arc::new(texts) as ArrayRef, labels()]
+            }
+            SampleKind::VideoFrames => {
+                let mut frames = ListBuilder::new(BinaryBuilder::new());
+                for record in &records {
+                    for frame in unpack_frames(&record.image) {
+                        frames.values().append_value(frame);
+                    }
+                    frames.append(true);
+                }
+                vec![Arc::new(frames.finish()) as ArrayRef, labels()]
+            }
+            SampleKind::Paired => {
+                let pairs: Vec<Vec<&[u8]>> =
+                    records.iter().map(|r| unpack_frames(&r.image)).collect();
+                let image = |index: usize| -> ArrayRef {
+                    let images = pairs
+                        .iter()
+                        .map(|pair| pair.get(index).copied().unwrap_or(&[]));
+                    if self.settings.large_binary {
+                        Arc::new(LargeBinaryArray::from_iter_values(images))
+                    } else {
+                        Arc::new(BinaryArray::from_iter_values(images))
+                    }
+                };
+                vec![image(0), image(1), labels()]
+            }
+            _ if self.settings.struct_samples() => {
+                let images = BinaryArray::from_iter_values(records.iter().map(|r| &r.image));
+                let paths = StringArray::from_iter_values(&file_names);
+                let images = StructArray::new(
+                    image_struct_fields(),
+                    vec![Arc::new(images), Arc::new(paths)],
+                    None,
+                );
+                vec![Arc::new(images) as ArrayRef, labels()]
+            }
+            _ if self.settings.large_binary => {
+                let images = LargeBinaryArray::from_iter_values(records.iter().map(|r| &r.image));
+                vec![Arc::new(images) as ArrayRef, labels()]
+            }
+            _ if self.settings.dictionary_labels || self.settings.class_labels => {
+                let images = BinaryArray::from_iter_values(records.iter().map(|r| &r.image));
+                vec![Arc::new(images) as ArrayRef, labels()]
+            }
+            _ => ImageRecord::columns(&records),
+        };
+
+        // Add the nullable columns and repeat the value of every constant column
+        columns.extend(nullable);
+        for value in self.settings.constant_columns.values() {
+            let column: ArrayRef = Arc::new(StringArray::from(vec![value.as_str(); records.len()]));
+            columns.push(column);
+        }
+        columns.extend(computed);
+
+        // Assemble the columns under the schema
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
@@ -0,0 +1,307 @@
+// Allow returning the large tonic Status, which is the error type of every Flight call
+#![allow(clippy::result_large_err)]
+
+use crate::cancel::CancellationToken;
+use crate::chunk_io::read_chunk_batches;
+use crate::dataset::{ArrowDataset, DatasetSplit};
+use crate::error::{Error, Result};
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::ipc::writer::IpcWriteOptions;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PollInfo, PutResult, SchemaAsIpc, SchemaResult, Ticket,
+};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use log::{info, warn};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status, Streaming};
+
+// Define the number of record batches read ahead of the client in every stream
+const READ_AHEAD_BATCHES: usize = 2;
+
+// Define a Flight service exposing every split of a converted dataset, and every chunk of a split,
+// as a flight addressed by the path "split" or "split/chunk"
+struct DatasetFlightService {
+    dataset: Arc<ArrowDataset>,
+}
+
+// Define the part of a dataset a descriptor or ticket refers to
+struct Target<'a> {
+    split: &'a DatasetSplit,
+    chunk: Option<usize>,
+}
+
+// Function to turn a crate error into the status returned to the client
+fn internal(error: Error) -> Status {
+    Status::internal(error.to_string())
+}
+
+// Function to get the schema of a split, which is empty when the split has no chunk to read it from
+fn split_schema(split: &DatasetSplit) -> Result<SchemaRef> {
+    Ok(split.schema()?.unwrap_or_else(|| Arc::new(Schema::empty())))
+}
+
+impl DatasetFlightService {
+    // Function to resolve the path segments of a descriptor or ticket to a split and optional chunk
+    fn resolve<'a>(&'a self, path: &[&str]) -> std::result::Result<Target<'a>, Status> {
+        // Look up the split named by the first segment
+        let (name, chunk) = match path {
+            [name] => (*name, None),
+            [name, chunk] => (*name, Some(*chunk)),
+            _ => {
+                return Err(Status::invalid_argument(
+                    "Expected a path of the form \"split\" or \"split/chunk\"",
+                ))
+            }
+        };
+        let split = self
+            .dataset
+            .split(name)
+            .ok_or_else(|| Status::not_found(format!("Unknown split '{}'", name)))?;
+
+        // Parse the chunk index and check that the split has such a chunk
+        let chunk = chunk
+            .map(|chunk| {
+                chunk
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&chunk| chunk < split.chunk_paths().len())
+                    .ok_or_else(|| {
+                        Status::not_found(format!("Unknown chunk '{}' of split '{}'", chunk, name))
+                    })
+            })
+            .transpose()?;
+        Ok(Target { split, chunk })
+    }
+
+    // Function to resolve the path of a flight descriptor
+    fn resolve_descriptor<'a>(
+        &'a self,
+        descriptor: &FlightDescriptor,
+    ) -> std::result::Result<Target<'a>, Status> {
+        let path: Vec<&str> = descriptor.path.iter().map(String::as_str).collect();
+        self.resolve(&path)
+    }
+
+    // Function to describe a split or one of its chunks, with one endpoint per chunk so clients can
+    // fetch the chunks in parallel
+    fn flight_info(&self, target: &Target<'_>) -> Result<FlightInfo> {
+        // Select the chunks covered by the target
+        let split = target.split;
+        let chunk_rows = split.chunk_rows()?;
+        let chunks: Vec<usize> = match target.chunk {
+            Some(chunk) => vec![chunk],
+            None => (0..chunk_rows.len()).collect(),
+        };
+
+        // Describe the target with its schema and the number of rows of the selected chunks
+        let mut path = vec![split.name().to_string()];
+        path.extend(target.chunk.map(|chunk| chunk.to_string()));
+        let total_records: usize = chunks.iter().map(|&chunk| chunk_rows[chunk]).sum();
+        let schema = split_schema(split)?;
+        let mut info = FlightInfo::new()
+            .try_with_schema(&schema)?
+            .with_descriptor(FlightDescriptor::new_path(path))
+            .with_total_records(total_records as i64)
+            .with_ordered(true);
+
+        // Add an endpoint whose ticket reads a single chunk, for every selected chunk
+        for chunk in chunks {
+            let ticket = format!("{}/{}", split.name(), chunk);
+            info = info.with_endpoint(FlightEndpoint::new().with_ticket(Ticket::new(ticket)));
+        }
+        Ok(info)
+    }
+}
+
+#[tonic::async_trait]
+impl FlightService for DatasetFlightService {
+    type HandshakeStream = BoxStream<'static, std::result::Result<HandshakeResponse, Status>>;
+    type ListFlightsStream = BoxStream<'static, std::result::Result<FlightInfo, Status>>;
+    type DoGetStream = BoxStream<'static, std::result::Result<FlightData, Status>>;
+    type DoPutStream = BoxStream<'static, std::result::Result<PutResult, Status>>;
+    type DoActionStream = BoxStream<'static, std::result::Result<arrow_flight::Result, Status>>;
+    type ListActionsStream = BoxStream<'static, std::result::Result<ActionType, Status>>;
+    type DoExchangeStream = BoxStream<'static, std::result::Result<FlightData, Status>>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> std::result::Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented(
+            "The dataset server needs no handshake",
+        ))
+    }
+
+    // Function to list one flight per split of the dataset
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> std::result::Result<Response<Self::ListFlightsStream>, Status> {
+        let flights = self
+            .dataset
+            .splits()
+            .iter()
+            .map(|split| {
+                self.flight_info(&Target { split, chunk: None })
+                    .map_err(internal)
+            })
+            .collect::<Vec<_>>();
+        Ok(Response::new(stream::iter(flights).boxed()))
+    }
+
+    // Function to describe the split or chunk named by the descriptor
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        let target = self.resolve_descriptor(request.get_ref())?;
+        Ok(Response::new(self.flight_info(&target).map_err(internal)?))
+    }
+
+    async fn poll_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<PollInfo>, Status> {
+        Err(Status::unimplemented(
+            "Flights of a converted dataset are always complete",
+        ))
+    }
+
+    // Function to return the schema of the split named by the descriptor
+    async fn get_schema(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<SchemaResult>, Status> {
+        let target = self.resolve_descriptor(request.get_ref())?;
+        let schema = split_schema(target.split).map_err(internal)?;
+        let result = SchemaAsIpc::new(&schema, &IpcWriteOptions::default())
+            .try_into()
+            .map_err(|e: arrow::error::ArrowError| Status::internal(e.to_string()))?;
+        Ok(Response::new(result))
+    }
+
+    // Function to stream the record batches of the split or chunk named by the ticket, reading the
+    // chunk files on the blocking thread pool a few batches ahead of the client
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> std::result::Result<Response<Self::DoGetStream>, Status> {
+        // Resolve the ticket to the chunk files to read
+        let ticket = std::str::from_utf8(&request.get_ref().ticket)
+            .map_err(|_| Status::invalid_argument("Ticket is not valid UTF-8"))?;
+        let path: Vec<&str> = ticket.split('/').collect();
+        let target = self.resolve(&path)?;
+        let chunk_paths: Vec<PathBuf> = match target.chunk {
+            Some(chunk) => vec![target.split.chunk_paths()[chunk].clone()],
+            None => target.split.chunk_paths(),
+        };
+        let schema = split_schema(target.split).map_err(internal)?;
+
+        // Read the batches of every chunk in order, stopping when the client goes away
+        let (tx, rx) = mpsc::channel(READ_AHEAD_BATCHES);
+        tokio::task::spawn_blocking(move || {
+            for chunk_path in chunk_paths {
+                let batches = match read_chunk_batches(&chunk_path) {
+                    Ok(batches) => batches,
+                    Err(e) => {
+                        let _ = tx.blocking_send(Err(FlightError::ExternalError(Box::new(e))));
+                        return;
+                    }
+                };
+                for batch in batches {
+                    if tx.blocking_send(batch.map_err(FlightError::from)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        // Encode the batches as Flight data as the client consumes them
+        let batches = stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|batch| (batch, rx))
+        });
+        let flight_data = FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(batches)
+            .map_err(Status::from);
+        Ok(Response::new(flight_data.boxed()))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> std::result::Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("The dataset server is read-only"))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> std::result::Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("The dataset server has no actions"))
+    }
+
+    // Function to list the actions of the server, of which there are none
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> std::result::Result<Response<Self::ListActionsStream>, Status> {
+        Ok(Response::new(stream::empty().boxed()))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> std::result::Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("The dataset server is read-only"))
+    }
+}
+
+// Function to serve the splits of a converted dataset over Arrow Flight on the given address until
+// the token is cancelled
+pub fn serve_dataset(path: &Path, address: SocketAddr, cancel: CancellationToken) -> Result<()> {
+    // Open the dataset once so every request shares its metadata
+    let dataset = ArrowDataset::open(path)?;
+    if dataset.splits().is_empty() {
+        warn!("No converted splits found in {:?}", path);
+    }
+
+    // Start the runtime driving the server
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(Error::io("Failed to start the Flight server for", path))?;
+
+    // Serve the dataset until cancelled, checking the token a few times per second
+    info!(
+        "Serving {} splits of {:?} over Arrow Flight on {}",
+        dataset.splits().len(),
+        path,
+        address
+    );
+    let service = DatasetFlightService {
+        dataset: Arc::new(dataset),
+    };
+    runtime.block_on(
+        Server::builder()
+            .add_service(FlightServiceServer::new(service))
+            .serve_with_shutdown(address, async move {
+                while !cancel.is_cancelled() {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                }
+            }),
+    )?;
+
+    // Print a message indicating the server stopped
+    info!("Stopped serving {:?}", path);
+    Ok(())
+}
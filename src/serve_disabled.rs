@@ -0,0 +1,9 @@
+use crate::cancel::CancellationToken;
+use crate::error::{Error, Result};
+use std::net::SocketAddr;
+use std::path::Path;
+
+// Function to report that the Flight server is unavailable in this build
+pub fn serve_dataset(_path: &Path, _address: SocketAddr, _cancel: CancellationToken) -> Result<()> {
+    Err(Error::FeatureDisabled("The serve subcommand", "flight"))
+}
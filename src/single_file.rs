@@ -0,0 +1,55 @@
+use crate::chunk_io::read_chunk_batches;
+use crate::dataset::DatasetSplit;
+use crate::error::{Error, Result};
+use crate::profile::OutputSettings;
+use crate::sink;
+use arrow::datatypes::Schema;
+use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// Define the key of the schema metadata holding the dataset_info.json of a single-file split
+pub const DATASET_INFO_KEY: &str = "dataset_info";
+
+// Function to gather the chunks of a converted split into one file next to its directory, named
+// after the split with the extension of its chunks and holding one record batch per chunk, with the
+// dataset info embedded in the schema metadata, then remove the split directory
+pub fn write_single_file(split_dir: &Path, output: &OutputSettings) -> Result<PathBuf> {
+    // Open the converted split to read its metadata and the schema of its chunks
+    let split = DatasetSplit::open(split_dir)?;
+    let schema = split.schema()?.ok_or_else(|| {
+        Error::Unsupported(format!(
+            "Writing the split without samples in {:?} as a single file",
+            split_dir
+        ))
+    })?;
+
+    // Embed the dataset info in the schema metadata, next to the metadata of the chunks
+    let mut metadata = schema.metadata().clone();
+    metadata.insert(
+        DATASET_INFO_KEY.to_string(),
+        serde_json::to_string(split.info()).expect("Failed to serialize metadata"),
+    );
+    let schema = Arc::new(Schema::new_with_metadata(schema.fields().clone(), metadata));
+
+    // Copy the record batches of every chunk in order into the single file
+    let path = split_dir.with_file_name(format!("{}.{}", split.name(), output.format.extension()));
+    let mut sink = sink::create_sink(&path, &schema, output)?;
+    for chunk_path in split.chunk_paths() {
+        for batch in read_chunk_batches(&chunk_path)? {
+            sink.write_batch(&batch?.with_schema(Arc::clone(&schema))?)?;
+        }
+    }
+    sink.finalize()?;
+
+    // Remove the split directory now that its samples are in the single file
+    fs::remove_dir_all(split_dir).map_err(Error::io("Failed to remove", split_dir))?;
+    info!(
+        "Wrote {} samples of {:?} to {:?}",
+        split.num_samples(),
+        split_dir,
+        path
+    );
+    Ok(path)
+}
@@ -0,0 +1,291 @@
+use crate::chunk_io::ChunkFormat;
+use crate::error::{Error, Result};
+use crate::profile::{Compression, IpcFormat, OutputSettings};
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::writer::{FileWriter, IpcWriteOptions, StreamWriter};
+#[cfg(feature = "ipc-compression")]
+use arrow::ipc::CompressionType;
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "parquet")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parquet")]
+use parquet::basic;
+#[cfg(feature = "parquet")]
+use parquet::file::properties::WriterProperties;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+// Define the suffix of the temporary file a sink writes to, renamed to its final path once it is
+// complete so a crash never leaves a truncated file under the name of a finished one
+pub const TEMP_FILE_SUFFIX: &str = ".tmp";
+
+// Function to get the temporary path a file is written to before being renamed to the given path
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(TEMP_FILE_SUFFIX);
+    PathBuf::from(temp_path)
+}
+
+// Function to create the temporary file of the given path
+fn create_temp_file(path: &Path) -> Result<File> {
+    let temp_path = temp_path(path);
+    File::create(&temp_path).map_err(Error::io("Failed to create", &temp_path))
+}
+
+// Function to move the completely written temporary file of the given path to that path, first
+// flushing its content to disk and then the directory entry of its new name when syncing
+fn commit_temp_file(path: &Path, file: File, fsync: bool) -> Result<()> {
+    let temp_path = temp_path(path);
+    if fsync {
+        file.sync_all()
+            .map_err(Error::io("Failed to sync", &temp_path))?;
+    }
+    drop(file);
+    fs::rename(&temp_path, path).map_err(Error::io("Failed to rename", &temp_path))?;
+    if fsync {
+        sync_dir(path.parent().unwrap_or(Path::new(".")))?;
+    }
+    Ok(())
+}
+
+// Function to flush the entries of the given folder to disk, so the files just created or renamed
+// in it survive a crash
+pub fn sync_dir(dir: &Path) -> Result<()> {
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(Error::io("Failed to sync", dir))
+}
+
+// Function to flush the given files of a folder to disk, then the entries of the folder
+pub fn sync_files(dir: &Path, file_names: &[&str]) -> Result<()> {
+    for file_name in file_names {
+        let path = dir.join(file_name);
+        File::open(&path)
+            .and_then(|file| file.sync_all())
+            .map_err(Error::io("Failed to sync", &path))?;
+    }
+    sync_dir(dir)
+}
+
+// Define the interface of an output writer receiving the record batches of one chunk
+pub trait DatasetSink: Send {
+    // Function to write a record batch to the output
+    fn write_batch(&mut self, batch: &RecordBatch) -> Result<()>;
+
+    // Function to complete the output once every batch has been written, moving it to its path
+    fn finalize(self: Box<Self>) -> Result<()>;
+}
+
+// Define a sink writing an Arrow IPC file
+pub struct ArrowFileSink {
+    path: PathBuf,
+    fsync: bool,
+    writer: FileWriter<File>,
+}
+
+// Define a sink writing an Arrow IPC stream, the layout of the cache files of HuggingFace datasets
+pub struct ArrowStreamSink {
+    path: PathBuf,
+    fsync: bool,
+    writer: StreamWriter<File>,
+}
+
+// Define a sink writing a Parquet file
+#[cfg(feature = "parquet")]
+pub struct ParquetSink {
+    path: PathBuf,
+    fsync: bool,
+    writer: ArrowWriter<File>,
+}
+
+// Function to build the options of an Arrow IPC writer compressing the buffers as requested
+pub fn ipc_write_options(compression: Compression) -> Result<IpcWriteOptions> {
+    // Map the compression to one of the codecs supported by the IPC format
+    let compression = match compression {
+        Compression::None => None,
+        #[cfg(feature = "ipc-compression")]
+        Compression::Zstd => Some(CompressionType::ZSTD),
+        #[cfg(not(feature = "ipc-compression"))]
+        Compression::Zstd => {
+            return Err(Error::FeatureDisabled(
+                "Zstd compression of Arrow IPC files",
+                "ipc-compression",
+            ))
+        }
+        #[cfg(feature = "ipc-compression")]
+        Compression::Lz4 => Some(CompressionType::LZ4_FRAME),
+        #[cfg(not(feature = "ipc-compression"))]
+        Compression::Lz4 => {
+            return Err(Error::FeatureDisabled(
+                "LZ4 compression of Arrow IPC files",
+                "ipc-compression",
+            ))
+        }
+        Compression::Snappy => {
+            return Err(Error::Unsupported(
+                "Snappy compression of Arrow IPC files".to_string(),
+            ))
+        }
+    };
+    Ok(IpcWriteOptions::default().try_with_compression(compression)?)
+}
+
+impl ArrowFileSink {
+    // Function to create an Arrow IPC file at the given path, compressing its buffers as requested
+    // and flushing it to disk once complete when syncing
+    pub fn new(
+        path: &Path,
+        schema: &SchemaRef,
+        compression: Compression,
+        fsync: bool,
+    ) -> Result<Self> {
+        // Create the temporary file for writing the Arrow data
+        let file = create_temp_file(path)?;
+
+        // Create a FileWriter with the requested compression
+        let options = ipc_write_options(compression)?;
+        let writer = FileWriter::try_new_with_options(file, schema, options)?;
+
+        // Return the sink
+        Ok(ArrowFileSink {
+            path: path.to_path_buf(),
+            fsync,
+            writer,
+        })
+    }
+}
+
+impl DatasetSink for ArrowFileSink {
+    fn write_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        Ok(self.writer.write(batch)?)
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        let file = self.writer.into_inner()?;
+        commit_temp_file(&self.path, file, self.fsync)
+    }
+}
+
+impl ArrowStreamSink {
+    // Function to create an Arrow IPC stream at the given path, compressing its buffers as requested
+    // and flushing it to disk once complete when syncing
+    pub fn new(
+        path: &Path,
+        schema: &SchemaRef,
+        compression: Compression,
+        fsync: bool,
+    ) -> Result<Self> {
+        // Create the temporary file for writing the Arrow stream
+        let file = create_temp_file(path)?;
+
+        // Create a StreamWriter with the requested compression
+        let options = ipc_write_options(compression)?;
+        let writer = StreamWriter::try_new_with_options(file, schema, options)?;
+
+        // Return the sink
+        Ok(ArrowStreamSink {
+            path: path.to_path_buf(),
+            fsync,
+            writer,
+        })
+    }
+}
+
+impl DatasetSink for ArrowStreamSink {
+    fn write_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        Ok(self.writer.write(batch)?)
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        let file = self.writer.into_inner()?;
+        commit_temp_file(&self.path, file, self.fsync)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetSink {
+    // Function to create a Parquet file at the given path, compressing its pages with the matching
+    // codec, capping its row groups at the given number of rows and flushing it to disk once complete
+    // when syncing
+    pub fn new(
+        path: &Path,
+        schema: &SchemaRef,
+        compression: Compression,
+        row_group_size: Option<usize>,
+        fsync: bool,
+    ) -> Result<Self> {
+        // Map the compression to the matching Parquet codec
+        let compression = match compression {
+            Compression::None => basic::Compression::UNCOMPRESSED,
+            Compression::Snappy => basic::Compression::SNAPPY,
+            Compression::Zstd => basic::Compression::ZSTD(Default::default()),
+            Compression::Lz4 => basic::Compression::LZ4_RAW,
+        };
+
+        // Create the temporary file for writing the Parquet data
+        let file = create_temp_file(path)?;
+
+        // Create an ArrowWriter producing a Parquet file with the chosen codec and row group size
+        let mut properties = WriterProperties::builder().set_compression(compression);
+        if let Some(row_group_size) = row_group_size {
+            properties = properties.set_max_row_group_size(row_group_size);
+        }
+        let properties = properties.build();
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))?;
+
+        // Return the sink
+        Ok(ParquetSink {
+            path: path.to_path_buf(),
+            fsync,
+            writer,
+        })
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl DatasetSink for ParquetSink {
+    fn write_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        Ok(self.writer.write(batch)?)
+    }
+
+    fn finalize(self: Box<Self>) -> Result<()> {
+        let file = self.writer.into_inner()?;
+        commit_temp_file(&self.path, file, self.fsync)
+    }
+}
+
+// Function to create the sink writing a chunk file in the format of the output settings
+pub fn create_sink(
+    path: &Path,
+    schema: &SchemaRef,
+    output: &OutputSettings,
+) -> Result<Box<dyn DatasetSink>> {
+    Ok(match output.format {
+        ChunkFormat::Arrow => match output.ipc_format {
+            IpcFormat::File => Box::new(ArrowFileSink::new(
+                path,
+                schema,
+                output.compression,
+                output.fsync,
+            )?),
+            IpcFormat::Stream => Box::new(ArrowStreamSink::new(
+                path,
+                schema,
+                output.compression,
+                output.fsync,
+            )?),
+        },
+        #[cfg(feature = "parquet")]
+        ChunkFormat::Parquet => Box::new(ParquetSink::new(
+            path,
+            schema,
+            output.compression,
+            output.row_group_size,
+            output.fsync,
+        )?),
+        #[cfg(not(feature = "parquet"))]
+        ChunkFormat::Parquet => {
+            return Err(Error::FeatureDisabled("Writing Parquet chunks", "parquet"))
+        }
+    })
+}
@@ -0,0 +1,558 @@
+use crate::cli::{InputFormat, LabelHierarchy};
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use crate::schema::{ColumnType, SampleKind};
+use indicatif::ProgressBar;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use walkdir::WalkDir;
+
+mod audio_folder;
+mod coco;
+#[cfg_attr(not(feature = "hdf5"), path = "source/hdf5_input_disabled.rs")]
+mod hdf5_input;
+mod jsonl;
+#[cfg_attr(not(feature = "lmdb"), path = "source/lmdb_disabled.rs")]
+mod lmdb;
+mod manifest;
+mod numpy_folder;
+mod paired_folder;
+#[cfg_attr(not(feature = "parquet"), path = "source/parquet_input_disabled.rs")]
+mod parquet_input;
+#[cfg_attr(
+    not(any(feature = "azure", feature = "gcs", feature = "s3")),
+    path = "source/remote_disabled.rs"
+)]
+mod remote;
+mod tar_archive;
+mod text_folder;
+#[cfg_attr(not(feature = "tfrecord"), path = "source/tfrecord_disabled.rs")]
+mod tfrecord;
+#[cfg_attr(not(feature = "http"), path = "source/url_list_disabled.rs")]
+mod url_list;
+mod video_folder;
+mod voc;
+mod webdataset;
+mod zip_archive;
+
+pub use audio_folder::AudioFolderSource;
+pub(crate) use coco::discover_coco_splits;
+pub use coco::CocoSource;
+pub use jsonl::JsonlSource;
+pub(crate) use lmdb::discover_lmdb_splits;
+#[cfg(feature = "lmdb")]
+pub(crate) use lmdb::Datum;
+pub use manifest::ManifestSource;
+pub use numpy_folder::NumpyFolderSource;
+pub use paired_folder::PairedFolderSource;
+pub(crate) use parquet_input::discover_parquet_splits;
+pub(crate) use remote::discover_remote_splits;
+#[cfg(any(feature = "azure", feature = "gcs", feature = "s3"))]
+pub(crate) use remote::{create_runtime, open_store, split_url};
+pub use tar_archive::TarSource;
+pub use text_folder::TextFolderSource;
+pub(crate) use tfrecord::discover_tfrecord_splits;
+pub use video_folder::VideoFolderSource;
+pub(crate) use voc::discover_voc_splits;
+pub use voc::VocSource;
+pub use webdataset::WebDatasetSource;
+pub use zip_archive::ZipSource;
+
+// Define the prefix of inputs naming a dataset repository of the HuggingFace Hub
+const HUB_PREFIX: &str = "hf://";
+
+// Define the prefix of inputs naming a Kaggle dataset
+const KAGGLE_PREFIX: &str = "kaggle://";
+
+// Define the URL schemes of the object storages inputs are read from and outputs written to, with
+// what accessing them is called in errors and the feature of the crate enabling it
+const REMOTE_SCHEMES: &[(&str, &str, &str)] = &[
+    ("s3://", "Accessing S3", "s3"),
+    ("gs://", "Accessing Google Cloud Storage", "gcs"),
+    ("az://", "Accessing Azure Blob Storage", "azure"),
+];
+
+// Define the settings shared by the sources of every split: the files they keep, and how the
+// images of URL lists are downloaded
+#[derive(Clone)]
+pub struct SourceSettings {
+    pub filter: PathFilter,
+    pub download_concurrency: usize,
+    pub download_timeout: Duration,
+    pub label_names: Vec<String>,
+    pub video_frames: Option<usize>,
+    pub captions: bool,
+    pub label_hierarchy: Option<LabelHierarchy>,
+    pub pair_folders: Option<(String, String)>,
+    pub hdf5_images: Option<String>,
+    pub hdf5_labels: Option<String>,
+    pub hdf5_columns: Vec<(String, String)>,
+    pub listed_paths: Option<Arc<Vec<PathBuf>>>,
+}
+
+// Define the reference to a sample listed by a source: where to load it from and its label
+pub type SampleRef = (PathBuf, String);
+
+// Define a struct describing a sample loaded from a source
+pub struct Record {
+    pub bytes: Vec<u8>,
+    pub label: String,
+    pub metadata: BTreeMap<String, String>,
+}
+
+// Define the interface of a dataset source, which lists cheap sample references that can be
+// shuffled before the samples are loaded by the worker threads
+pub trait DataSource: Send + Sync {
+    // Function to list the samples of the source in a stable order, counting them on the progress bar
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>>;
+
+    // Function to load the record of a listed sample
+    fn load(&self, sample: &SampleRef) -> io::Result<Record>;
+
+    // Function to load the records of consecutive samples in order, one at a time unless the source
+    // can fetch several concurrently
+    fn load_many<'a>(
+        &'a self,
+        samples: &'a [SampleRef],
+    ) -> Box<dyn Iterator<Item = io::Result<Record>> + 'a> {
+        Box::new(samples.iter().map(|sample| self.load(sample)))
+    }
+
+    // Function to estimate the size in bytes of a sample without loading it
+    fn size_hint(&self, _sample: &SampleRef) -> u64 {
+        0
+    }
+
+    // Function to get the columns found while listing, filled from the metadata of every record
+    fn metadata_columns(&self) -> BTreeMap<String, ColumnType> {
+        BTreeMap::new()
+    }
+
+    // Function to get the kind of samples the source loads, images unless it reads documents or
+    // audio files
+    fn sample_kind(&self) -> SampleKind {
+        SampleKind::Image
+    }
+
+    // Function to get the sampling rate of a loaded record of an audio source from the header of
+    // its bytes, gathered into the Audio feature of the split, or None when it holds no audio
+    fn sampling_rate(&self, _record: &Record) -> Option<u32> {
+        None
+    }
+}
+
+// Function to read the names of integer labels from a text file with one class name per line, the
+// first line naming label 0
+pub fn read_label_names(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path).map_err(Error::io("Failed to read", path))?;
+    Ok(content
+        .lines()
+        .map(|line| line.trim().to_string())
+        .collect())
+}
+
+// Function to read a list of image paths with one path per line from a file, or from the standard
+// input when the path is "-", resolving relative paths against the dataset root
+pub fn read_path_list(path: &Path, dataset_path: &Path) -> Result<Vec<PathBuf>> {
+    let content = if path == Path::new("-") {
+        io::read_to_string(io::stdin()).map_err(Error::io("Failed to read", path))?
+    } else {
+        fs::read_to_string(path).map_err(Error::io("Failed to read", path))?
+    };
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| dataset_path.join(line))
+        .collect())
+}
+
+// Function to keep the listed image paths under a split folder that pass the filter, in the order
+// of the list, labelled like the walked ones by their parent folder
+fn listed_image_paths(
+    data_dir: &Path,
+    paths: &[PathBuf],
+    filter: &PathFilter,
+    scan_progress: &ProgressBar,
+) -> Vec<(PathBuf, String)> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let relative = path.strip_prefix(data_dir).ok()?;
+            if !filter.matches(relative) {
+                return None;
+            }
+            let label = path.parent()?.file_name()?.to_str()?;
+            Some((path.clone(), label.to_string()))
+        })
+        .inspect(|_| scan_progress.inc(1))
+        .collect()
+}
+
+// Function to strip the shard numbering from the end of a file stem such as "train-00000-of-00004"
+// or "train-00000-of-00004-1a2b3c", giving the name of the split
+#[cfg(any(feature = "parquet", feature = "tfrecord"))]
+pub(crate) fn strip_shard_numbering(stem: &str) -> String {
+    let parts: Vec<&str> = stem.split('-').collect();
+    let is_number = |part: &&str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let of = (2..parts.len().saturating_sub(1))
+        .find(|&i| parts[i] == "of" && is_number(&parts[i - 1]) && is_number(&parts[i + 1]));
+    match of {
+        Some(i) => parts[..i - 1].join("-"),
+        None => stem.to_string(),
+    }
+}
+
+// Function to get the label of an integer class index, named by the label names when they cover it
+#[cfg(any(feature = "hdf5", feature = "lmdb", feature = "tfrecord"))]
+pub(crate) fn label_name(label_names: &[String], index: i64) -> String {
+    usize::try_from(index)
+        .ok()
+        .and_then(|index| label_names.get(index))
+        .cloned()
+        .unwrap_or_else(|| index.to_string())
+}
+
+// Define the extension of the caption files read next to the images of a folder
+const CAPTION_EXTENSION: &str = "txt";
+
+// Define the column holding the caption of every image of a folder read with its captions
+const CAPTION_COLUMN: &str = "caption";
+
+// Define the separator of the class folders in the labels of nested images
+const LABEL_PATH_SEPARATOR: &str = "/";
+
+// Define a source reading images from a folder with one subfolder per label, along with the
+// caption file next to every image and the nested class folders above it when requested, walking
+// the folder or taking the images of a path list
+pub struct ImageFolderSource {
+    root: PathBuf,
+    filter: PathFilter,
+    captions: bool,
+    label_hierarchy: Option<LabelHierarchy>,
+    label_levels: Mutex<usize>,
+    listed_paths: Option<Arc<Vec<PathBuf>>>,
+}
+
+// Function to get the repository id of an input naming a HuggingFace Hub dataset as
+// "hf://org/name", or None for local inputs
+pub fn hub_repo_id(input: &Path) -> Option<&str> {
+    input.to_str()?.strip_prefix(HUB_PREFIX)
+}
+
+// Function to get the slug of an input naming a Kaggle dataset as "kaggle://owner/name", or None
+// for local inputs
+pub fn kaggle_slug(input: &Path) -> Option<&str> {
+    input.to_str()?.strip_prefix(KAGGLE_PREFIX)
+}
+
+// Function to check whether an input is a local path rather than a Hub or Kaggle dataset or an
+// object storage URL
+pub fn is_local_input(input: &Path) -> bool {
+    hub_repo_id(input).is_none() && kaggle_slug(input).is_none() && remote_url(input).is_none()
+}
+
+// Function to get the URL of an input stored in object storage, such as "s3://bucket/prefix", or
+// None for local inputs
+pub fn remote_url(input: &Path) -> Option<&str> {
+    let input = input.to_str()?;
+    REMOTE_SCHEMES
+        .iter()
+        .any(|(scheme, _, _)| input.starts_with(scheme))
+        .then_some(input)
+}
+
+// Function to get the error reporting that the object storage of a URL cannot be accessed by this
+// build
+pub(crate) fn remote_disabled(url: &str) -> Error {
+    let (_, action, feature) = REMOTE_SCHEMES
+        .iter()
+        .find(|(scheme, _, _)| url.starts_with(scheme))
+        .copied()
+        .unwrap_or(("", "Accessing object storage", "s3"));
+    Error::FeatureDisabled(action, feature)
+}
+
+// Function to read an image file as raw bytes
+pub fn read_image_as_bytes(image_path: &Path) -> io::Result<Vec<u8>> {
+    // Open the file at the given path and return the error on failure
+    let mut file = File::open(image_path)?;
+
+    // Create a new buffer to store file contents
+    let mut buffer = Vec::new();
+
+    // Read the entire file into the buffer, returning the error on failure
+    file.read_to_end(&mut buffer)?;
+
+    // Return the buffer containing the file bytes
+    Ok(buffer)
+}
+
+// Function to collect image paths and labels from a directory, keeping the paths accepted by the filter
+// and counting each one on the given progress bar
+pub fn collect_image_paths(
+    data_dir: &Path,
+    filter: &PathFilter,
+    scan_progress: &ProgressBar,
+) -> Vec<(PathBuf, String)> {
+    // Walk through the directory recursively in a stable order and filter valid entries
+    WalkDir::new(data_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            // Get the path from the entry
+            let path = entry.path();
+            // Check if the path is a file with an accepted extension that passes the include and
+            // exclude patterns
+            if path.is_file() && filter.matches(path.strip_prefix(data_dir).unwrap_or(path)) {
+                // Get the parent directory of the file
+                if let Some(parent) = path.parent() {
+                    // Get the label from the parent's file name as a string
+                    if let Some(label) = parent.file_name().and_then(|s| s.to_str()) {
+                        // Return the path and label as a tuple
+                        return Some((path.to_path_buf(), label.to_string()));
+                    }
+                }
+            }
+            None
+        })
+        .inspect(|_| scan_progress.inc(1))
+        .collect()
+}
+
+impl ImageFolderSource {
+    // Function to create a source over a split folder, keeping the files accepted by the filter
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        ImageFolderSource {
+            root: root.into(),
+            filter,
+            captions: false,
+            label_hierarchy: None,
+            label_levels: Mutex::new(0),
+            listed_paths: None,
+        }
+    }
+
+    // Function to take the images from a list of paths instead of walking the split folder
+    pub fn with_listed_paths(mut self, listed_paths: Option<Arc<Vec<PathBuf>>>) -> Self {
+        self.listed_paths = listed_paths;
+        self
+    }
+
+    // Function to read the caption of every image from the .txt file of the same name next to it
+    pub fn with_captions(mut self, captions: bool) -> Self {
+        self.captions = captions;
+        self
+    }
+
+    // Function to record the nested class folders above every image as a label path or as one
+    // column per level
+    pub fn with_label_hierarchy(mut self, label_hierarchy: Option<LabelHierarchy>) -> Self {
+        self.label_hierarchy = label_hierarchy;
+        self
+    }
+
+    // Function to get the class folders above an image, from the outermost one
+    fn class_folders<'p>(&self, path: &'p Path) -> Vec<&'p str> {
+        path.parent()
+            .and_then(|parent| parent.strip_prefix(&self.root).ok())
+            .map(|parent| {
+                parent
+                    .components()
+                    .filter_map(|component| component.as_os_str().to_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+// Function to get the name of the column holding the class folder of the given level, from 1
+fn label_level_column(level: usize) -> String {
+    format!("label_{}", level)
+}
+
+// Function to read the caption of an image from the .txt file next to it, without the surrounding
+// whitespace, or None when the image has no caption file
+fn read_caption(image_path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(image_path.with_extension(CAPTION_EXTENSION)) {
+        Ok(caption) => Ok(Some(
+            caption.trim_start_matches('\u{feff}').trim().to_string(),
+        )),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+impl DataSource for ImageFolderSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        let mut samples = match &self.listed_paths {
+            Some(paths) => listed_image_paths(&self.root, paths, &self.filter, scan_progress),
+            None => collect_image_paths(&self.root, &self.filter, scan_progress),
+        };
+        match self.label_hierarchy {
+            // Label the images nested in class folders by the path of their folders
+            Some(LabelHierarchy::Path) => {
+                for (path, label) in &mut samples {
+                    let folders = self.class_folders(path);
+                    if !folders.is_empty() {
+                        *label = folders.join(LABEL_PATH_SEPARATOR);
+                    }
+                }
+            }
+            // Count the levels of class folders for the columns holding them
+            Some(LabelHierarchy::Columns) => {
+                *self.label_levels.lock().unwrap() = samples
+                    .iter()
+                    .map(|(path, _)| self.class_folders(path).len())
+                    .max()
+                    .unwrap_or(0);
+            }
+            None => {}
+        }
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read the image file and keep the label found while scanning, with the caption next to
+        // it when requested, leaving the caption null when the image has none
+        let (path, label) = sample;
+        let mut metadata = BTreeMap::new();
+        if self.captions {
+            if let Some(caption) = read_caption(path)? {
+                metadata.insert(CAPTION_COLUMN.to_string(), caption);
+            }
+        }
+
+        // Record every class folder above the image in the column of its level when requested,
+        // leaving the deeper levels null
+        if self.label_hierarchy == Some(LabelHierarchy::Columns) {
+            for (index, folder) in self.class_folders(path).into_iter().enumerate() {
+                metadata.insert(label_level_column(index + 1), folder.to_string());
+            }
+        }
+        Ok(Record {
+            bytes: read_image_as_bytes(path)?,
+            label: label.clone(),
+            metadata,
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Read the image size from the file metadata without opening the file
+        fs::metadata(&sample.0).map(|m| m.len()).unwrap_or(0)
+    }
+
+    fn metadata_columns(&self) -> BTreeMap<String, ColumnType> {
+        let mut columns = BTreeMap::new();
+        if self.captions {
+            columns.insert(CAPTION_COLUMN.to_string(), ColumnType::Utf8);
+        }
+        if self.label_hierarchy == Some(LabelHierarchy::Columns) {
+            for level in 1..=*self.label_levels.lock().unwrap() {
+                columns.insert(label_level_column(level), ColumnType::Utf8);
+            }
+        }
+        columns
+    }
+}
+
+// Function to open the source reading the samples of a split in the given input format, from the
+// local filesystem or from object storage
+pub fn open_source(
+    format: InputFormat,
+    root: &Path,
+    settings: &SourceSettings,
+) -> Result<Arc<dyn DataSource>> {
+    let filter = settings.filter.clone();
+    if let Some(url) = remote_url(root) {
+        return remote::open_remote_source(format, url, filter);
+    }
+    Ok(match format {
+        InputFormat::Folder => Arc::new(
+            ImageFolderSource::new(root, filter)
+                .with_captions(settings.captions)
+                .with_label_hierarchy(settings.label_hierarchy)
+                .with_listed_paths(settings.listed_paths.clone()),
+        ),
+        InputFormat::Tar => Arc::new(TarSource::new(root, filter)),
+        InputFormat::Zip => Arc::new(ZipSource::new(root, filter)),
+        InputFormat::Webdataset => Arc::new(WebDatasetSource::new(root, filter)),
+        InputFormat::Csv => Arc::new(ManifestSource::new(root, filter)),
+        InputFormat::Jsonl => Arc::new(JsonlSource::new(root, filter)),
+        InputFormat::Coco => Arc::new(CocoSource::new(root, filter)),
+        InputFormat::Voc => Arc::new(VocSource::new(root, filter)),
+        InputFormat::Urls => url_list::open_url_list_source(root, settings)?,
+        InputFormat::Parquet => parquet_input::open_parquet_source(root)?,
+        InputFormat::Lmdb => lmdb::open_lmdb_source(root, settings)?,
+        InputFormat::Tfrecord => tfrecord::open_tfrecord_source(root, settings)?,
+        InputFormat::Text => Arc::new(TextFolderSource::new(root, filter)),
+        InputFormat::Audio => Arc::new(AudioFolderSource::new(root, filter)),
+        InputFormat::Numpy => Arc::new(NumpyFolderSource::new(root, filter)),
+        InputFormat::Paired => Arc::new(PairedFolderSource::new(
+            root,
+            filter,
+            settings.pair_folders.clone(),
+        )),
+        InputFormat::Video => Arc::new(VideoFolderSource::new(root, filter, settings.video_frames)),
+        InputFormat::Hdf5 => hdf5_input::open_hdf5_source(root, settings)?,
+    })
+}
+
+// Function to list the archives or manifests of a source in a stable order: the root itself when it
+// is a file, or else every file of the given input format under it
+pub(crate) fn find_input_files(format: InputFormat, root: &Path) -> Vec<PathBuf> {
+    // Use the root itself when it is a file
+    if root.is_file() {
+        return vec![root.to_path_buf()];
+    }
+
+    // Otherwise collect every file with the extension of the format under the root
+    WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| path.is_file() && is_input_file(format, path))
+        .collect()
+}
+
+// Function to check whether a file is an archive or manifest read by the given input format, from
+// its extension
+pub fn is_input_file(format: InputFormat, path: &Path) -> bool {
+    let extensions: &[&str] = match format {
+        InputFormat::Folder
+        | InputFormat::Coco
+        | InputFormat::Voc
+        | InputFormat::Lmdb
+        | InputFormat::Text
+        | InputFormat::Audio
+        | InputFormat::Numpy
+        | InputFormat::Paired
+        | InputFormat::Video => &[],
+        InputFormat::Tar | InputFormat::Webdataset => &["tar"],
+        InputFormat::Zip => &["zip"],
+        InputFormat::Csv => &["csv", "tsv"],
+        InputFormat::Urls => &["txt", "csv", "tsv"],
+        InputFormat::Jsonl => &["jsonl"],
+        InputFormat::Parquet => &["parquet"],
+        InputFormat::Tfrecord => &["tfrecord", "tfrecords"],
+        InputFormat::Hdf5 => &["h5", "hdf5"],
+    };
+
+    // Also accept the TensorFlow Datasets shards named like "mnist-train.tfrecord-00000-of-00001"
+    let is_tfds_shard = || {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.contains(".tfrecord-"))
+    };
+    path.extension().is_some_and(|ext| {
+        extensions
+            .iter()
+            .any(|extension| ext.eq_ignore_ascii_case(extension))
+    }) || (format == InputFormat::Tfrecord && is_tfds_shard())
+}
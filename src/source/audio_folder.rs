@@ -0,0 +1,218 @@
+use super::{collect_image_paths, read_image_as_bytes, DataSource, Record, SampleRef};
+use crate::error::Result;
+use crate::filter::PathFilter;
+use crate::schema::SampleKind;
+use indicatif::ProgressBar;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Define the extensions of the audio files of an audio folder
+const AUDIO_EXTENSIONS: &[&str] = &["wav", "flac", "mp3"];
+
+// Define the sampling rates of MPEG-1 audio frames by the rate index of the frame header, halved
+// for MPEG-2 and quartered for MPEG-2.5
+const MPEG1_SAMPLING_RATES: [u32; 3] = [44100, 48000, 32000];
+
+// Define the bitrates in kbit/s of MPEG audio frames by the bitrate index of the frame header, for
+// layers I, II and III of MPEG-1, then layer I and layers II and III of MPEG-2 and MPEG-2.5, the
+// free format index 0 and the invalid index 15 being left out
+const MPEG1_BITRATES: [[u32; 15]; 3] = [
+    [
+        0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+    ],
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+    ],
+    [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ],
+];
+const MPEG2_BITRATES: [[u32; 15]; 2] = [
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+    ],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+// Define the fields of an MPEG audio frame header compared between consecutive frames, with the
+// length of the frame in bytes locating the next one
+struct FrameHeader {
+    version: u8,
+    layer: u8,
+    sampling_rate: u32,
+    length: usize,
+}
+
+// Define a source reading audio files from a folder with one subfolder per label, keeping the
+// encoded bytes of every file whose header gives a sampling rate
+pub struct AudioFolderSource {
+    root: PathBuf,
+    filter: PathFilter,
+}
+
+impl AudioFolderSource {
+    // Function to create a source over a split folder, keeping the audio files passing the include
+    // and exclude patterns
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        AudioFolderSource {
+            root: root.into(),
+            filter: filter.with_extensions(AUDIO_EXTENSIONS),
+        }
+    }
+}
+
+// Function to read the sampling rate from the fmt chunk of a WAV file
+fn wav_sampling_rate(bytes: &[u8]) -> Option<u32> {
+    // Walk the chunks after the RIFF header, each padded to an even size
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().ok()?) as usize;
+        if id == b"fmt " {
+            let rate = bytes.get(offset + 12..offset + 16)?;
+            return Some(u32::from_le_bytes(rate.try_into().ok()?));
+        }
+        offset = offset.checked_add(8 + size + size % 2)?;
+    }
+    None
+}
+
+// Function to read the sampling rate from the STREAMINFO block of a FLAC file, which always comes
+// first, as the 20 bits following its block sizes and frame sizes
+fn flac_sampling_rate(bytes: &[u8]) -> Option<u32> {
+    let info = bytes.get(8..21)?;
+    let rate =
+        (u32::from(info[10]) << 12) | (u32::from(info[11]) << 4) | (u32::from(info[12]) >> 4);
+    (rate > 0).then_some(rate)
+}
+
+// Function to get the length of the ID3v2 tag an MP3 file starts with, whose size is stored as
+// four 7-bit bytes, including its header and optional footer, or None when it has no such tag
+fn id3_tag_len(bytes: &[u8]) -> Option<usize> {
+    let header = bytes
+        .get(..10)
+        .filter(|header| header.starts_with(b"ID3"))?;
+    let size = header[6..10]
+        .iter()
+        .fold(0usize, |size, &byte| (size << 7) | usize::from(byte & 0x7f));
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+    Some(10 + size + footer)
+}
+
+// Function to decode the MPEG audio frame header at the start of the given bytes, or None when they
+// do not start with a frame sync followed by a valid version, layer, bitrate and sampling rate
+fn frame_header(bytes: &[u8]) -> Option<FrameHeader> {
+    let header = bytes.get(..4)?;
+    if header[0] != 0xff || header[1] & 0xe0 != 0xe0 {
+        return None;
+    }
+
+    // Refuse the reserved version and layer, and the free format and invalid bitrate indexes
+    let version = (header[1] >> 3) & 0x03;
+    let layer = (header[1] >> 1) & 0x03;
+    let bitrate_index = usize::from(header[2] >> 4);
+    let rate_index = usize::from((header[2] >> 2) & 0x03);
+    if version == 0b01 || layer == 0b00 || bitrate_index == 0 || bitrate_index == 15 {
+        return None;
+    }
+    let rate = *MPEG1_SAMPLING_RATES.get(rate_index)?;
+
+    // Look up the bitrate and sampling rate of the version, layer I being 0b11 and layer III 0b01
+    let (bitrate, sampling_rate) = match version {
+        0b11 => (MPEG1_BITRATES[usize::from(3 - layer)][bitrate_index], rate),
+        0b10 => (
+            MPEG2_BITRATES[usize::from(layer != 0b11)][bitrate_index],
+            rate / 2,
+        ),
+        _ => (
+            MPEG2_BITRATES[usize::from(layer != 0b11)][bitrate_index],
+            rate / 4,
+        ),
+    };
+
+    // Compute the frame length from the samples per frame, adding the padding slot when set
+    let padding = u32::from((header[2] >> 1) & 0x01);
+    let length = match layer {
+        0b11 => (12 * bitrate * 1000 / sampling_rate + padding) * 4,
+        0b01 if version != 0b11 => 72 * bitrate * 1000 / sampling_rate + padding,
+        _ => 144 * bitrate * 1000 / sampling_rate + padding,
+    };
+    Some(FrameHeader {
+        version,
+        layer,
+        sampling_rate,
+        length: length as usize,
+    })
+}
+
+// Function to read the sampling rate from the first frame header found in the frames following the
+// ID3v2 tag of an MP3 file, which the tag already identifies
+fn tagged_mp3_sampling_rate(frames: &[u8]) -> Option<u32> {
+    (0..frames.len())
+        .find_map(|offset| frame_header(&frames[offset..]))
+        .map(|frame| frame.sampling_rate)
+}
+
+// Function to read the sampling rate of an MP3 file, recognized by the ID3v2 tag it starts with or
+// else by a frame header at its start followed by a second frame of the same version, layer and
+// sampling rate, so that other data happening to hold a frame sync is not taken for MP3
+fn mp3_sampling_rate(bytes: &[u8]) -> Option<u32> {
+    if let Some(tag_len) = id3_tag_len(bytes) {
+        return tagged_mp3_sampling_rate(bytes.get(tag_len..)?);
+    }
+    let first = frame_header(bytes)?;
+    let second = frame_header(bytes.get(first.length..)?)?;
+    let same_stream = (second.version, second.layer, second.sampling_rate)
+        == (first.version, first.layer, first.sampling_rate);
+    same_stream.then_some(first.sampling_rate)
+}
+
+// Function to read the sampling rate of an audio file from its header, or None when the format is
+// not recognized
+fn header_sampling_rate(bytes: &[u8]) -> Option<u32> {
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WAVE") {
+        wav_sampling_rate(bytes)
+    } else if bytes.starts_with(b"fLaC") {
+        flac_sampling_rate(bytes)
+    } else {
+        mp3_sampling_rate(bytes)
+    }
+}
+
+impl DataSource for AudioFolderSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        Ok(collect_image_paths(&self.root, &self.filter, scan_progress))
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read the audio file, refusing files whose header gives no sampling rate
+        let (path, label) = sample;
+        let bytes = read_image_as_bytes(path)?;
+        if header_sampling_rate(&bytes).is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Unrecognized audio header",
+            ));
+        }
+        Ok(Record {
+            bytes,
+            label: label.clone(),
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Read the audio file size from the file metadata without opening the file
+        fs::metadata(&sample.0).map(|m| m.len()).unwrap_or(0)
+    }
+
+    fn sample_kind(&self) -> SampleKind {
+        SampleKind::Audio
+    }
+
+    fn sampling_rate(&self, record: &Record) -> Option<u32> {
+        header_sampling_rate(&record.bytes)
+    }
+}
@@ -0,0 +1,254 @@
+use super::{read_image_as_bytes, DataSource, Record, SampleRef};
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use crate::schema::ColumnType;
+use indicatif::ProgressBar;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Define the prefix of the COCO annotation files describing object instances
+const INSTANCES_PREFIX: &str = "instances_";
+
+// Define the parts of a COCO instances file the conversion needs
+#[derive(Deserialize)]
+struct CocoFile {
+    images: Vec<CocoImage>,
+    #[serde(default)]
+    annotations: Vec<CocoAnnotation>,
+    #[serde(default)]
+    categories: Vec<CocoCategory>,
+}
+
+// Define an image listed in a COCO instances file
+#[derive(Deserialize)]
+struct CocoImage {
+    id: i64,
+    file_name: String,
+    width: Option<i64>,
+    height: Option<i64>,
+}
+
+// Define an object annotated on an image, with its box as [x, y, width, height]
+#[derive(Deserialize)]
+struct CocoAnnotation {
+    image_id: i64,
+    bbox: [f64; 4],
+    category_id: i64,
+}
+
+// Define a category objects can belong to
+#[derive(Deserialize)]
+struct CocoCategory {
+    id: i64,
+    name: String,
+}
+
+// Define a source reading a COCO detection dataset from its instances annotation files, storing
+// the boxes and categories of the objects of every image in list columns
+pub struct CocoSource {
+    root: PathBuf,
+    filter: PathFilter,
+    metadata: Mutex<HashMap<PathBuf, BTreeMap<String, String>>>,
+}
+
+impl CocoSource {
+    // Function to create a source over an instances file or a folder of them, keeping the images
+    // accepted by the filter
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        CocoSource {
+            root: root.into(),
+            filter,
+            metadata: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Function to list the images of an instances file with the objects annotated on each
+    fn list_instances(
+        &self,
+        instances_path: &Path,
+        scan_progress: &ProgressBar,
+        metadata: &mut HashMap<PathBuf, BTreeMap<String, String>>,
+    ) -> Result<Vec<SampleRef>> {
+        // Parse the annotation file
+        let file =
+            File::open(instances_path).map_err(Error::io("Failed to open", instances_path))?;
+        let coco: CocoFile =
+            serde_json::from_reader(BufReader::new(file)).map_err(Error::json(instances_path))?;
+
+        // Group the annotations by image and name the categories
+        let categories: HashMap<i64, &str> = coco
+            .categories
+            .iter()
+            .map(|category| (category.id, category.name.as_str()))
+            .collect();
+        let mut objects: HashMap<i64, Vec<&CocoAnnotation>> = HashMap::new();
+        for annotation in &coco.annotations {
+            objects
+                .entry(annotation.image_id)
+                .or_default()
+                .push(annotation);
+        }
+
+        // List the images in the order of the file, with the fields of their objects as JSON arrays
+        let images_dir = images_dir(instances_path);
+        let mut samples = Vec::new();
+        for image in &coco.images {
+            if !self.filter.matches(Path::new(&image.file_name)) {
+                continue;
+            }
+            let annotations = objects.remove(&image.id).unwrap_or_default();
+            let bboxes: Vec<[f64; 4]> = annotations.iter().map(|a| a.bbox).collect();
+            let category_ids: Vec<i64> = annotations.iter().map(|a| a.category_id).collect();
+            let names: Vec<&str> = category_ids
+                .iter()
+                .map(|id| categories.get(id).copied().unwrap_or_default())
+                .collect();
+            let mut fields = BTreeMap::from([
+                ("image_id".to_string(), image.id.to_string()),
+                ("bboxes".to_string(), serde_json::json!(bboxes).to_string()),
+                (
+                    "category_ids".to_string(),
+                    serde_json::json!(category_ids).to_string(),
+                ),
+                (
+                    "categories".to_string(),
+                    serde_json::json!(names).to_string(),
+                ),
+            ]);
+            fields.extend(
+                image
+                    .width
+                    .map(|width| ("width".to_string(), width.to_string())),
+            );
+            fields.extend(
+                image
+                    .height
+                    .map(|height| ("height".to_string(), height.to_string())),
+            );
+
+            // Leave the label empty, since an image holds any number of objects
+            let path = images_dir.join(&image.file_name);
+            metadata.insert(path.clone(), fields);
+            samples.push((path, String::new()));
+            scan_progress.inc(1);
+        }
+        Ok(samples)
+    }
+}
+
+// Function to find the folder holding the images of an instances file, which in the standard layout
+// is named after the file, "train2017" for "annotations/instances_train2017.json", next to the
+// annotations folder; otherwise the images are looked up next to the file
+fn images_dir(instances_path: &Path) -> PathBuf {
+    let annotations_dir = instances_path.parent().unwrap_or(Path::new(""));
+    let subset = instances_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.strip_prefix(INSTANCES_PREFIX));
+    let candidates = subset.into_iter().flat_map(|subset| {
+        [
+            annotations_dir.parent().map(|root| root.join(subset)),
+            Some(annotations_dir.join(subset)),
+        ]
+    });
+    candidates
+        .flatten()
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| annotations_dir.to_path_buf())
+}
+
+// Function to check whether a file is a COCO instances file from its name
+fn is_instances_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(INSTANCES_PREFIX) && name.ends_with(".json"))
+}
+
+// Function to discover the splits of a COCO dataset, one per instances file in its annotations
+// folder or at its root, named after the file without the prefix, "train2017" for
+// "instances_train2017.json"
+pub(crate) fn discover_coco_splits(dataset_path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut splits = Vec::new();
+    for dir in [dataset_path.join("annotations"), dataset_path.to_path_buf()] {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir).map_err(Error::io("Failed to read", &dir))? {
+            let path = entry.map_err(Error::io("Failed to read", &dir))?.path();
+            if !is_instances_file(&path) {
+                continue;
+            }
+            let name = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.strip_prefix(INSTANCES_PREFIX));
+            if let Some(name) = name {
+                splits.push((name.to_string(), path));
+            }
+        }
+    }
+    splits.sort();
+    Ok(splits)
+}
+
+impl DataSource for CocoSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // Use the root itself when it is an instances file, or else every one under it
+        let instances_paths = if self.root.is_file() {
+            vec![self.root.clone()]
+        } else {
+            discover_coco_splits(&self.root)?
+                .into_iter()
+                .map(|(_, path)| path)
+                .collect()
+        };
+
+        // List the images of every instances file in order
+        let mut metadata = HashMap::new();
+        let mut samples = Vec::new();
+        for instances_path in instances_paths {
+            samples.extend(self.list_instances(&instances_path, scan_progress, &mut metadata)?);
+        }
+
+        // Keep the objects of every image for loading
+        *self.metadata.lock().unwrap() = metadata;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read the image file and attach the objects annotated on it
+        let (path, label) = sample;
+        let metadata = self
+            .metadata
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .unwrap_or_default();
+        Ok(Record {
+            bytes: read_image_as_bytes(path)?,
+            label: label.clone(),
+            metadata,
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Read the image size from the file metadata without opening the file
+        fs::metadata(&sample.0).map(|m| m.len()).unwrap_or(0)
+    }
+
+    fn metadata_columns(&self) -> BTreeMap<String, ColumnType> {
+        BTreeMap::from([
+            ("image_id".to_string(), ColumnType::Int64),
+            ("width".to_string(), ColumnType::Int64),
+            ("height".to_string(), ColumnType::Int64),
+            ("bboxes".to_string(), ColumnType::BboxList),
+            ("category_ids".to_string(), ColumnType::Int64List),
+            ("categories".to_string(), ColumnType::Utf8List),
+        ])
+    }
+}
@@ -0,0 +1,396 @@
+use super::{find_input_files, label_name, DataSource, Record, SampleRef, SourceSettings};
+use crate::cli::InputFormat;
+use crate::error::{Error, Result};
+use crate::schema::{ColumnType, SampleKind};
+use hdf5_reader::{Dataset, Datatype, Hdf5File, SliceInfo, SliceInfoElem};
+use indicatif::ProgressBar;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Define the datasets holding the image stack and the labels when none are given
+const DEFAULT_IMAGES_DATASET: &str = "images";
+const DEFAULT_LABELS_DATASET: &str = "labels";
+
+// Define the columns describing the layout of the raw data of every image
+const SHAPE_COLUMN: &str = "shape";
+const DTYPE_COLUMN: &str = "dtype";
+
+// Macro to evaluate an expression with the Rust type of the elements of a numeric HDF5 dataset
+// bound to the given name, or to None for the other data types
+macro_rules! with_numeric_type {
+    ($dtype:expr, $ty:ident => $body:expr) => {
+        match $dtype {
+            Datatype::FixedPoint {
+                size: 1,
+                signed: false,
+                ..
+            } => {
+                type $ty = u8;
+                Some($body)
+            }
+            Datatype::FixedPoint {
+                size: 1,
+                signed: true,
+                ..
+            } => {
+                type $ty = i8;
+                Some($body)
+            }
+            Datatype::FixedPoint {
+                size: 2,
+                signed: false,
+                ..
+            } => {
+                type $ty = u16;
+                Some($body)
+            }
+            Datatype::FixedPoint {
+                size: 2,
+                signed: true,
+                ..
+            } => {
+                type $ty = i16;
+                Some($body)
+            }
+            Datatype::FixedPoint {
+                size: 4,
+                signed: false,
+                ..
+            } => {
+                type $ty = u32;
+                Some($body)
+            }
+            Datatype::FixedPoint {
+                size: 4,
+                signed: true,
+                ..
+            } => {
+                type $ty = i32;
+                Some($body)
+            }
+            Datatype::FixedPoint {
+                size: 8,
+                signed: false,
+                ..
+            } => {
+                type $ty = u64;
+                Some($body)
+            }
+            Datatype::FixedPoint {
+                size: 8,
+                signed: true,
+                ..
+            } => {
+                type $ty = i64;
+                Some($body)
+            }
+            Datatype::FloatingPoint { size: 4, .. } => {
+                type $ty = f32;
+                Some($body)
+            }
+            Datatype::FloatingPoint { size: 8, .. } => {
+                type $ty = f64;
+                Some($body)
+            }
+            _ => None,
+        }
+    };
+}
+
+// Define where an image of a stack is stored, the size of its raw data and the values of the
+// other datasets for it
+struct StackLocation {
+    file: PathBuf,
+    index: u64,
+    size: u64,
+    metadata: BTreeMap<String, String>,
+}
+
+// Define a source reading the image stacks of HDF5 files, such as the scientific and medical
+// datasets written with h5py, every slice along the first axis of the images dataset being a
+// sample labelled by the labels dataset, with other per-sample datasets becoming extra columns
+pub struct Hdf5Source {
+    root: PathBuf,
+    images: String,
+    labels: Option<String>,
+    columns: Vec<(String, String)>,
+    label_names: Vec<String>,
+    locations: Mutex<HashMap<PathBuf, StackLocation>>,
+    column_types: Mutex<BTreeMap<String, ColumnType>>,
+}
+
+// Function to open the source reading the HDF5 files of a split
+pub(crate) fn open_hdf5_source(
+    root: &Path,
+    settings: &SourceSettings,
+) -> Result<Arc<dyn DataSource>> {
+    Ok(Arc::new(Hdf5Source {
+        root: root.to_path_buf(),
+        images: settings
+            .hdf5_images
+            .clone()
+            .unwrap_or_else(|| DEFAULT_IMAGES_DATASET.to_string()),
+        labels: settings.hdf5_labels.clone(),
+        columns: settings.hdf5_columns.clone(),
+        label_names: settings.label_names.clone(),
+        locations: Mutex::new(HashMap::new()),
+        column_types: Mutex::new(BTreeMap::new()),
+    }))
+}
+
+// Function to convert an error of the HDF5 reader into an I/O error
+fn invalid_data(error: hdf5_reader::error::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+// Function to get the NumPy name of the data type of a numeric dataset, in the little-endian byte
+// order its raw data is written in
+fn numpy_dtype(dtype: &Datatype) -> Option<String> {
+    match dtype {
+        Datatype::FixedPoint {
+            size: 1, signed, ..
+        } => Some(format!("|{}1", if *signed { 'i' } else { 'u' })),
+        Datatype::FixedPoint { size, signed, .. } => {
+            Some(format!("<{}{}", if *signed { 'i' } else { 'u' }, size))
+        }
+        Datatype::FloatingPoint { size, .. } => Some(format!("<f{}", size)),
+        _ => None,
+    }
+}
+
+// Function to read the image at an index of a stack as little-endian raw data
+fn read_image(images: &Dataset, index: u64) -> io::Result<Vec<u8>> {
+    // Select the slice at the index along the first axis and the whole of the other axes
+    let mut selections = vec![SliceInfoElem::Index(index)];
+    selections.extend((1..images.ndim()).map(|_| SliceInfoElem::Slice {
+        start: 0,
+        end: u64::MAX,
+        step: 1,
+    }));
+    let selection = SliceInfo { selections };
+
+    // Read the slice with the element type of the dataset
+    with_numeric_type!(images.dtype(), T => images
+        .read_slice::<T>(&selection)
+        .map(|image| image.iter().flat_map(|value| value.to_le_bytes()).collect()))
+    .ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported image data type {:?}", images.dtype()),
+        )
+    })?
+    .map_err(invalid_data)
+}
+
+// Function to read the values of a dataset holding one number or string per sample, as strings
+// with the type of column they fit in
+fn read_values(dataset: &Dataset) -> io::Result<(ColumnType, Vec<String>)> {
+    if dataset.ndim() != 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Dataset {} does not have one value per sample",
+                dataset.name()
+            ),
+        ));
+    }
+    if let Datatype::String { .. } = dataset.dtype() {
+        return Ok((
+            ColumnType::Utf8,
+            dataset.read_strings().map_err(invalid_data)?,
+        ));
+    }
+    let column_type = match dataset.dtype() {
+        Datatype::FloatingPoint { .. } => ColumnType::Float64,
+        _ => ColumnType::Int64,
+    };
+    let values = with_numeric_type!(dataset.dtype(), T => dataset
+        .read_array::<T>()
+        .map(|values| values.iter().map(T::to_string).collect()))
+    .ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Unsupported data type {:?} of {}",
+                dataset.dtype(),
+                dataset.name()
+            ),
+        )
+    })?
+    .map_err(invalid_data)?;
+    Ok((column_type, values))
+}
+
+impl Hdf5Source {
+    // Function to read the labels of the images of a file from the labels dataset, naming integer
+    // labels by the label names, or no labels when the default dataset is missing
+    fn read_labels(&self, file: &Hdf5File, count: usize) -> io::Result<Vec<String>> {
+        let name = self.labels.as_deref().unwrap_or(DEFAULT_LABELS_DATASET);
+        let dataset = match file.dataset(name) {
+            Ok(dataset) => dataset,
+            Err(_) if self.labels.is_none() => return Ok(vec![String::new(); count]),
+            Err(e) => return Err(invalid_data(e)),
+        };
+        let (column_type, labels) = read_values(&dataset)?;
+        Ok(match column_type {
+            ColumnType::Int64 => labels
+                .iter()
+                .map(|label| label_name(&self.label_names, label.parse().unwrap_or_default()))
+                .collect(),
+            _ => labels,
+        })
+    }
+
+    // Function to list the images of an HDF5 file with their labels and the values of the other
+    // datasets for them
+    fn list_file(
+        &self,
+        path: &Path,
+        locations: &mut HashMap<PathBuf, StackLocation>,
+        samples: &mut Vec<SampleRef>,
+    ) -> io::Result<()> {
+        // Find the image count and the size of every image from the shape of the stack
+        let file = Hdf5File::open(path).map_err(invalid_data)?;
+        let images = file.dataset(&self.images).map_err(invalid_data)?;
+        let Some((&count, image_shape)) = images.shape().split_first() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Dataset {} is not a stack of images", self.images),
+            ));
+        };
+        let dtype = numpy_dtype(images.dtype()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported image data type {:?}", images.dtype()),
+            )
+        })?;
+        let item_size = images.raw_element_size().map_err(invalid_data)? as u64;
+        let size = image_shape.iter().product::<u64>() * item_size;
+        let shape = serde_json::to_string(image_shape).map_err(io::Error::other)?;
+
+        // Read the labels and the values of the other datasets, which must have one per image
+        let labels = self.read_labels(&file, count as usize)?;
+        let mut columns = Vec::new();
+        for (column, name) in &self.columns {
+            let dataset = file.dataset(name).map_err(invalid_data)?;
+            let (column_type, values) = read_values(&dataset)?;
+            self.column_types
+                .lock()
+                .unwrap()
+                .insert(column.clone(), column_type);
+            columns.push((column, values));
+        }
+        let mismatched = std::iter::once(("labels", labels.len()))
+            .chain(
+                columns
+                    .iter()
+                    .map(|(column, values)| (column.as_str(), values.len())),
+            )
+            .find(|&(_, length)| length as u64 != count);
+        if let Some((column, length)) = mismatched {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} has {} values for {} images", column, length, count),
+            ));
+        }
+
+        // Name every image after its file and position in the stack
+        for (index, label) in labels.into_iter().enumerate() {
+            let mut metadata = BTreeMap::from([
+                (SHAPE_COLUMN.to_string(), shape.clone()),
+                (DTYPE_COLUMN.to_string(), dtype.clone()),
+            ]);
+            for (column, values) in &columns {
+                metadata.insert(column.to_string(), values[index].clone());
+            }
+            let sample_path = path.join(index.to_string());
+            locations.insert(
+                sample_path.clone(),
+                StackLocation {
+                    file: path.to_path_buf(),
+                    index: index as u64,
+                    size,
+                    metadata,
+                },
+            );
+            samples.push((sample_path, label));
+        }
+        Ok(())
+    }
+}
+
+impl DataSource for Hdf5Source {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        let mut locations = HashMap::new();
+        let mut samples = Vec::new();
+        for path in find_input_files(InputFormat::Hdf5, &self.root) {
+            let count = samples.len();
+            self.list_file(&path, &mut locations, &mut samples)
+                .map_err(Error::io("Failed to read", &path))?;
+            scan_progress.inc((samples.len() - count) as u64);
+        }
+
+        // Keep the location of every image for loading
+        *self.locations.lock().unwrap() = locations;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        self.load_many(std::slice::from_ref(sample))
+            .next()
+            .unwrap_or_else(|| Err(io::Error::other("Sample was not loaded")))
+    }
+
+    fn load_many<'a>(
+        &'a self,
+        samples: &'a [SampleRef],
+    ) -> Box<dyn Iterator<Item = io::Result<Record>> + 'a> {
+        // Read the images in order, reopening a file only when the next image is in another one
+        let locations = self.locations.lock().unwrap();
+        let mut open_stack: Option<(&Path, Dataset)> = None;
+        let records: Vec<io::Result<Record>> = samples
+            .iter()
+            .map(|(path, label)| {
+                let location = locations.get(path).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, "Image was not listed")
+                })?;
+                let images = match &mut open_stack {
+                    Some((file, images)) if *file == location.file => images,
+                    _ => {
+                        let file = Hdf5File::open(&location.file).map_err(invalid_data)?;
+                        let images = file.dataset(&self.images).map_err(invalid_data)?;
+                        &mut open_stack.insert((&location.file, images)).1
+                    }
+                };
+                Ok(Record {
+                    bytes: read_image(images, location.index)?,
+                    label: label.clone(),
+                    metadata: location.metadata.clone(),
+                })
+            })
+            .collect();
+        Box::new(records.into_iter())
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Use the image size found from the shape of the stack while listing
+        self.locations
+            .lock()
+            .unwrap()
+            .get(&sample.0)
+            .map_or(0, |location| location.size)
+    }
+
+    fn metadata_columns(&self) -> BTreeMap<String, ColumnType> {
+        let mut columns = self.column_types.lock().unwrap().clone();
+        columns.insert(SHAPE_COLUMN.to_string(), ColumnType::Int64List);
+        columns.insert(DTYPE_COLUMN.to_string(), ColumnType::Utf8);
+        columns
+    }
+
+    fn sample_kind(&self) -> SampleKind {
+        SampleKind::Array
+    }
+}
@@ -0,0 +1,12 @@
+use super::{DataSource, SourceSettings};
+use crate::error::{Error, Result};
+use std::path::Path;
+use std::sync::Arc;
+
+// Function to report that reading HDF5 files is unavailable in this build
+pub(crate) fn open_hdf5_source(
+    _root: &Path,
+    _settings: &SourceSettings,
+) -> Result<Arc<dyn DataSource>> {
+    Err(Error::FeatureDisabled("Reading HDF5 files", "hdf5"))
+}
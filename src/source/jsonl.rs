@@ -0,0 +1,169 @@
+use super::{find_input_files, read_image_as_bytes, DataSource, Record, SampleRef};
+use crate::cli::InputFormat;
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use crate::schema::ColumnType;
+use indicatif::ProgressBar;
+use serde_json::{Deserializer, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Define the keys of a manifest line holding the image path and the label
+const FILE_KEY: &str = "file";
+const LABEL_KEY: &str = "label";
+
+// Define a source reading the image paths, labels and any other fields from JSON Lines manifests,
+// turning the other fields into extra columns such as captions or attributes
+pub struct JsonlSource {
+    root: PathBuf,
+    filter: PathFilter,
+    metadata: Mutex<HashMap<PathBuf, BTreeMap<String, String>>>,
+    columns: Mutex<BTreeMap<String, ColumnType>>,
+}
+
+impl JsonlSource {
+    // Function to create a source over a manifest or a folder of manifests, keeping the images
+    // accepted by the filter
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        JsonlSource {
+            root: root.into(),
+            filter,
+            metadata: Mutex::new(HashMap::new()),
+            columns: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    // Function to list the lines of a manifest, resolving the image paths against its folder and
+    // collecting the other fields of every line with the type of their column
+    fn list_manifest(
+        &self,
+        manifest_path: &Path,
+        scan_progress: &ProgressBar,
+        metadata: &mut HashMap<PathBuf, BTreeMap<String, String>>,
+        columns: &mut BTreeMap<String, ColumnType>,
+    ) -> Result<Vec<SampleRef>> {
+        // Read the manifest as a stream of JSON objects, one per line
+        let file = File::open(manifest_path).map_err(Error::io("Failed to open", manifest_path))?;
+        let lines =
+            Deserializer::from_reader(BufReader::new(file)).into_iter::<Map<String, Value>>();
+
+        // Keep the lines whose file passes the filter, in the order of the manifest
+        let base = manifest_path.parent().unwrap_or(Path::new(""));
+        let mut samples = Vec::new();
+        for line in lines {
+            // Take the image path and the label out of the line
+            let mut line = line.map_err(Error::json(manifest_path))?;
+            let Some(Value::String(file)) = line.remove(FILE_KEY) else {
+                return Err(Error::MissingColumn {
+                    path: manifest_path.to_path_buf(),
+                    column: FILE_KEY.to_string(),
+                });
+            };
+            if !self.filter.matches(Path::new(&file)) {
+                continue;
+            }
+            let label = line.remove(LABEL_KEY).and_then(metadata_value);
+
+            // Store the remaining fields as metadata, widening the type of their column to fit
+            let mut fields = BTreeMap::new();
+            for (key, value) in line {
+                let Some(column_type) = value_type(&value) else {
+                    continue;
+                };
+                columns
+                    .entry(key.clone())
+                    .and_modify(|existing| *existing = widen(*existing, column_type))
+                    .or_insert(column_type);
+                fields.extend(metadata_value(value).map(|value| (key, value)));
+            }
+            let path = base.join(file);
+            metadata.insert(path.clone(), fields);
+            samples.push((path, label.unwrap_or_default()));
+            scan_progress.inc(1);
+        }
+        Ok(samples)
+    }
+}
+
+// Function to get the column type of a JSON value, or None for nulls which fit every type
+fn value_type(value: &Value) -> Option<ColumnType> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) => Some(ColumnType::Bool),
+        Value::Number(number) if number.is_i64() => Some(ColumnType::Int64),
+        Value::Number(_) => Some(ColumnType::Float64),
+        _ => Some(ColumnType::Utf8),
+    }
+}
+
+// Function to get the type of a column holding values of both types
+fn widen(existing: ColumnType, other: ColumnType) -> ColumnType {
+    match (existing, other) {
+        (a, b) if a == b => a,
+        (ColumnType::Int64, ColumnType::Float64) | (ColumnType::Float64, ColumnType::Int64) => {
+            ColumnType::Float64
+        }
+        _ => ColumnType::Utf8,
+    }
+}
+
+// Function to turn a JSON value into the string stored in the metadata of a record, with strings
+// unquoted and arrays and objects kept as JSON text
+fn metadata_value(value: Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(value) => Some(value),
+        value => Some(value.to_string()),
+    }
+}
+
+impl DataSource for JsonlSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // List the lines of every manifest in order
+        let mut metadata = HashMap::new();
+        let mut columns = BTreeMap::new();
+        let mut samples = Vec::new();
+        for manifest_path in find_input_files(InputFormat::Jsonl, &self.root) {
+            samples.extend(self.list_manifest(
+                &manifest_path,
+                scan_progress,
+                &mut metadata,
+                &mut columns,
+            )?);
+        }
+
+        // Keep the fields of every sample and the types of their columns for the conversion
+        *self.metadata.lock().unwrap() = metadata;
+        *self.columns.lock().unwrap() = columns;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read the image file and attach the label and fields given by the manifest
+        let (path, label) = sample;
+        let metadata = self
+            .metadata
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .unwrap_or_default();
+        Ok(Record {
+            bytes: read_image_as_bytes(path)?,
+            label: label.clone(),
+            metadata,
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Read the image size from the file metadata without opening the file
+        fs::metadata(&sample.0).map(|m| m.len()).unwrap_or(0)
+    }
+
+    fn metadata_columns(&self) -> BTreeMap<String, ColumnType> {
+        self.columns.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,272 @@
+use super::{label_name, DataSource, Record, SampleRef, SourceSettings};
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use heed::types::Bytes;
+use heed::{Database, Env, EnvFlags, EnvOpenOptions, RoTxn, WithoutTls};
+use indicatif::ProgressBar;
+use prost::Message;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use walkdir::WalkDir;
+
+// Define the data file found in the folder of every LMDB environment
+const DATA_FILE_NAME: &str = "data.mdb";
+
+// Define the Datum message Caffe stores in its LMDB databases, either an encoded image or raw
+// pixels in channel, row, column order with the channels in BGR order
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct Datum {
+    #[prost(int32, tag = "1")]
+    pub channels: i32,
+    #[prost(int32, tag = "2")]
+    pub height: i32,
+    #[prost(int32, tag = "3")]
+    pub width: i32,
+    #[prost(bytes = "vec", tag = "4")]
+    pub data: Vec<u8>,
+    #[prost(int32, tag = "5")]
+    pub label: i32,
+    #[prost(bool, tag = "7")]
+    pub encoded: bool,
+}
+
+// Define an LMDB environment of a source with its unnamed database, and the label given to values
+// that are plain encoded images
+struct LmdbDatabase {
+    env: Env<WithoutTls>,
+    database: Database<Bytes, Bytes>,
+    label: String,
+}
+
+// Define a source reading images from LMDB databases, such as the LSUN scenes, whose values are
+// encoded images labelled by the folder of the database, or the Caffe databases, whose values are
+// Datum messages carrying their integer label
+pub struct LmdbSource {
+    root: PathBuf,
+    filter: PathFilter,
+    label_names: Vec<String>,
+    databases: Mutex<Vec<LmdbDatabase>>,
+    keys: Mutex<HashMap<PathBuf, (usize, Vec<u8>)>>,
+}
+
+// Function to open the source reading the LMDB databases of a split
+pub(crate) fn open_lmdb_source(
+    root: &Path,
+    settings: &SourceSettings,
+) -> Result<Arc<dyn DataSource>> {
+    Ok(Arc::new(LmdbSource {
+        root: root.to_path_buf(),
+        filter: settings.filter.clone(),
+        label_names: settings.label_names.clone(),
+        databases: Mutex::new(Vec::new()),
+        keys: Mutex::new(HashMap::new()),
+    }))
+}
+
+// Function to open an LMDB environment read-only with its unnamed database
+fn open_database(path: &Path) -> Result<LmdbDatabase> {
+    // Open the environment without writing to it, the map size being taken from the data file
+    let mut options = EnvOpenOptions::new().read_txn_without_tls();
+    // SAFETY: the environment is only read, and the converter does not modify it while it is open
+    let env = unsafe {
+        options.flags(EnvFlags::READ_ONLY);
+        options.open(path)?
+    };
+
+    // Open the unnamed database holding the samples
+    let rtxn = env.read_txn()?;
+    let database = env
+        .open_database::<Bytes, Bytes>(&rtxn, None)?
+        .ok_or_else(|| {
+            Error::Unsupported(format!("LMDB environment {:?} without a database", path))
+        })?;
+    rtxn.commit()?;
+
+    // Label plain images by the name of the database folder
+    let label = path
+        .file_name()
+        .map_or(String::new(), |name| name.to_string_lossy().to_string());
+    Ok(LmdbDatabase {
+        env,
+        database,
+        label,
+    })
+}
+
+// Function to decode a value as a Caffe Datum, or None when it is a plain encoded image
+fn decode_datum(value: &[u8]) -> Option<Datum> {
+    Datum::decode(value)
+        .ok()
+        .filter(|datum| !datum.data.is_empty() && (datum.encoded || datum.channels > 0))
+}
+
+// Function to get the image bytes of a Datum, wrapping raw pixels into a binary PGM or PPM image
+fn datum_image(datum: Datum) -> io::Result<Vec<u8>> {
+    // Keep encoded images as they are
+    if datum.encoded {
+        return Ok(datum.data);
+    }
+
+    // Check that the raw pixels match the recorded shape
+    let (channels, height, width) = (
+        datum.channels as usize,
+        datum.height as usize,
+        datum.width as usize,
+    );
+    let magic = match channels {
+        1 => "P5",
+        3 => "P6",
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Datum with {} channels", channels),
+            ))
+        }
+    };
+    if datum.data.len() != channels * height * width {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Datum pixels do not match its shape",
+        ));
+    }
+
+    // Interleave the planes into rows of RGB pixels after the header
+    let mut image = format!("{}\n{} {}\n255\n", magic, width, height).into_bytes();
+    let plane = height * width;
+    for pixel in 0..plane {
+        for channel in (0..channels).rev() {
+            image.push(datum.data[channel * plane + pixel]);
+        }
+    }
+    Ok(image)
+}
+
+// Function to find the LMDB environments under a folder: the folder itself, or every folder under
+// it holding a data file, in a stable order
+fn environment_dirs(root: &Path) -> Vec<PathBuf> {
+    if root.join(DATA_FILE_NAME).is_file() {
+        return vec![root.to_path_buf()];
+    }
+    WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| path.join(DATA_FILE_NAME).is_file())
+        .collect()
+}
+
+// Function to discover the splits of an LMDB dataset, one per folder directly under the root
+// holding LMDB environments, named after the folder
+pub(crate) fn discover_lmdb_splits(dataset_path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut splits: Vec<(String, PathBuf)> = fs::read_dir(dataset_path)
+        .map_err(Error::io("Failed to read", dataset_path))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && !environment_dirs(path).is_empty())
+        .filter_map(|path| Some((path.file_name()?.to_str()?.to_string(), path)))
+        .collect();
+    splits.sort();
+    Ok(splits)
+}
+
+impl LmdbSource {
+    // Function to read the record stored under a key, labelling Datum values by their integer
+    // label and plain images by their database
+    fn read_record(
+        &self,
+        database: &LmdbDatabase,
+        rtxn: &RoTxn<WithoutTls>,
+        key: &[u8],
+    ) -> io::Result<Record> {
+        let value = database
+            .database
+            .get(rtxn, key)
+            .map_err(io::Error::other)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Key is not in the database"))?;
+        let (bytes, label) = match decode_datum(value) {
+            Some(datum) => {
+                let label = label_name(&self.label_names, datum.label.into());
+                (datum_image(datum)?, label)
+            }
+            None => (value.to_vec(), database.label.clone()),
+        };
+        Ok(Record {
+            bytes,
+            label,
+            metadata: BTreeMap::new(),
+        })
+    }
+}
+
+impl DataSource for LmdbSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        let mut databases = Vec::new();
+        let mut keys = HashMap::new();
+        let mut samples = Vec::new();
+        for (index, env_dir) in environment_dirs(&self.root).iter().enumerate() {
+            // Iterate over the entries of the database in key order
+            let database = open_database(env_dir)?;
+            let rtxn = database.env.read_txn()?;
+            for entry in database.database.iter(&rtxn)? {
+                // Keep the keys passing the include and exclude patterns, which have no extension
+                let (key, value) = entry?;
+                let name = PathBuf::from(String::from_utf8_lossy(key).as_ref());
+                if !self.filter.matches_patterns(&name) {
+                    continue;
+                }
+
+                // Label the sample from its Datum, or else by its database
+                let label = match decode_datum(value) {
+                    Some(datum) => label_name(&self.label_names, datum.label.into()),
+                    None => database.label.clone(),
+                };
+                let path = env_dir.join(name);
+                keys.insert(path.clone(), (index, key.to_vec()));
+                samples.push((path, label));
+                scan_progress.inc(1);
+            }
+            drop(rtxn);
+            databases.push(database);
+        }
+
+        // Keep the databases open and the key of every sample for loading
+        *self.databases.lock().unwrap() = databases;
+        *self.keys.lock().unwrap() = keys;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        self.load_many(std::slice::from_ref(sample))
+            .next()
+            .unwrap_or_else(|| Err(io::Error::other("Sample was not loaded")))
+    }
+
+    fn load_many<'a>(
+        &'a self,
+        samples: &'a [SampleRef],
+    ) -> Box<dyn Iterator<Item = io::Result<Record>> + 'a> {
+        // Read the samples of every database within a single read transaction
+        let databases = self.databases.lock().unwrap();
+        let keys = self.keys.lock().unwrap();
+        let mut transactions: HashMap<usize, RoTxn<WithoutTls>> = HashMap::new();
+        let records: Vec<io::Result<Record>> = samples
+            .iter()
+            .map(|(path, _)| {
+                let (index, key) = keys
+                    .get(path)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Key was not listed"))?;
+                let database = &databases[*index];
+                if !transactions.contains_key(index) {
+                    let rtxn = database.env.read_txn().map_err(io::Error::other)?;
+                    transactions.insert(*index, rtxn);
+                }
+                self.read_record(database, &transactions[index], key)
+            })
+            .collect();
+        Box::new(records.into_iter())
+    }
+}
@@ -0,0 +1,17 @@
+use super::{DataSource, SourceSettings};
+use crate::error::{Error, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// Function to report that discovering the splits of LMDB datasets is unavailable in this build
+pub(crate) fn discover_lmdb_splits(_dataset_path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    Err(Error::FeatureDisabled("Reading LMDB databases", "lmdb"))
+}
+
+// Function to report that reading LMDB databases is unavailable in this build
+pub(crate) fn open_lmdb_source(
+    _root: &Path,
+    _settings: &SourceSettings,
+) -> Result<Arc<dyn DataSource>> {
+    Err(Error::FeatureDisabled("Reading LMDB databases", "lmdb"))
+}
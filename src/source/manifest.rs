@@ -0,0 +1,106 @@
+use super::{find_input_files, read_image_as_bytes, DataSource, Record, SampleRef};
+use crate::cli::InputFormat;
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use csv::{ReaderBuilder, StringRecord};
+use indicatif::ProgressBar;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Define the names of the manifest columns holding the image path and the label
+const PATH_COLUMN: &str = "path";
+const LABEL_COLUMN: &str = "label";
+
+// Define a source reading the image paths and labels from CSV or TSV manifests, for datasets whose
+// labels come from an annotation export rather than the folder layout
+pub struct ManifestSource {
+    root: PathBuf,
+    filter: PathFilter,
+}
+
+impl ManifestSource {
+    // Function to create a source over a manifest or a folder of manifests, keeping the images
+    // accepted by the filter
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        ManifestSource {
+            root: root.into(),
+            filter,
+        }
+    }
+
+    // Function to list the rows of a manifest, resolving the image paths against its folder
+    fn list_manifest(
+        &self,
+        manifest_path: &Path,
+        scan_progress: &ProgressBar,
+    ) -> Result<Vec<SampleRef>> {
+        // Read tab-separated values from .tsv manifests and comma-separated values otherwise
+        let delimiter = match manifest_path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("tsv") => b'\t',
+            _ => b',',
+        };
+        let mut reader = ReaderBuilder::new()
+            .delimiter(delimiter)
+            .from_path(manifest_path)
+            .map_err(Error::csv(manifest_path))?;
+
+        // Find the path and label columns in the header
+        let headers = reader.headers().map_err(Error::csv(manifest_path))?.clone();
+        let path_column = column_index(&headers, PATH_COLUMN, manifest_path)?;
+        let label_column = column_index(&headers, LABEL_COLUMN, manifest_path)?;
+
+        // Keep the rows whose path passes the filter, in the order of the manifest
+        let base = manifest_path.parent().unwrap_or(Path::new(""));
+        let mut samples = Vec::new();
+        for row in reader.records() {
+            let row = row.map_err(Error::csv(manifest_path))?;
+            let path = Path::new(row.get(path_column).unwrap_or_default());
+            if !self.filter.matches(path) {
+                continue;
+            }
+            let label = row.get(label_column).unwrap_or_default().to_string();
+            samples.push((base.join(path), label));
+            scan_progress.inc(1);
+        }
+        Ok(samples)
+    }
+}
+
+// Function to find a column of a manifest by name
+fn column_index(headers: &StringRecord, column: &str, manifest_path: &Path) -> Result<usize> {
+    headers
+        .iter()
+        .position(|header| header.trim() == column)
+        .ok_or_else(|| Error::MissingColumn {
+            path: manifest_path.to_path_buf(),
+            column: column.to_string(),
+        })
+}
+
+impl DataSource for ManifestSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // List the rows of every manifest in order
+        let mut samples = Vec::new();
+        for manifest_path in find_input_files(InputFormat::Csv, &self.root) {
+            samples.extend(self.list_manifest(&manifest_path, scan_progress)?);
+        }
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read the image file and keep the label given by the manifest
+        let (path, label) = sample;
+        Ok(Record {
+            bytes: read_image_as_bytes(path)?,
+            label: label.clone(),
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Read the image size from the file metadata without opening the file
+        fs::metadata(&sample.0).map(|m| m.len()).unwrap_or(0)
+    }
+}
@@ -0,0 +1,234 @@
+use super::{collect_image_paths, read_image_as_bytes, DataSource, Record, SampleRef};
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use crate::schema::{ColumnType, SampleKind};
+use indicatif::ProgressBar;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use zip::ZipArchive;
+
+// Define the extensions of single arrays and of archives of arrays
+const NUMPY_EXTENSIONS: &[&str] = &["npy", "npz"];
+
+// Define the magic string starting every .npy file
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+// Define the columns describing the layout of the raw data of every array
+const SHAPE_COLUMN: &str = "shape";
+const DTYPE_COLUMN: &str = "dtype";
+const FORTRAN_ORDER_COLUMN: &str = "fortran_order";
+
+// Define where the member of an .npz archive holding an array is stored
+struct MemberLocation {
+    archive: PathBuf,
+    index: usize,
+    size: u64,
+}
+
+// Define a source reading NumPy arrays from a folder with one subfolder per label, every .npy file
+// and every array of an .npz archive being a sample whose raw data is stored with its shape and
+// data type, such as precomputed features or medical volumes
+pub struct NumpyFolderSource {
+    root: PathBuf,
+    filter: PathFilter,
+    members: Mutex<HashMap<PathBuf, MemberLocation>>,
+}
+
+impl NumpyFolderSource {
+    // Function to create a source over a split folder, keeping the files passing the include and
+    // exclude patterns
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        NumpyFolderSource {
+            root: root.into(),
+            filter: filter.with_extensions(NUMPY_EXTENSIONS),
+            members: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// Define the array read from an .npy file: its raw data and the header describing it
+struct NpyArray {
+    data: Vec<u8>,
+    dtype: String,
+    fortran_order: bool,
+    shape: Vec<u64>,
+}
+
+// Function to get the text following a key of the Python dictionary of an .npy header
+fn header_value<'h>(header: &'h str, key: &str) -> Option<&'h str> {
+    let pattern = format!("'{}':", key);
+    let start = header.find(&pattern)? + pattern.len();
+    Some(header[start..].trim_start())
+}
+
+// Function to get the size in bytes of one item of a NumPy data type such as "<f4" or "<U10",
+// or None for types without a fixed size such as Python objects
+fn item_size(dtype: &str) -> Option<u64> {
+    let kind = dtype.trim_start_matches(['<', '>', '|', '=']);
+    let (code, size) = kind.split_at(kind.find(|c: char| c.is_ascii_digit())?);
+    let size: u64 = size.parse().ok()?;
+    match code {
+        "U" => Some(size * 4),
+        "O" => None,
+        _ => Some(size),
+    }
+}
+
+// Function to parse an .npy file into its raw data and header, refusing arrays of Python objects
+// and files whose data does not match the header
+fn parse_npy(mut bytes: Vec<u8>) -> io::Result<NpyArray> {
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+
+    // Read the header length, stored on 2 bytes in version 1 and on 4 bytes in later versions
+    if !bytes.starts_with(NPY_MAGIC) || bytes.len() < 10 {
+        return Err(invalid("Not a NumPy array file"));
+    }
+    let (header_start, header_len) = match bytes[6] {
+        1 => (10, usize::from(u16::from_le_bytes([bytes[8], bytes[9]]))),
+        _ => {
+            let length = bytes
+                .get(8..12)
+                .ok_or_else(|| invalid("Truncated header"))?;
+            (12, u32::from_le_bytes(length.try_into().unwrap()) as usize)
+        }
+    };
+    let header = bytes
+        .get(header_start..header_start + header_len)
+        .ok_or_else(|| invalid("Truncated header"))?;
+    let header = String::from_utf8_lossy(header).to_string();
+
+    // Read the data type, memory order and shape from the header dictionary
+    let dtype = header_value(&header, "descr")
+        .and_then(|value| value.strip_prefix('\'')?.split('\'').next())
+        .ok_or_else(|| invalid("Header has no data type"))?
+        .to_string();
+    let fortran_order =
+        header_value(&header, "fortran_order").is_some_and(|value| value.starts_with("True"));
+    let shape: Vec<u64> = header_value(&header, "shape")
+        .and_then(|value| value.strip_prefix('(')?.split(')').next())
+        .ok_or_else(|| invalid("Header has no shape"))?
+        .split(',')
+        .map(str::trim)
+        .filter(|dimension| !dimension.is_empty())
+        .map(|dimension| dimension.trim_end_matches('L').parse())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| invalid("Header has an invalid shape"))?;
+
+    // Check that the data holds every item of the array
+    let item_size =
+        item_size(&dtype).ok_or_else(|| invalid(&format!("Unsupported data type '{}'", dtype)))?;
+    let data = bytes.split_off(header_start + header_len);
+    if data.len() as u64 != shape.iter().product::<u64>() * item_size {
+        return Err(invalid("Array data does not match its shape"));
+    }
+    Ok(NpyArray {
+        data,
+        dtype,
+        fortran_order,
+        shape,
+    })
+}
+
+// Function to check whether a file is an .npz archive, ignoring the case of its extension
+fn is_npz(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("npz"))
+}
+
+impl NumpyFolderSource {
+    // Function to read the raw bytes of the .npy file or .npz member of a sample
+    fn read_sample_bytes(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let members = self.members.lock().unwrap();
+        let Some(member) = members.get(path) else {
+            return read_image_as_bytes(path);
+        };
+        let mut archive = ZipArchive::new(BufReader::new(File::open(&member.archive)?))?;
+        let mut bytes = Vec::with_capacity(member.size as usize);
+        archive.by_index(member.index)?.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl DataSource for NumpyFolderSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // Replace every .npz archive by the arrays it holds, named after the archive and the array
+        let mut members = HashMap::new();
+        let mut samples = Vec::new();
+        for (path, label) in collect_image_paths(&self.root, &self.filter, scan_progress) {
+            if !is_npz(&path) {
+                samples.push((path, label));
+                continue;
+            }
+            let file = File::open(&path).map_err(Error::io("Failed to open", &path))?;
+            let mut archive = ZipArchive::new(BufReader::new(file))
+                .map_err(|e| Error::io("Failed to read", &path)(e.into()))?;
+            for index in 0..archive.len() {
+                let member = archive
+                    .by_index_raw(index)
+                    .map_err(|e| Error::io("Failed to read", &path)(e.into()))?;
+                let Ok(name) = member.name() else {
+                    continue;
+                };
+                let Some(name) = name.strip_suffix(".npy") else {
+                    continue;
+                };
+                let sample_path = path.join(name);
+                members.insert(
+                    sample_path.clone(),
+                    MemberLocation {
+                        archive: path.clone(),
+                        index,
+                        size: member.size(),
+                    },
+                );
+                samples.push((sample_path, label.clone()));
+            }
+        }
+
+        // Keep the location of the arrays of the archives for loading
+        *self.members.lock().unwrap() = members;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read the array and store its raw data, describing it in the companion columns
+        let (path, label) = sample;
+        let array = parse_npy(self.read_sample_bytes(path)?)?;
+        let shape = serde_json::to_string(&array.shape).map_err(io::Error::other)?;
+        Ok(Record {
+            bytes: array.data,
+            label: label.clone(),
+            metadata: BTreeMap::from([
+                (SHAPE_COLUMN.to_string(), shape),
+                (DTYPE_COLUMN.to_string(), array.dtype),
+                (
+                    FORTRAN_ORDER_COLUMN.to_string(),
+                    array.fortran_order.to_string(),
+                ),
+            ]),
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Use the decompressed size of archive members, or else the size of the file
+        match self.members.lock().unwrap().get(&sample.0) {
+            Some(member) => member.size,
+            None => fs::metadata(&sample.0).map(|m| m.len()).unwrap_or(0),
+        }
+    }
+
+    fn metadata_columns(&self) -> BTreeMap<String, ColumnType> {
+        BTreeMap::from([
+            (SHAPE_COLUMN.to_string(), ColumnType::Int64List),
+            (DTYPE_COLUMN.to_string(), ColumnType::Utf8),
+            (FORTRAN_ORDER_COLUMN.to_string(), ColumnType::Bool),
+        ])
+    }
+
+    fn sample_kind(&self) -> SampleKind {
+        SampleKind::Array
+    }
+}
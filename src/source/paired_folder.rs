@@ -0,0 +1,169 @@
+use super::{read_image_as_bytes, DataSource, Record, SampleRef};
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use crate::schema::{pack_frames, SampleKind};
+use indicatif::ProgressBar;
+use log::warn;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::WalkDir;
+
+// Define the folders holding the input and target images of a pair when none are given
+const DEFAULT_PAIR_FOLDERS: (&str, &str) = ("input", "target");
+
+// Define the images of a pair found while listing, either of which may be missing
+#[derive(Default)]
+struct ImagePair {
+    input: Option<PathBuf>,
+    target: Option<PathBuf>,
+}
+
+// Define a source reading image pairs from two parallel folders of a split, such as the low and
+// high resolution images of a super-resolution dataset or the images and masks of a segmentation
+// dataset, matching the images by their path without extension
+pub struct PairedFolderSource {
+    root: PathBuf,
+    filter: PathFilter,
+    folders: (String, String),
+    pairs: Mutex<HashMap<PathBuf, ImagePair>>,
+}
+
+impl PairedFolderSource {
+    // Function to create a source over a split folder holding the given input and target folders,
+    // or the default ones, keeping the images accepted by the filter
+    pub fn new(
+        root: impl Into<PathBuf>,
+        filter: PathFilter,
+        folders: Option<(String, String)>,
+    ) -> Self {
+        let (input, target) = DEFAULT_PAIR_FOLDERS;
+        PairedFolderSource {
+            root: root.into(),
+            filter,
+            folders: folders.unwrap_or_else(|| (input.to_string(), target.to_string())),
+            pairs: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// Function to list the images of a folder accepted by the filter by their path relative to the
+// folder without extension, which pairs them with the images of the other folder
+fn images_by_stem(folder: &Path, filter: &PathFilter) -> Result<BTreeMap<PathBuf, PathBuf>> {
+    if !folder.is_dir() {
+        return Err(Error::io("Failed to read", folder)(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Pair folder is missing",
+        )));
+    }
+    Ok(WalkDir::new(folder)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let relative = path.strip_prefix(folder).ok()?;
+            filter
+                .matches(relative)
+                .then(|| (relative.with_extension(""), path.clone()))
+        })
+        .collect())
+}
+
+impl DataSource for PairedFolderSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // Index the images of both folders by their path without extension
+        let (input_folder, target_folder) = &self.folders;
+        let mut inputs = images_by_stem(&self.root.join(input_folder), &self.filter)?;
+        let mut targets = images_by_stem(&self.root.join(target_folder), &self.filter)?;
+
+        // Pair the images in path order, keeping the unmatched ones as samples that fail to load so
+        // they are reported with the other failed samples
+        let mut stems: Vec<PathBuf> = inputs.keys().chain(targets.keys()).cloned().collect();
+        stems.sort();
+        stems.dedup();
+        let mut pairs = HashMap::new();
+        let mut samples = Vec::new();
+        let mut unmatched = 0;
+        for stem in stems {
+            let pair = ImagePair {
+                input: inputs.remove(&stem),
+                target: targets.remove(&stem),
+            };
+            let Some(path) = pair.input.clone().or_else(|| pair.target.clone()) else {
+                continue;
+            };
+            if pair.input.is_none() || pair.target.is_none() {
+                unmatched += 1;
+            }
+
+            // Label the pair by the folder of its images within the pair folders, if any
+            let label = stem
+                .parent()
+                .map_or(String::new(), |parent| parent.to_string_lossy().to_string());
+            pairs.insert(path.clone(), pair);
+            samples.push((path, label));
+            scan_progress.inc(1);
+        }
+        if unmatched > 0 {
+            warn!(
+                "{} images of {:?} have no matching image in the other pair folder",
+                unmatched, self.root
+            );
+        }
+
+        // Keep the pair of every sample for loading
+        *self.pairs.lock().unwrap() = pairs;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read both images of the pair, failing when one of them is missing
+        let (path, label) = sample;
+        let pairs = self.pairs.lock().unwrap();
+        let pair = pairs
+            .get(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Pair was not listed"))?;
+        let (input, target) = match (&pair.input, &pair.target) {
+            (Some(input), Some(target)) => (input.clone(), target.clone()),
+            (Some(_), None) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No matching image in the {} folder", self.folders.1),
+                ))
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No matching image in the {} folder", self.folders.0),
+                ))
+            }
+        };
+        drop(pairs);
+        Ok(Record {
+            bytes: pack_frames(&[read_image_as_bytes(&input)?, read_image_as_bytes(&target)?]),
+            label: label.clone(),
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Add up the sizes of both images from the file metadata without opening the files
+        let pairs = self.pairs.lock().unwrap();
+        let Some(pair) = pairs.get(&sample.0) else {
+            return 0;
+        };
+        [&pair.input, &pair.target]
+            .into_iter()
+            .flatten()
+            .map(|path| fs::metadata(path).map(|m| m.len()).unwrap_or(0))
+            .sum()
+    }
+
+    fn sample_kind(&self) -> SampleKind {
+        SampleKind::Paired
+    }
+}
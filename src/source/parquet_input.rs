@@ -0,0 +1,412 @@
+use super::{find_input_files, strip_shard_numbering, DataSource, Record, SampleRef};
+use crate::cli::InputFormat;
+use crate::error::{Error, Result};
+use crate::schema::ColumnType;
+use arrow::array::{Array, ArrayRef, AsArray};
+use arrow::compute::concat_batches;
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::util::display::array_value_to_string;
+use indicatif::ProgressBar;
+use parquet::arrow::arrow_reader::{
+    ArrowReaderMetadata, ParquetRecordBatchReaderBuilder, RowSelection, RowSelector,
+};
+use parquet::arrow::ProjectionMask;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Define the names of the columns holding the image and the label in Parquet inputs, as written by
+// HuggingFace datasets
+const IMAGE_COLUMN: &str = "image";
+const LABEL_COLUMN: &str = "label";
+
+// Define a Parquet file of a source, with its footer parsed once and the index of its image column
+struct ParquetFile {
+    path: PathBuf,
+    metadata: ArrowReaderMetadata,
+    image_column: usize,
+}
+
+// Define where the row of a sample is stored
+#[derive(Clone, Copy)]
+struct RowLocation {
+    file: usize,
+    row_group: usize,
+    row: usize,
+}
+
+// Define a source reading the images, labels and other columns of an existing Parquet dataset, such
+// as one exported by HuggingFace datasets, so it can be re-chunked into Arrow IPC files
+pub struct ParquetSource {
+    root: PathBuf,
+    files: Mutex<Vec<ParquetFile>>,
+    locations: Mutex<HashMap<PathBuf, RowLocation>>,
+    metadata: Mutex<HashMap<PathBuf, BTreeMap<String, String>>>,
+    columns: Mutex<BTreeMap<String, ColumnType>>,
+}
+
+// Function to open the source reading the rows of a Parquet dataset
+pub(crate) fn open_parquet_source(root: &Path) -> Result<Arc<dyn DataSource>> {
+    Ok(Arc::new(ParquetSource::new(root)))
+}
+
+impl ParquetSource {
+    // Function to create a source over a Parquet file, a folder of them, or the shards of a split
+    // named like "train-00000-of-00004.parquet" when the root is the split name next to them
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        ParquetSource {
+            root: root.into(),
+            files: Mutex::new(Vec::new()),
+            locations: Mutex::new(HashMap::new()),
+            metadata: Mutex::new(HashMap::new()),
+            columns: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    // Function to list the Parquet files of the source in a stable order
+    fn input_files(&self) -> Result<Vec<PathBuf>> {
+        // Use the root itself, or the files under it, when it exists
+        if self.root.exists() {
+            return Ok(find_input_files(InputFormat::Parquet, &self.root));
+        }
+
+        // Otherwise take the shards next to the root whose split name is the name of the root
+        let dir = self.root.parent().unwrap_or(Path::new(""));
+        let name = self.root.file_name().and_then(|name| name.to_str());
+        let mut shards: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(Error::io("Failed to read", dir))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && super::is_input_file(InputFormat::Parquet, path))
+            .filter(|path| name.is_some() && shard_split_name(path).as_deref() == name)
+            .collect();
+        shards.sort();
+        Ok(shards)
+    }
+}
+
+// Function to get the split name of a Parquet file, stripping the shard numbering of names such as
+// "train-00000-of-00004.parquet" or "train-00000-of-00004-1a2b3c.parquet"
+fn shard_split_name(path: &Path) -> Option<String> {
+    Some(strip_shard_numbering(path.file_stem()?.to_str()?))
+}
+
+// Function to discover the splits of a Parquet dataset: every folder holding Parquet files, and the
+// Parquet files at the root grouped by split name
+pub(crate) fn discover_parquet_splits(dataset_path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    // Look into the data folder, where HuggingFace repositories keep their shards, when it holds
+    // shards directly
+    let data_dir = dataset_path.join("data");
+    let has_shards = fs::read_dir(&data_dir).is_ok_and(|entries| {
+        entries
+            .filter_map(|entry| entry.ok())
+            .any(|entry| super::is_input_file(InputFormat::Parquet, &entry.path()))
+    });
+    if has_shards {
+        return discover_parquet_splits(&data_dir);
+    }
+
+    // Otherwise take every folder holding Parquet files and every group of shards at the root
+    let mut splits: Vec<(String, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(dataset_path).map_err(Error::io("Failed to read", dataset_path))? {
+        let path = entry
+            .map_err(Error::io("Failed to read", dataset_path))?
+            .path();
+        let split = if path.is_dir() {
+            if find_input_files(InputFormat::Parquet, &path).is_empty() {
+                continue;
+            }
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| (name.to_string(), path.clone()))
+        } else if super::is_input_file(InputFormat::Parquet, &path) {
+            shard_split_name(&path).map(|name| (name.clone(), dataset_path.join(name)))
+        } else {
+            None
+        };
+        if let Some(split) = split.filter(|split| !splits.contains(split)) {
+            splits.push(split);
+        }
+    }
+
+    splits.sort();
+    Ok(splits)
+}
+
+// Function to find the image column of a Parquet schema: the "image" column, or else the first
+// binary column or struct with a bytes field
+fn find_image_column(schema: &SchemaRef) -> Option<usize> {
+    schema.index_of(IMAGE_COLUMN).ok().or_else(|| {
+        schema
+            .fields()
+            .iter()
+            .position(|field| is_image_type(field.data_type()))
+    })
+}
+
+// Function to check whether a column type can hold images, as raw bytes or as the bytes field of
+// the image struct of HuggingFace datasets
+fn is_image_type(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Binary | DataType::LargeBinary | DataType::BinaryView => true,
+        DataType::Struct(fields) => fields.iter().any(|field| field.name() == "bytes"),
+        _ => false,
+    }
+}
+
+// Function to get the bytes of the image in a row of an image column
+fn image_bytes(array: &dyn Array, index: usize) -> Option<Vec<u8>> {
+    if array.is_null(index) {
+        return None;
+    }
+    match array.data_type() {
+        DataType::Binary => Some(array.as_binary::<i32>().value(index).to_vec()),
+        DataType::LargeBinary => Some(array.as_binary::<i64>().value(index).to_vec()),
+        DataType::BinaryView => Some(array.as_binary_view().value(index).to_vec()),
+        DataType::Struct(_) => image_bytes(array.as_struct().column_by_name("bytes")?, index),
+        _ => None,
+    }
+}
+
+// Function to get the column type of the output for a column of the input, or None for the types
+// that are not carried over
+fn column_type(data_type: &DataType) -> Option<ColumnType> {
+    match data_type {
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => Some(ColumnType::Utf8),
+        DataType::Boolean => Some(ColumnType::Bool),
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => Some(ColumnType::Float64),
+        data_type if data_type.is_integer() => Some(ColumnType::Int64),
+        _ => None,
+    }
+}
+
+// Function to get the string of a value of a column, or None for nulls
+fn value_string(array: &ArrayRef, index: usize) -> Option<String> {
+    (!array.is_null(index))
+        .then(|| array_value_to_string(array, index).ok())
+        .flatten()
+}
+
+// Function to build the selection of the given rows of a row group, sorted in increasing order
+fn row_selection(rows: &[usize]) -> RowSelection {
+    let mut selectors = Vec::new();
+    let mut next = 0;
+    for &row in rows {
+        if row > next {
+            selectors.push(RowSelector::skip(row - next));
+        }
+        selectors.push(RowSelector::select(1));
+        next = row + 1;
+    }
+    RowSelection::from(selectors)
+}
+
+impl ParquetSource {
+    // Function to list the rows of a Parquet file, reading every column but the images
+    fn list_file(
+        &self,
+        index: usize,
+        path: &Path,
+        scan_progress: &ProgressBar,
+        state: &mut ListState,
+    ) -> Result<ParquetFile> {
+        // Parse the footer and find the image, label and other columns
+        let file = File::open(path).map_err(Error::io("Failed to open", path))?;
+        let metadata = ArrowReaderMetadata::load(&file, Default::default())?;
+        let schema = metadata.schema().clone();
+        let image_column = find_image_column(&schema).ok_or_else(|| Error::MissingColumn {
+            path: path.to_path_buf(),
+            column: IMAGE_COLUMN.to_string(),
+        })?;
+        let fields: Vec<(usize, &str, Option<ColumnType>)> = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|(column, _)| *column != image_column)
+            .map(|(column, field)| {
+                let column_type = (field.name() != LABEL_COLUMN)
+                    .then(|| column_type(field.data_type()))
+                    .flatten();
+                (column, field.name().as_str(), column_type)
+            })
+            .filter(|(_, name, column_type)| *name == LABEL_COLUMN || column_type.is_some())
+            .collect();
+        for (_, name, column_type) in &fields {
+            if let Some(column_type) = column_type {
+                state.columns.insert(name.to_string(), *column_type);
+            }
+        }
+
+        // Read the label and other columns of every row group, numbering the rows across the file
+        let projection = ProjectionMask::roots(
+            metadata.parquet_schema(),
+            fields.iter().map(|(column, _, _)| *column),
+        );
+        let mut file_row = 0;
+        for row_group in 0..metadata.metadata().num_row_groups() {
+            let num_rows = metadata.metadata().row_group(row_group).num_rows() as usize;
+            let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(
+                file.try_clone()
+                    .map_err(Error::io("Failed to open", path))?,
+                metadata.clone(),
+            )
+            .with_projection(projection.clone())
+            .with_row_groups(vec![row_group])
+            .build()?;
+            let batches = reader.collect::<std::result::Result<Vec<_>, _>>()?;
+            let batch = concat_batches(
+                &batches.first().map_or(schema.clone(), |b| b.schema()),
+                &batches,
+            )?;
+
+            // Record where every row is, with its label and the values of the other columns
+            for row in 0..num_rows {
+                let mut label = String::new();
+                let mut values = BTreeMap::new();
+                for (_, name, column_type) in &fields {
+                    let Some(value) = batch
+                        .column_by_name(name)
+                        .and_then(|array| value_string(array, row))
+                    else {
+                        continue;
+                    };
+                    match column_type {
+                        None => label = value,
+                        Some(_) => {
+                            values.insert(name.to_string(), value);
+                        }
+                    }
+                }
+                let sample_path = path.join((file_row + row).to_string());
+                state.locations.insert(
+                    sample_path.clone(),
+                    RowLocation {
+                        file: index,
+                        row_group,
+                        row,
+                    },
+                );
+                state.metadata.insert(sample_path.clone(), values);
+                state.samples.push((sample_path, label));
+                scan_progress.inc(1);
+            }
+            file_row += num_rows;
+        }
+        Ok(ParquetFile {
+            path: path.to_path_buf(),
+            metadata,
+            image_column,
+        })
+    }
+
+    // Function to read the images of the given rows of a row group, in the order of the rows
+    fn read_images(file: &ParquetFile, row_group: usize, rows: &[usize]) -> io::Result<ArrayRef> {
+        let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(
+            File::open(&file.path)?,
+            file.metadata.clone(),
+        )
+        .with_projection(ProjectionMask::roots(
+            file.metadata.parquet_schema(),
+            [file.image_column],
+        ))
+        .with_row_groups(vec![row_group])
+        .with_row_selection(row_selection(rows))
+        .with_batch_size(rows.len())
+        .build()
+        .map_err(io::Error::other)?;
+        let batches = reader
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(io::Error::other)?;
+        let arrays: Vec<&dyn Array> = batches
+            .iter()
+            .map(|batch| batch.column(0).as_ref())
+            .collect();
+        arrow::compute::concat(&arrays).map_err(io::Error::other)
+    }
+}
+
+// Define what is collected while listing the files of a source
+#[derive(Default)]
+struct ListState {
+    samples: Vec<SampleRef>,
+    locations: HashMap<PathBuf, RowLocation>,
+    metadata: HashMap<PathBuf, BTreeMap<String, String>>,
+    columns: BTreeMap<String, ColumnType>,
+}
+
+impl DataSource for ParquetSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // List the rows of every file in order
+        let mut state = ListState::default();
+        let mut files = Vec::new();
+        for (index, path) in self.input_files()?.iter().enumerate() {
+            files.push(self.list_file(index, path, scan_progress, &mut state)?);
+        }
+
+        // Keep the files, row locations and values of every sample for loading
+        *self.files.lock().unwrap() = files;
+        *self.locations.lock().unwrap() = state.locations;
+        *self.metadata.lock().unwrap() = state.metadata;
+        *self.columns.lock().unwrap() = state.columns;
+        Ok(state.samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        self.load_many(std::slice::from_ref(sample))
+            .next()
+            .unwrap_or_else(|| Err(io::Error::other("Sample was not loaded")))
+    }
+
+    fn load_many<'a>(
+        &'a self,
+        samples: &'a [SampleRef],
+    ) -> Box<dyn Iterator<Item = io::Result<Record>> + 'a> {
+        // Group the samples by row group so each one is decoded once for all its samples
+        let locations = self.locations.lock().unwrap();
+        let mut groups: BTreeMap<(usize, usize), Vec<(usize, usize)>> = BTreeMap::new();
+        let mut results: Vec<io::Result<Record>> = Vec::with_capacity(samples.len());
+        for (position, (path, _)) in samples.iter().enumerate() {
+            results.push(Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Row was not listed",
+            )));
+            if let Some(location) = locations.get(path) {
+                groups
+                    .entry((location.file, location.row_group))
+                    .or_default()
+                    .push((location.row, position));
+            }
+        }
+        drop(locations);
+
+        // Read the images of every row group in increasing row order and place them at the
+        // position of their sample
+        let files = self.files.lock().unwrap();
+        let metadata = self.metadata.lock().unwrap();
+        for ((file, row_group), mut rows) in groups {
+            rows.sort_unstable();
+            let row_numbers: Vec<usize> = rows.iter().map(|(row, _)| *row).collect();
+            let images = Self::read_images(&files[file], row_group, &row_numbers);
+            for (index, (_, position)) in rows.into_iter().enumerate() {
+                let (path, label) = &samples[position];
+                results[position] = match &images {
+                    Ok(images) => image_bytes(images.as_ref(), index)
+                        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Image is null"))
+                        .map(|bytes| Record {
+                            bytes,
+                            label: label.clone(),
+                            metadata: metadata.get(path).cloned().unwrap_or_default(),
+                        }),
+                    Err(e) => Err(io::Error::new(e.kind(), e.to_string())),
+                };
+            }
+        }
+        Box::new(results.into_iter())
+    }
+
+    fn metadata_columns(&self) -> BTreeMap<String, ColumnType> {
+        self.columns.lock().unwrap().clone()
+    }
+}
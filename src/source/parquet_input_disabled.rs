@@ -0,0 +1,14 @@
+use super::DataSource;
+use crate::error::{Error, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// Function to report that discovering the splits of Parquet inputs is unavailable in this build
+pub(crate) fn discover_parquet_splits(_dataset_path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    Err(Error::FeatureDisabled("Reading Parquet inputs", "parquet"))
+}
+
+// Function to report that reading Parquet inputs is unavailable in this build
+pub(crate) fn open_parquet_source(_root: &Path) -> Result<Arc<dyn DataSource>> {
+    Err(Error::FeatureDisabled("Reading Parquet inputs", "parquet"))
+}
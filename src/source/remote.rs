@@ -0,0 +1,220 @@
+use super::{DataSource, Record, SampleRef};
+use crate::cli::InputFormat;
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use futures::future::join_all;
+use futures::TryStreamExt;
+use indicatif::ProgressBar;
+#[cfg(feature = "s3")]
+use object_store::aws::AmazonS3Builder;
+#[cfg(feature = "azure")]
+use object_store::azure::MicrosoftAzureBuilder;
+#[cfg(feature = "gcs")]
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, ObjectStoreExt};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::runtime::{self, Runtime};
+
+// Define how many objects are fetched at the same time by every chunk worker
+const CONCURRENT_FETCHES: usize = 16;
+
+// Define the number of threads driving the requests of a source
+const RUNTIME_THREADS: usize = 2;
+
+// Define a source reading images from an object storage prefix laid out like an image folder, with
+// one key prefix per label, fetching the objects without a local copy
+pub struct RemoteSource {
+    store: Arc<dyn ObjectStore>,
+    base_url: String,
+    prefix: ObjectPath,
+    filter: PathFilter,
+    runtime: Runtime,
+    sizes: Mutex<HashMap<PathBuf, u64>>,
+}
+
+// Function to split an object storage URL such as "s3://bucket/prefix" into the URL of its bucket,
+// or Azure container, and the prefix of the keys under it
+pub(crate) fn split_url(url: &str) -> (&str, &str) {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let bucket_len = rest.find('/').unwrap_or(rest.len());
+    let base_len = scheme.len() + "://".len() + bucket_len;
+    (&url[..base_len], url[base_len..].trim_matches('/'))
+}
+
+// Function to connect to the bucket of an object storage URL, taking the credentials and settings
+// from the environment: the AWS variables for S3, the service account or application default
+// credentials for Google Cloud Storage, and the AZURE variables for Azure Blob Storage
+pub(crate) fn open_store(base_url: &str) -> Result<Arc<dyn ObjectStore>> {
+    let (scheme, _) = base_url.split_once("://").unwrap_or_default();
+    Ok(match scheme {
+        #[cfg(feature = "s3")]
+        "s3" => Arc::new(AmazonS3Builder::from_env().with_url(base_url).build()?),
+        #[cfg(feature = "gcs")]
+        "gs" => Arc::new(
+            GoogleCloudStorageBuilder::from_env()
+                .with_url(base_url)
+                .build()?,
+        ),
+        #[cfg(feature = "azure")]
+        "az" => Arc::new(
+            MicrosoftAzureBuilder::from_env()
+                .with_url(base_url)
+                .build()?,
+        ),
+        _ => return Err(super::remote_disabled(base_url)),
+    })
+}
+
+// Function to create the runtime driving the requests to the object storage
+pub(crate) fn create_runtime(url: &str) -> Result<Runtime> {
+    runtime::Builder::new_multi_thread()
+        .worker_threads(RUNTIME_THREADS)
+        .enable_all()
+        .build()
+        .map_err(Error::io("Failed to start the runtime for", Path::new(url)))
+}
+
+// Function to get the name of the last segment of a key, if it has one
+fn last_segment(key: &ObjectPath) -> Option<String> {
+    key.parts()
+        .next_back()
+        .map(|part| part.as_ref().to_string())
+}
+
+// Function to discover the splits of an object storage dataset, one per key prefix directly under
+// the given URL, named after the prefix
+pub(crate) fn discover_remote_splits(url: &str) -> Result<Vec<(String, PathBuf)>> {
+    // List the prefixes one level under the URL
+    let (base_url, prefix) = split_url(url);
+    let store = open_store(base_url)?;
+    let runtime = create_runtime(url)?;
+    let prefix = ObjectPath::from(prefix);
+    let listing = runtime.block_on(store.list_with_delimiter(Some(&prefix)))?;
+
+    // Address every split by the URL of its prefix
+    let mut splits: Vec<(String, PathBuf)> = listing
+        .common_prefixes
+        .iter()
+        .filter_map(|split| {
+            let name = last_segment(split)?;
+            Some((name, PathBuf::from(format!("{}/{}", base_url, split))))
+        })
+        .collect();
+    splits.sort();
+    Ok(splits)
+}
+
+// Function to open the source reading a split stored under an object storage URL, which must be
+// laid out as an image folder
+pub(crate) fn open_remote_source(
+    format: InputFormat,
+    url: &str,
+    filter: PathFilter,
+) -> Result<Arc<dyn DataSource>> {
+    if format != InputFormat::Folder {
+        return Err(Error::Unsupported(
+            "Reading archives or manifests from object storage".to_string(),
+        ));
+    }
+    let (base_url, prefix) = split_url(url);
+    Ok(Arc::new(RemoteSource {
+        store: open_store(base_url)?,
+        base_url: base_url.to_string(),
+        prefix: ObjectPath::from(prefix),
+        filter,
+        runtime: create_runtime(url)?,
+        sizes: Mutex::new(HashMap::new()),
+    }))
+}
+
+impl RemoteSource {
+    // Function to get the key of the object a sample was listed from
+    fn object_key(&self, path: &Path) -> io::Result<ObjectPath> {
+        let key = path
+            .to_str()
+            .and_then(|path| path.strip_prefix(&self.base_url))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not an object URL"))?;
+        ObjectPath::parse(key.trim_start_matches('/'))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+
+    // Function to fetch the bytes of a sample from the object storage
+    async fn fetch(&self, sample: &SampleRef) -> io::Result<Record> {
+        let (path, label) = sample;
+        let key = self.object_key(path)?;
+        let bytes = self.store.get(&key).await?.bytes().await?;
+        Ok(Record {
+            bytes: bytes.to_vec(),
+            label: label.clone(),
+            metadata: BTreeMap::new(),
+        })
+    }
+}
+
+impl DataSource for RemoteSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // List every object under the prefix, recursively
+        let objects: Vec<_> = self
+            .runtime
+            .block_on(self.store.list(Some(&self.prefix)).try_collect())?;
+
+        // Keep the objects accepted by the filter, labelled by the last segment of their prefix
+        // like the files of an image folder
+        let mut sizes = HashMap::new();
+        let mut samples = Vec::new();
+        for object in objects {
+            let relative: PathBuf = object
+                .location
+                .prefix_match(&self.prefix)
+                .into_iter()
+                .flatten()
+                .map(|part| part.as_ref().to_string())
+                .collect();
+            if !self.filter.matches(&relative) {
+                continue;
+            }
+            let Some(label) = object.location.parts().rev().nth(1) else {
+                continue;
+            };
+            let path = PathBuf::from(format!("{}/{}", self.base_url, object.location));
+            sizes.insert(path.clone(), object.size);
+            samples.push((path, label.as_ref().to_string()));
+            scan_progress.inc(1);
+        }
+
+        // Sort the samples by key so they are listed in a stable order, and keep the object sizes
+        // for the size estimates
+        samples.sort();
+        *self.sizes.lock().unwrap() = sizes;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        self.runtime.block_on(self.fetch(sample))
+    }
+
+    fn load_many<'a>(
+        &'a self,
+        samples: &'a [SampleRef],
+    ) -> Box<dyn Iterator<Item = io::Result<Record>> + 'a> {
+        // Fetch the samples in windows of concurrent requests, yielding them in order
+        Box::new(samples.chunks(CONCURRENT_FETCHES).flat_map(|window| {
+            self.runtime
+                .block_on(join_all(window.iter().map(|sample| self.fetch(sample))))
+        }))
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Use the object size found while listing
+        self.sizes
+            .lock()
+            .unwrap()
+            .get(&sample.0)
+            .copied()
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,20 @@
+use super::DataSource;
+use crate::cli::InputFormat;
+use crate::error::Result;
+use crate::filter::PathFilter;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// Function to report that discovering splits in object storage is unavailable in this build
+pub(crate) fn discover_remote_splits(url: &str) -> Result<Vec<(String, PathBuf)>> {
+    Err(super::remote_disabled(url))
+}
+
+// Function to report that reading from object storage is unavailable in this build
+pub(crate) fn open_remote_source(
+    _format: InputFormat,
+    url: &str,
+    _filter: PathFilter,
+) -> Result<Arc<dyn DataSource>> {
+    Err(super::remote_disabled(url))
+}
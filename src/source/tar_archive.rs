@@ -0,0 +1,162 @@
+use super::{find_input_files, DataSource, Record, SampleRef};
+use crate::cli::InputFormat;
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use indicatif::ProgressBar;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tar::{Archive, EntryType};
+
+// Define where the data of an archive member starts and how long it is
+#[derive(Clone, Copy)]
+pub(super) struct MemberLocation {
+    pub archive: usize,
+    pub offset: u64,
+    pub size: u64,
+}
+
+// Define a source reading images straight from tar archives without extracting them, either a
+// single archive or every archive under a folder, such as one archive per class
+pub struct TarSource {
+    root: PathBuf,
+    filter: PathFilter,
+    archives: Mutex<Vec<PathBuf>>,
+    members: Mutex<HashMap<PathBuf, MemberLocation>>,
+}
+
+impl TarSource {
+    // Function to create a source over a tar archive or a folder of tar archives, keeping the
+    // members accepted by the filter
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        TarSource {
+            root: root.into(),
+            filter,
+            archives: Mutex::new(Vec::new()),
+            members: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Function to list the file members of an archive accepted by the filter, recording where the
+    // data of each one starts
+    fn list_archive(
+        &self,
+        index: usize,
+        archive_path: &Path,
+        scan_progress: &ProgressBar,
+    ) -> io::Result<Vec<(SampleRef, MemberLocation)>> {
+        // Label members stored at the top of the archive after the archive itself
+        let archive_label = archive_path
+            .file_stem()
+            .map_or(String::new(), |stem| stem.to_string_lossy().to_string());
+
+        // Walk the headers of the archive, seeking over the member data
+        let mut archive = Archive::new(BufReader::new(File::open(archive_path)?));
+        let mut members = Vec::new();
+        for entry in archive.entries_with_seek()? {
+            // Skip directories, links and other entries holding no file data
+            let entry = entry?;
+            if entry.header().entry_type() != EntryType::Regular {
+                continue;
+            }
+
+            // Keep the members with an accepted extension that pass the include and exclude patterns
+            let member_path = entry.path()?.into_owned();
+            if !self.filter.matches(&member_path) {
+                continue;
+            }
+
+            // Take the label from the folder holding the member, like in an image folder
+            let label = member_path
+                .parent()
+                .and_then(|parent| parent.file_name())
+                .map_or(archive_label.clone(), |name| {
+                    name.to_string_lossy().to_string()
+                });
+
+            // Address the member by the archive path followed by its path inside the archive
+            let location = MemberLocation {
+                archive: index,
+                offset: entry.raw_file_position(),
+                size: entry.size(),
+            };
+            members.push(((archive_path.join(&member_path), label), location));
+            scan_progress.inc(1);
+        }
+        Ok(members)
+    }
+
+    // Function to get the location of a listed member
+    fn location(&self, path: &Path) -> io::Result<MemberLocation> {
+        self.members
+            .lock()
+            .unwrap()
+            .get(path)
+            .copied()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Member was not listed"))
+    }
+}
+
+// Function to read the data of a member directly from its archive
+pub(super) fn read_member(archive_path: &Path, location: MemberLocation) -> io::Result<Vec<u8>> {
+    // Seek to the member data and read it
+    let mut file = File::open(archive_path)?;
+    file.seek(SeekFrom::Start(location.offset))?;
+    let mut bytes = Vec::with_capacity(location.size as usize);
+    file.take(location.size).read_to_end(&mut bytes)?;
+
+    // Fail on archives truncated since they were listed
+    if bytes.len() as u64 != location.size {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Archive member is truncated",
+        ));
+    }
+    Ok(bytes)
+}
+
+impl DataSource for TarSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // Find the archives and remember them so members can be read back by index
+        let archives = find_input_files(InputFormat::Tar, &self.root);
+        let mut members = HashMap::new();
+        let mut samples = Vec::new();
+
+        // List the members of every archive in order
+        for (index, archive_path) in archives.iter().enumerate() {
+            let listed = self
+                .list_archive(index, archive_path, scan_progress)
+                .map_err(Error::io("Failed to read", archive_path))?;
+            for (sample, location) in listed {
+                members.insert(sample.0.clone(), location);
+                samples.push(sample);
+            }
+        }
+
+        // Keep the index of the archives and members for loading
+        *self.archives.lock().unwrap() = archives;
+        *self.members.lock().unwrap() = members;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Locate the member data inside its archive
+        let (path, label) = sample;
+        let location = self.location(path)?;
+        let archive_path = self.archives.lock().unwrap()[location.archive].clone();
+
+        // Return the member with the label found while listing
+        Ok(Record {
+            bytes: read_member(&archive_path, location)?,
+            label: label.clone(),
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Use the member size recorded in its header
+        self.location(&sample.0).map_or(0, |location| location.size)
+    }
+}
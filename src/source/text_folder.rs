@@ -0,0 +1,64 @@
+use super::{collect_image_paths, read_image_as_bytes, DataSource, Record, SampleRef};
+use crate::error::Result;
+use crate::filter::PathFilter;
+use crate::schema::SampleKind;
+use indicatif::ProgressBar;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Define the extension of the documents of a text folder
+const TEXT_EXTENSIONS: &[&str] = &["txt"];
+
+// Define the byte order mark some editors write at the start of UTF-8 files
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+// Define a source reading text documents from a folder with one subfolder per label, such as the
+// IMDB or 20 Newsgroups datasets, written to a UTF-8 text column instead of an image column
+pub struct TextFolderSource {
+    root: PathBuf,
+    filter: PathFilter,
+}
+
+impl TextFolderSource {
+    // Function to create a source over a split folder, keeping the documents passing the include
+    // and exclude patterns
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        TextFolderSource {
+            root: root.into(),
+            filter: filter.with_extensions(TEXT_EXTENSIONS),
+        }
+    }
+}
+
+impl DataSource for TextFolderSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        Ok(collect_image_paths(&self.root, &self.filter, scan_progress))
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read the document without its byte order mark, refusing files that are not UTF-8
+        let (path, label) = sample;
+        let mut bytes = read_image_as_bytes(path)?;
+        if bytes.starts_with(UTF8_BOM) {
+            bytes.drain(..UTF8_BOM.len());
+        }
+        let text =
+            String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Record {
+            bytes: text.into_bytes(),
+            label: label.clone(),
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Read the document size from the file metadata without opening the file
+        fs::metadata(&sample.0).map(|m| m.len()).unwrap_or(0)
+    }
+
+    fn sample_kind(&self) -> SampleKind {
+        SampleKind::Text
+    }
+}
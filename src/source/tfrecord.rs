@@ -0,0 +1,247 @@
+use super::{
+    find_input_files, is_input_file, label_name, strip_shard_numbering, DataSource, Record,
+    SampleRef, SourceSettings,
+};
+use crate::cli::InputFormat;
+use crate::error::{Error, Result};
+use crate::tfrecord::{self, Example, Kind};
+use indicatif::ProgressBar;
+use prost::Message;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// Define the features holding the encoded image, in order of preference, as named by the
+// TensorFlow object detection and slim datasets and by TensorFlow Datasets
+const IMAGE_FEATURES: &[&str] = &["image/encoded", "image", "image_raw"];
+
+// Define the features holding the label, in order of preference, either an integer class index or
+// a class name
+const LABEL_FEATURES: &[&str] = &["image/class/label", "label", "image/class/text"];
+
+// Define where the record of a sample is stored and the size of its image
+struct RecordLocation {
+    file: PathBuf,
+    offset: u64,
+    size: u64,
+}
+
+// Define a source reading the tf.Example records of TFRecord files, such as the datasets written
+// for TensorFlow, with an encoded image feature and a label feature
+pub struct TfrecordSource {
+    root: PathBuf,
+    label_names: Vec<String>,
+    locations: Mutex<HashMap<PathBuf, RecordLocation>>,
+}
+
+// Function to open the source reading the TFRecord files of a split
+pub(crate) fn open_tfrecord_source(
+    root: &Path,
+    settings: &SourceSettings,
+) -> Result<Arc<dyn DataSource>> {
+    Ok(Arc::new(TfrecordSource {
+        root: root.to_path_buf(),
+        label_names: settings.label_names.clone(),
+        locations: Mutex::new(HashMap::new()),
+    }))
+}
+
+// Function to get the split name of a TFRecord file, the part of its name before the extension
+// without the shard numbering, as in "train-00000-of-00004.tfrecord" or the TensorFlow Datasets
+// shard "mnist-train.tfrecord-00000-of-00001"
+fn tfrecord_split_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let stem = match name.split_once(".tfrecord") {
+        Some((stem, _)) => stem,
+        None => path.file_stem()?.to_str()?,
+    };
+    Some(strip_shard_numbering(stem))
+}
+
+// Function to discover the splits of a TFRecord dataset: every folder holding TFRecord files, and
+// the TFRecord files at the root grouped by split name
+pub(crate) fn discover_tfrecord_splits(dataset_path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut splits: Vec<(String, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(dataset_path).map_err(Error::io("Failed to read", dataset_path))? {
+        let path = entry
+            .map_err(Error::io("Failed to read", dataset_path))?
+            .path();
+        let split = if path.is_dir() {
+            if find_input_files(InputFormat::Tfrecord, &path).is_empty() {
+                continue;
+            }
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| (name.to_string(), path.clone()))
+        } else if is_input_file(InputFormat::Tfrecord, &path) {
+            tfrecord_split_name(&path).map(|name| (name.clone(), dataset_path.join(name)))
+        } else {
+            None
+        };
+        if let Some(split) = split.filter(|split| !splits.contains(split)) {
+            splits.push(split);
+        }
+    }
+    splits.sort();
+    Ok(splits)
+}
+
+// Function to get the first value of the first of the given features an Example has
+fn find_feature<'a>(example: &'a Example, names: &[&str]) -> Option<&'a Kind> {
+    let features = &example.features.as_ref()?.feature;
+    names
+        .iter()
+        .find_map(|name| features.get(*name)?.kind.as_ref())
+}
+
+// Function to get the encoded image of an Example
+fn example_image(example: &Example) -> Option<&[u8]> {
+    match find_feature(example, IMAGE_FEATURES)? {
+        Kind::Bytes(list) => list.value.first().map(Vec::as_slice),
+        _ => None,
+    }
+}
+
+impl TfrecordSource {
+    // Function to list the TFRecord files of the source in a stable order
+    fn input_files(&self) -> Result<Vec<PathBuf>> {
+        // Use the root itself, or the files under it, when it exists
+        if self.root.exists() {
+            return Ok(find_input_files(InputFormat::Tfrecord, &self.root));
+        }
+
+        // Otherwise take the shards next to the root whose split name is the name of the root
+        let dir = self.root.parent().unwrap_or(Path::new(""));
+        let name = self.root.file_name().and_then(|name| name.to_str());
+        let mut shards: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(Error::io("Failed to read", dir))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_input_file(InputFormat::Tfrecord, path))
+            .filter(|path| name.is_some() && tfrecord_split_name(path).as_deref() == name)
+            .collect();
+        shards.sort();
+        Ok(shards)
+    }
+
+    // Function to get the label of an Example, naming integer labels by the label names
+    fn example_label(&self, example: &Example) -> String {
+        match find_feature(example, LABEL_FEATURES) {
+            Some(Kind::Int64(list)) => list
+                .value
+                .first()
+                .map(|&label| label_name(&self.label_names, label))
+                .unwrap_or_default(),
+            Some(Kind::Bytes(list)) => list
+                .value
+                .first()
+                .map(|label| String::from_utf8_lossy(label).to_string())
+                .unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+
+    // Function to read the Example stored at an offset of an open TFRecord file
+    fn read_example(reader: &mut BufReader<File>, offset: u64) -> io::Result<Example> {
+        reader.seek(SeekFrom::Start(offset))?;
+        let data = tfrecord::read_record(reader)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Record is missing"))?;
+        Example::decode(data.as_slice()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl DataSource for TfrecordSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        let mut locations = HashMap::new();
+        let mut samples = Vec::new();
+        for file_path in self.input_files()? {
+            // Read every record of the file in order, keeping its offset to load it again later
+            let file = File::open(&file_path).map_err(Error::io("Failed to open", &file_path))?;
+            let mut reader = BufReader::new(file);
+            let mut offset = 0;
+            let mut index = 0u64;
+            while let Some(data) = tfrecord::read_record(&mut reader)
+                .map_err(Error::io("Failed to read", &file_path))?
+            {
+                // Skip the records that are not Examples with an image
+                let (record_offset, record_index) = (offset, index);
+                offset += tfrecord::framed_len(data.len());
+                index += 1;
+                let Ok(example) = Example::decode(data.as_slice()) else {
+                    continue;
+                };
+                let Some(image) = example_image(&example) else {
+                    continue;
+                };
+
+                // Name the sample after its file and position
+                let path = file_path.join(record_index.to_string());
+                locations.insert(
+                    path.clone(),
+                    RecordLocation {
+                        file: file_path.clone(),
+                        offset: record_offset,
+                        size: image.len() as u64,
+                    },
+                );
+                samples.push((path, self.example_label(&example)));
+                scan_progress.inc(1);
+            }
+        }
+
+        // Keep the location of every sample for loading
+        *self.locations.lock().unwrap() = locations;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        self.load_many(std::slice::from_ref(sample))
+            .next()
+            .unwrap_or_else(|| Err(io::Error::other("Sample was not loaded")))
+    }
+
+    fn load_many<'a>(
+        &'a self,
+        samples: &'a [SampleRef],
+    ) -> Box<dyn Iterator<Item = io::Result<Record>> + 'a> {
+        // Read the records in order, reopening a file only when the next record is in another one
+        let locations = self.locations.lock().unwrap();
+        let mut open_file: Option<(&Path, BufReader<File>)> = None;
+        let records: Vec<io::Result<Record>> = samples
+            .iter()
+            .map(|(path, label)| {
+                let location = locations.get(path).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, "Record was not listed")
+                })?;
+                let reader = match &mut open_file {
+                    Some((file, reader)) if *file == location.file => reader,
+                    _ => {
+                        let reader = BufReader::new(File::open(&location.file)?);
+                        &mut open_file.insert((&location.file, reader)).1
+                    }
+                };
+                let example = Self::read_example(reader, location.offset)?;
+                let image = example_image(&example).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Record has no image")
+                })?;
+                Ok(Record {
+                    bytes: image.to_vec(),
+                    label: label.clone(),
+                    metadata: BTreeMap::new(),
+                })
+            })
+            .collect();
+        Box::new(records.into_iter())
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Use the image size found while listing
+        self.locations
+            .lock()
+            .unwrap()
+            .get(&sample.0)
+            .map_or(0, |location| location.size)
+    }
+}
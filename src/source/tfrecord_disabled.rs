@@ -0,0 +1,17 @@
+use super::{DataSource, SourceSettings};
+use crate::error::{Error, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// Function to report that discovering the splits of TFRecord datasets is unavailable in this build
+pub(crate) fn discover_tfrecord_splits(_dataset_path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    Err(Error::FeatureDisabled("Reading TFRecord files", "tfrecord"))
+}
+
+// Function to report that reading TFRecord files is unavailable in this build
+pub(crate) fn open_tfrecord_source(
+    _root: &Path,
+    _settings: &SourceSettings,
+) -> Result<Arc<dyn DataSource>> {
+    Err(Error::FeatureDisabled("Reading TFRecord files", "tfrecord"))
+}
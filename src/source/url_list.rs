@@ -0,0 +1,185 @@
+use super::{find_input_files, DataSource, Record, SampleRef, SourceSettings};
+use crate::cli::InputFormat;
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use csv::ReaderBuilder;
+use indicatif::ProgressBar;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use ureq::Agent;
+
+// Define the names of the columns of CSV and TSV URL lists holding the image URL and the label
+const URL_COLUMN: &str = "url";
+const LABEL_COLUMN: &str = "label";
+
+// Define the largest image accepted from a URL, guarding against endless responses
+const MAX_IMAGE_BYTES: u64 = 1 << 30;
+
+// Define a source downloading the images listed by URL in text, CSV or TSV files, such as the
+// image-text pairs of web-scale datasets, with a bounded number of downloads in flight
+pub struct UrlListSource {
+    root: PathBuf,
+    filter: PathFilter,
+    agent: Agent,
+    concurrency: usize,
+    free_slots: Mutex<usize>,
+    slot_released: Condvar,
+}
+
+// Function to open the source downloading the images listed by a URL list or a folder of them
+pub(crate) fn open_url_list_source(
+    root: &Path,
+    settings: &SourceSettings,
+) -> Result<Arc<dyn DataSource>> {
+    // Give up on every download taking longer than the timeout, and report error statuses
+    let config = Agent::config_builder()
+        .timeout_global(Some(settings.download_timeout))
+        .build();
+    Ok(Arc::new(UrlListSource {
+        root: root.to_path_buf(),
+        filter: settings.filter.clone(),
+        agent: Agent::new_with_config(config),
+        concurrency: settings.download_concurrency,
+        free_slots: Mutex::new(settings.download_concurrency),
+        slot_released: Condvar::new(),
+    }))
+}
+
+impl UrlListSource {
+    // Function to read the URLs and labels of a text file, one URL per line optionally followed by
+    // whitespace and a label, skipping empty lines and # comments
+    fn read_text_list(list_path: &Path) -> Result<Vec<SampleRef>> {
+        let content =
+            fs::read_to_string(list_path).map_err(Error::io("Failed to read", list_path))?;
+        Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| match line.split_once(char::is_whitespace) {
+                Some((url, label)) => (PathBuf::from(url), label.trim().to_string()),
+                None => (PathBuf::from(line), String::new()),
+            })
+            .collect())
+    }
+
+    // Function to read the URLs and labels of a CSV or TSV file with a url column and an optional
+    // label column
+    fn read_table_list(list_path: &Path) -> Result<Vec<SampleRef>> {
+        // Read tab-separated values from .tsv lists and comma-separated values otherwise
+        let delimiter = match list_path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("tsv") => b'\t',
+            _ => b',',
+        };
+        let mut reader = ReaderBuilder::new()
+            .delimiter(delimiter)
+            .from_path(list_path)
+            .map_err(Error::csv(list_path))?;
+
+        // Find the URL column, and the label column when there is one, in the header
+        let headers = reader.headers().map_err(Error::csv(list_path))?.clone();
+        let column = |name: &str| headers.iter().position(|header| header.trim() == name);
+        let url_column = column(URL_COLUMN).ok_or_else(|| Error::MissingColumn {
+            path: list_path.to_path_buf(),
+            column: URL_COLUMN.to_string(),
+        })?;
+        let label_column = column(LABEL_COLUMN);
+
+        // Collect the rows in the order of the file
+        let mut samples = Vec::new();
+        for row in reader.records() {
+            let row = row.map_err(Error::csv(list_path))?;
+            let url = row.get(url_column).unwrap_or_default().trim();
+            let label = label_column.and_then(|column| row.get(column));
+            samples.push((PathBuf::from(url), label.unwrap_or_default().to_string()));
+        }
+        Ok(samples)
+    }
+
+    // Function to wait until fewer downloads than the concurrency are in flight and take a slot
+    fn acquire_slot(&self) {
+        let mut free_slots = self
+            .slot_released
+            .wait_while(self.free_slots.lock().unwrap(), |free| *free == 0)
+            .unwrap();
+        *free_slots -= 1;
+    }
+
+    // Function to give back the slot of a finished download
+    fn release_slot(&self) {
+        *self.free_slots.lock().unwrap() += 1;
+        self.slot_released.notify_one();
+    }
+
+    // Function to download the body of a URL
+    fn download(&self, url: &str) -> io::Result<Vec<u8>> {
+        let mut response = self.agent.get(url).call().map_err(ureq::Error::into_io)?;
+        response
+            .body_mut()
+            .with_config()
+            .limit(MAX_IMAGE_BYTES)
+            .read_to_vec()
+            .map_err(ureq::Error::into_io)
+    }
+}
+
+impl DataSource for UrlListSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // Read every list in order, as text or as a table according to its extension
+        let mut samples = Vec::new();
+        for list_path in find_input_files(InputFormat::Urls, &self.root) {
+            let listed = match list_path.extension() {
+                Some(ext) if ext.eq_ignore_ascii_case("txt") => Self::read_text_list(&list_path)?,
+                _ => Self::read_table_list(&list_path)?,
+            };
+
+            // Keep the URLs passing the include and exclude patterns, whatever their extension
+            samples.extend(
+                listed
+                    .into_iter()
+                    .filter(|(url, _)| !url.as_os_str().is_empty())
+                    .filter(|(url, _)| self.filter.matches_patterns(url))
+                    .inspect(|_| scan_progress.inc(1)),
+            );
+        }
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Download the image once a slot is free, keeping the label given by the list
+        let (url, label) = sample;
+        let url = url
+            .to_str()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "URL is not UTF-8"))?;
+        self.acquire_slot();
+        let bytes = self.download(url);
+        self.release_slot();
+        Ok(Record {
+            bytes: bytes?,
+            label: label.clone(),
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    fn load_many<'a>(
+        &'a self,
+        samples: &'a [SampleRef],
+    ) -> Box<dyn Iterator<Item = io::Result<Record>> + 'a> {
+        // Download the samples in windows of concurrent requests, yielding them in order
+        Box::new(samples.chunks(self.concurrency).flat_map(|window| {
+            thread::scope(|scope| {
+                let downloads: Vec<_> = window
+                    .iter()
+                    .map(|sample| scope.spawn(|| self.load(sample)))
+                    .collect();
+                downloads
+                    .into_iter()
+                    .map(|download| download.join().unwrap())
+                    .collect::<Vec<_>>()
+            })
+        }))
+    }
+}
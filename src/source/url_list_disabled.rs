@@ -0,0 +1,12 @@
+use super::{DataSource, SourceSettings};
+use crate::error::{Error, Result};
+use std::path::Path;
+use std::sync::Arc;
+
+// Function to report that downloading URL lists is unavailable in this build
+pub(crate) fn open_url_list_source(
+    _root: &Path,
+    _settings: &SourceSettings,
+) -> Result<Arc<dyn DataSource>> {
+    Err(Error::FeatureDisabled("Downloading URL lists", "http"))
+}
@@ -0,0 +1,125 @@
+use super::{collect_image_paths, read_image_as_bytes, DataSource, Record, SampleRef};
+use crate::error::Result;
+use crate::filter::PathFilter;
+use crate::schema::{pack_frames, SampleKind};
+use indicatif::ProgressBar;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Define the extensions of the video files of a video folder
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "avi", "mkv", "mov", "webm"];
+
+// Define a source reading videos from a folder with one subfolder per label, such as the UCF101 or
+// Kinetics action recognition datasets, keeping the encoded clips or extracting a fixed number of
+// frames from each with ffmpeg
+pub struct VideoFolderSource {
+    root: PathBuf,
+    filter: PathFilter,
+    frames: Option<usize>,
+}
+
+impl VideoFolderSource {
+    // Function to create a source over a split folder, keeping the videos passing the include and
+    // exclude patterns, and extracting the given number of frames from each when there is one
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter, frames: Option<usize>) -> Self {
+        VideoFolderSource {
+            root: root.into(),
+            filter: filter.with_extensions(VIDEO_EXTENSIONS),
+            frames,
+        }
+    }
+}
+
+// Function to run an ffmpeg tool and get what it wrote to its standard output, failing with its
+// error output when it does not succeed
+fn run_tool(command: &mut Command, tool: &str) -> io::Result<Vec<u8>> {
+    let output = command
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to run {}: {}", tool, e)))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{} failed: {}",
+            tool,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+// Function to read the duration of a video in seconds with ffprobe
+fn video_duration(path: &Path) -> io::Result<f64> {
+    let stdout = run_tool(
+        Command::new("ffprobe")
+            .args(["-v", "error", "-show_entries", "format=duration"])
+            .args(["-of", "default=noprint_wrappers=1:nokey=1"])
+            .arg(path),
+        "ffprobe",
+    )?;
+    String::from_utf8_lossy(&stdout)
+        .trim()
+        .parse()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Video has no duration"))
+}
+
+// Function to extract the frame of a video shown at the given time, in seconds, as a JPEG image
+fn extract_frame(path: &Path, time: f64) -> io::Result<Vec<u8>> {
+    let frame = run_tool(
+        Command::new("ffmpeg")
+            .args(["-v", "error", "-ss", &format!("{:.3}", time), "-i"])
+            .arg(path)
+            .args(["-frames:v", "1", "-f", "image2pipe", "-c:v", "mjpeg", "-"]),
+        "ffmpeg",
+    )?;
+    if frame.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("No frame at {:.3}s", time),
+        ));
+    }
+    Ok(frame)
+}
+
+// Function to extract evenly spaced frames of a video, taken at the middle of equal intervals so
+// the first and last frames are not black fades
+fn extract_frames(path: &Path, count: usize) -> io::Result<Vec<Vec<u8>>> {
+    let duration = video_duration(path)?;
+    (0..count)
+        .map(|i| extract_frame(path, duration * (i as f64 + 0.5) / count as f64))
+        .collect()
+}
+
+impl DataSource for VideoFolderSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        Ok(collect_image_paths(&self.root, &self.filter, scan_progress))
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read the encoded clip, or the requested number of frames packed together
+        let (path, label) = sample;
+        let bytes = match self.frames {
+            Some(count) => pack_frames(&extract_frames(path, count)?),
+            None => read_image_as_bytes(path)?,
+        };
+        Ok(Record {
+            bytes,
+            label: label.clone(),
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Read the video size from the file metadata without opening the file, which overestimates
+        // the size of extracted frames
+        fs::metadata(&sample.0).map(|m| m.len()).unwrap_or(0)
+    }
+
+    fn sample_kind(&self) -> SampleKind {
+        match self.frames {
+            Some(_) => SampleKind::VideoFrames,
+            None => SampleKind::Video,
+        }
+    }
+}
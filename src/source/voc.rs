@@ -0,0 +1,218 @@
+use super::{read_image_as_bytes, DataSource, Record, SampleRef};
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use crate::schema::ColumnType;
+use indicatif::ProgressBar;
+use roxmltree::{Document, Node};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Define the folders of a VOC dataset holding the images, the annotations and the image sets
+const IMAGES_DIR: &str = "JPEGImages";
+const ANNOTATIONS_DIR: &str = "Annotations";
+const IMAGE_SETS_DIR: &str = "ImageSets/Main";
+
+// Define a source reading a Pascal VOC detection dataset, whose images in JPEGImages are described
+// by an XML file of the same name in Annotations, storing the objects of every image in list columns
+pub struct VocSource {
+    root: PathBuf,
+    filter: PathFilter,
+}
+
+impl VocSource {
+    // Function to create a source over a VOC dataset folder, or over one of its image set files
+    // listing the ids of the images of a split, keeping the images accepted by the filter
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        VocSource {
+            root: root.into(),
+            filter,
+        }
+    }
+
+    // Function to list the ids of the images of the source, from the image set file when the source
+    // is one, or else from the annotation files
+    fn image_ids(&self) -> Result<(PathBuf, Vec<String>)> {
+        // Read the ids listed by an image set file, one per line, the dataset being three levels up
+        if self.root.is_file() {
+            let content =
+                fs::read_to_string(&self.root).map_err(Error::io("Failed to read", &self.root))?;
+            let dataset_dir = self
+                .root
+                .ancestors()
+                .nth(3)
+                .unwrap_or(Path::new(""))
+                .to_path_buf();
+            let ids = content
+                .lines()
+                .filter_map(|line| line.split_whitespace().next())
+                .map(str::to_string)
+                .collect();
+            return Ok((dataset_dir, ids));
+        }
+
+        // Otherwise take the name of every annotation file in a stable order
+        let annotations_dir = self.root.join(ANNOTATIONS_DIR);
+        let mut ids: Vec<String> = fs::read_dir(&annotations_dir)
+            .map_err(Error::io("Failed to read", &annotations_dir))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "xml"))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .collect();
+        ids.sort();
+        Ok((self.root.clone(), ids))
+    }
+}
+
+// Function to get the text of the first child element of a node with the given name
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| child.has_tag_name(name))?
+        .text()
+        .map(str::trim)
+}
+
+// Function to read the size and the objects of an annotation file into the fields of its image,
+// with the boxes converted from corners to [x, y, width, height]
+fn read_annotation(annotation_path: &Path) -> io::Result<BTreeMap<String, String>> {
+    // Parse the XML document
+    let content = fs::read_to_string(annotation_path)?;
+    let document =
+        Document::parse(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let annotation = document.root_element();
+
+    // Collect the name, difficulty and box of every object
+    let mut categories = Vec::new();
+    let mut difficult = Vec::new();
+    let mut bboxes = Vec::new();
+    for object in annotation
+        .children()
+        .filter(|child| child.has_tag_name("object"))
+    {
+        let bndbox = object
+            .children()
+            .find(|child| child.has_tag_name("bndbox"))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Object has no bndbox"))?;
+        let corner = |name: &str| -> io::Result<f64> {
+            child_text(bndbox, name)
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid {} of bndbox", name),
+                    )
+                })
+        };
+        let (xmin, ymin) = (corner("xmin")?, corner("ymin")?);
+        let (xmax, ymax) = (corner("xmax")?, corner("ymax")?);
+        bboxes.push([xmin, ymin, xmax - xmin, ymax - ymin]);
+        categories.push(child_text(object, "name").unwrap_or_default());
+        difficult.push(child_text(object, "difficult").map_or(0, |value| (value == "1") as i64));
+    }
+
+    // Store the objects as JSON arrays, with the size of the image when the file gives it
+    let mut fields = BTreeMap::from([
+        ("bboxes".to_string(), serde_json::json!(bboxes).to_string()),
+        (
+            "categories".to_string(),
+            serde_json::json!(categories).to_string(),
+        ),
+        (
+            "difficult".to_string(),
+            serde_json::json!(difficult).to_string(),
+        ),
+    ]);
+    if let Some(size) = annotation
+        .children()
+        .find(|child| child.has_tag_name("size"))
+    {
+        for dimension in ["width", "height"] {
+            if let Some(value) = child_text(size, dimension) {
+                fields.insert(dimension.to_string(), value.to_string());
+            }
+        }
+    }
+    Ok(fields)
+}
+
+// Function to discover the splits of a VOC dataset, one per image set file such as "train.txt" in
+// ImageSets/Main, skipping the per-class files such as "cat_train.txt"; a dataset without image
+// sets is converted as a single "train" split
+pub(crate) fn discover_voc_splits(dataset_path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    // Fall back to the whole dataset when it has no image sets
+    let image_sets_dir = dataset_path.join(IMAGE_SETS_DIR);
+    if !image_sets_dir.is_dir() {
+        return Ok(vec![("train".to_string(), dataset_path.to_path_buf())]);
+    }
+
+    // Name every split after its image set file
+    let mut splits: Vec<(String, PathBuf)> = fs::read_dir(&image_sets_dir)
+        .map_err(Error::io("Failed to read", &image_sets_dir))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            (!name.contains('_')).then_some((name, path))
+        })
+        .collect();
+    splits.sort();
+    Ok(splits)
+}
+
+impl DataSource for VocSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // Address every image by its file in JPEGImages, leaving the label empty since an image
+        // holds any number of objects
+        let (dataset_dir, ids) = self.image_ids()?;
+        let images_dir = dataset_dir.join(IMAGES_DIR);
+        let samples = ids
+            .into_iter()
+            .map(|id| PathBuf::from(format!("{}.jpg", id)))
+            .filter(|file_name| self.filter.matches(file_name))
+            .map(|file_name| (images_dir.join(file_name), String::new()))
+            .inspect(|_| scan_progress.inc(1))
+            .collect();
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read the annotation of the image, next to the images folder, then the image itself
+        let (path, label) = sample;
+        let dataset_dir = path
+            .parent()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new(""));
+        let annotation_path = dataset_dir
+            .join(ANNOTATIONS_DIR)
+            .join(path.with_extension("xml").file_name().unwrap_or_default());
+        let metadata = read_annotation(&annotation_path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Failed to read annotation {:?}: {}", annotation_path, e),
+            )
+        })?;
+        Ok(Record {
+            bytes: read_image_as_bytes(path)?,
+            label: label.clone(),
+            metadata,
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Read the image size from the file metadata without opening the file
+        fs::metadata(&sample.0).map(|m| m.len()).unwrap_or(0)
+    }
+
+    fn metadata_columns(&self) -> BTreeMap<String, ColumnType> {
+        BTreeMap::from([
+            ("width".to_string(), ColumnType::Int64),
+            ("height".to_string(), ColumnType::Int64),
+            ("bboxes".to_string(), ColumnType::BboxList),
+            ("categories".to_string(), ColumnType::Utf8List),
+            ("difficult".to_string(), ColumnType::Int64List),
+        ])
+    }
+}
@@ -0,0 +1,182 @@
+use super::tar_archive::{read_member, MemberLocation};
+use super::{find_input_files, DataSource, Record, SampleRef};
+use crate::cli::InputFormat;
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use indicatif::ProgressBar;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tar::{Archive, EntryType};
+
+// Define the members of a shard sharing the same key, which together form one sample
+#[derive(Default)]
+struct ShardSample {
+    image: Option<(PathBuf, MemberLocation)>,
+    class: Option<String>,
+    json_label: Option<String>,
+}
+
+// Define a source reading WebDataset shards, tar archives where the members of a sample share a
+// key, such as "000123.jpg" with its label in "000123.cls" or in the "label" field of "000123.json"
+pub struct WebDatasetSource {
+    root: PathBuf,
+    filter: PathFilter,
+    shards: Mutex<Vec<PathBuf>>,
+    images: Mutex<HashMap<PathBuf, MemberLocation>>,
+}
+
+impl WebDatasetSource {
+    // Function to create a source over a shard or a folder of shards, keeping the samples whose
+    // image member is accepted by the filter
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        WebDatasetSource {
+            root: root.into(),
+            filter,
+            shards: Mutex::new(Vec::new()),
+            images: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Function to list the samples of a shard in the order their first member appears, reading the
+    // small label members while walking the shard
+    fn list_shard(
+        &self,
+        index: usize,
+        shard_path: &Path,
+        scan_progress: &ProgressBar,
+    ) -> io::Result<Vec<(SampleRef, MemberLocation)>> {
+        // Group the members of the shard by key, remembering the order of the keys
+        let mut archive = Archive::new(BufReader::new(File::open(shard_path)?));
+        let mut keys = Vec::new();
+        let mut samples: HashMap<PathBuf, ShardSample> = HashMap::new();
+        for entry in archive.entries_with_seek()? {
+            // Skip directories, links and other entries holding no file data
+            let mut entry = entry?;
+            if entry.header().entry_type() != EntryType::Regular {
+                continue;
+            }
+
+            // Find the key of the member and the extension telling what it holds
+            let member_path = entry.path()?.into_owned();
+            let Some((key, extension)) = split_key(&member_path) else {
+                continue;
+            };
+            let sample = samples.entry(key.clone()).or_insert_with(|| {
+                keys.push(key);
+                ShardSample::default()
+            });
+
+            // Record where the image is, and read the label members right away
+            match extension.to_ascii_lowercase().as_str() {
+                "cls" => {
+                    let mut class = String::new();
+                    entry.read_to_string(&mut class)?;
+                    sample.class = Some(class.trim().to_string());
+                }
+                "json" => {
+                    let mut json = Vec::new();
+                    entry.read_to_end(&mut json)?;
+                    sample.json_label = json_label(&json);
+                }
+                _ if sample.image.is_none() && self.filter.matches(&member_path) => {
+                    let location = MemberLocation {
+                        archive: index,
+                        offset: entry.raw_file_position(),
+                        size: entry.size(),
+                    };
+                    sample.image = Some((member_path, location));
+                }
+                _ => {}
+            }
+        }
+
+        // Emit the keys holding an image, labelled by their class member, else their JSON member
+        let mut listed = Vec::new();
+        for key in keys {
+            let sample = samples.remove(&key).unwrap_or_default();
+            let Some((member_path, location)) = sample.image else {
+                continue;
+            };
+            let label = sample.class.or(sample.json_label).unwrap_or_default();
+            listed.push(((shard_path.join(member_path), label), location));
+            scan_progress.inc(1);
+        }
+        Ok(listed)
+    }
+
+    // Function to get the location of the image member of a listed sample
+    fn location(&self, path: &Path) -> io::Result<MemberLocation> {
+        self.images
+            .lock()
+            .unwrap()
+            .get(path)
+            .copied()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Sample was not listed"))
+    }
+}
+
+// Function to split the path of a member into the key of its sample, which is the path up to the
+// first dot of the file name, and the extension after the last dot
+fn split_key(member_path: &Path) -> Option<(PathBuf, String)> {
+    let file_name = member_path.file_name()?.to_str()?;
+    let (stem, _) = file_name.split_once('.')?;
+    let (_, extension) = file_name.rsplit_once('.')?;
+    Some((member_path.with_file_name(stem), extension.to_string()))
+}
+
+// Function to read the "label" field of a JSON member, which may be a string or a number
+fn json_label(json: &[u8]) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(json).ok()?;
+    match value.get("label")? {
+        serde_json::Value::String(label) => Some(label.clone()),
+        serde_json::Value::Number(label) => Some(label.to_string()),
+        _ => None,
+    }
+}
+
+impl DataSource for WebDatasetSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // Find the shards and remember them so images can be read back by index
+        let shards = find_input_files(InputFormat::Webdataset, &self.root);
+        let mut images = HashMap::new();
+        let mut samples = Vec::new();
+
+        // List the samples of every shard in order
+        for (index, shard_path) in shards.iter().enumerate() {
+            let listed = self
+                .list_shard(index, shard_path, scan_progress)
+                .map_err(Error::io("Failed to read", shard_path))?;
+            for (sample, location) in listed {
+                images.insert(sample.0.clone(), location);
+                samples.push(sample);
+            }
+        }
+
+        // Keep the index of the shards and images for loading
+        *self.shards.lock().unwrap() = shards;
+        *self.images.lock().unwrap() = images;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Read the image member directly from its shard
+        let (path, label) = sample;
+        let location = self.location(path)?;
+        let shard_path = self.shards.lock().unwrap()[location.archive].clone();
+
+        // Return the image with the label found while listing
+        Ok(Record {
+            bytes: read_member(&shard_path, location)?,
+            label: label.clone(),
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Use the member size recorded in its header
+        self.location(&sample.0).map_or(0, |location| location.size)
+    }
+}
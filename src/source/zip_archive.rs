@@ -0,0 +1,184 @@
+use super::{find_input_files, DataSource, Record, SampleRef};
+use crate::cli::InputFormat;
+use crate::error::{Error, Result};
+use crate::filter::PathFilter;
+use indicatif::ProgressBar;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use zip::ZipArchive;
+
+// Define an opened archive whose central directory was already parsed
+type ZipReader = ZipArchive<BufReader<File>>;
+
+// Define where an entry is stored and how large it is once decompressed
+#[derive(Clone, Copy)]
+struct EntryLocation {
+    archive: usize,
+    index: usize,
+    size: u64,
+}
+
+// Define a source reading images straight from zip archives without extracting them, either a
+// single archive or every archive under a folder, such as a Kaggle download
+pub struct ZipSource {
+    root: PathBuf,
+    filter: PathFilter,
+    archives: Mutex<Vec<PathBuf>>,
+    entries: Mutex<HashMap<PathBuf, EntryLocation>>,
+    // Opened archives not used by any worker, kept per archive so loading an entry does not parse
+    // the central directory again
+    idle_readers: Mutex<HashMap<usize, Vec<ZipReader>>>,
+}
+
+impl ZipSource {
+    // Function to create a source over a zip archive or a folder of zip archives, keeping the
+    // entries accepted by the filter
+    pub fn new(root: impl Into<PathBuf>, filter: PathFilter) -> Self {
+        ZipSource {
+            root: root.into(),
+            filter,
+            archives: Mutex::new(Vec::new()),
+            entries: Mutex::new(HashMap::new()),
+            idle_readers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Function to list the file entries of an archive accepted by the filter, in the order of its
+    // central directory
+    fn list_archive(
+        &self,
+        index: usize,
+        archive_path: &Path,
+        scan_progress: &ProgressBar,
+    ) -> io::Result<Vec<(SampleRef, EntryLocation)>> {
+        // Label entries stored at the top of the archive after the archive itself
+        let archive_label = archive_path
+            .file_stem()
+            .map_or(String::new(), |stem| stem.to_string_lossy().to_string());
+
+        // Read the central directory, which lists every entry without decompressing any
+        let mut archive = open_archive(archive_path)?;
+        let mut entries = Vec::new();
+        for entry_index in 0..archive.len() {
+            // Skip directories and entries whose name would escape the archive
+            let entry = archive.by_index_raw(entry_index)?;
+            let Some(entry_path) = entry.enclosed_name().filter(|_| entry.is_file()) else {
+                continue;
+            };
+
+            // Keep the entries with an accepted extension that pass the include and exclude patterns
+            if !self.filter.matches(&entry_path) {
+                continue;
+            }
+
+            // Take the label from the folder holding the entry, like in an image folder
+            let label = entry_path
+                .parent()
+                .and_then(|parent| parent.file_name())
+                .map_or(archive_label.clone(), |name| {
+                    name.to_string_lossy().to_string()
+                });
+
+            // Address the entry by the archive path followed by its path inside the archive
+            let location = EntryLocation {
+                archive: index,
+                index: entry_index,
+                size: entry.size(),
+            };
+            entries.push(((archive_path.join(&entry_path), label), location));
+            scan_progress.inc(1);
+        }
+
+        // Keep the opened archive for the first worker loading one of its entries
+        self.idle_readers
+            .lock()
+            .unwrap()
+            .entry(index)
+            .or_default()
+            .push(archive);
+        Ok(entries)
+    }
+
+    // Function to get the location of a listed entry
+    fn location(&self, path: &Path) -> io::Result<EntryLocation> {
+        self.entries
+            .lock()
+            .unwrap()
+            .get(path)
+            .copied()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Entry was not listed"))
+    }
+}
+
+// Function to open an archive and parse its central directory
+fn open_archive(path: &Path) -> io::Result<ZipReader> {
+    Ok(ZipArchive::new(BufReader::new(File::open(path)?))?)
+}
+
+impl DataSource for ZipSource {
+    fn list(&self, scan_progress: &ProgressBar) -> Result<Vec<SampleRef>> {
+        // Find the archives and remember them so entries can be read back by index
+        let archives = find_input_files(InputFormat::Zip, &self.root);
+        let mut entries = HashMap::new();
+        let mut samples = Vec::new();
+
+        // List the entries of every archive in order
+        for (index, archive_path) in archives.iter().enumerate() {
+            let listed = self
+                .list_archive(index, archive_path, scan_progress)
+                .map_err(Error::io("Failed to read", archive_path))?;
+            for (sample, location) in listed {
+                entries.insert(sample.0.clone(), location);
+                samples.push(sample);
+            }
+        }
+
+        // Keep the index of the archives and entries for loading
+        *self.archives.lock().unwrap() = archives;
+        *self.entries.lock().unwrap() = entries;
+        Ok(samples)
+    }
+
+    fn load(&self, sample: &SampleRef) -> io::Result<Record> {
+        // Take an idle reader of the archive holding the entry, or open another one
+        let (path, label) = sample;
+        let location = self.location(path)?;
+        let idle = self
+            .idle_readers
+            .lock()
+            .unwrap()
+            .get_mut(&location.archive)
+            .and_then(Vec::pop);
+        let mut archive = match idle {
+            Some(archive) => archive,
+            None => open_archive(&self.archives.lock().unwrap()[location.archive])?,
+        };
+
+        // Decompress the entry, which also checks its checksum
+        let mut bytes = Vec::with_capacity(location.size as usize);
+        archive.by_index(location.index)?.read_to_end(&mut bytes)?;
+
+        // Hand the reader back for the next entry of the same archive
+        self.idle_readers
+            .lock()
+            .unwrap()
+            .entry(location.archive)
+            .or_default()
+            .push(archive);
+
+        // Return the entry with the label found while listing
+        Ok(Record {
+            bytes,
+            label: label.clone(),
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    fn size_hint(&self, sample: &SampleRef) -> u64 {
+        // Use the decompressed size recorded in the central directory
+        self.location(&sample.0).map_or(0, |location| location.size)
+    }
+}
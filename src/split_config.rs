@@ -0,0 +1,61 @@
+use crate::error::{Error, Result};
+use crate::metadata::Attribution;
+use crate::schema::SchemaSettings;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+// Define the settings a config file can override for a single split
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SplitOverrides {
+    pub chunk_size: Option<NonZeroUsize>,
+    pub shuffle: Option<bool>,
+    #[serde(default)]
+    pub constant_columns: BTreeMap<String, String>,
+}
+
+// Define a struct describing a whole split config file
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SplitConfig {
+    pub description: Option<String>,
+    pub citation: Option<String>,
+    pub homepage: Option<String>,
+    pub license: Option<String>,
+    pub schema: Option<SchemaSettings>,
+    #[serde(default)]
+    pub splits: BTreeMap<String, SplitOverrides>,
+}
+
+impl SplitConfig {
+    // Function to get the attribution of the dataset recorded in the dataset info of every split
+    pub fn attribution(&self) -> Attribution {
+        Attribution {
+            description: self.description.clone(),
+            citation: self.citation.clone(),
+            homepage: self.homepage.clone(),
+            license: self.license.clone(),
+        }
+    }
+
+    // Function to get the overrides of a split, which are empty when the config does not list it
+    pub fn overrides(&self, split: &str) -> SplitOverrides {
+        self.splits.get(split).cloned().unwrap_or_default()
+    }
+}
+
+// Function to read a split config file in JSON or TOML format depending on its extension
+pub fn read_split_config(config_path: &Path) -> Result<SplitConfig> {
+    // Read the whole config file into a string
+    let content =
+        fs::read_to_string(config_path).map_err(Error::io("Failed to read", config_path))?;
+
+    // Parse the content as TOML or JSON according to the file extension
+    match config_path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&content).map_err(Error::toml(config_path)),
+        _ => serde_json::from_str(&content).map_err(Error::json(config_path)),
+    }
+}
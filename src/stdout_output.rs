@@ -0,0 +1,120 @@
+use crate::error::{Error, Result};
+use crate::metadata::DatasetInfo;
+use crate::profile::{Compression, OutputSettings};
+use crate::single_file::DATASET_INFO_KEY;
+use crate::sink;
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use std::collections::BTreeMap;
+use std::io::{self, BufWriter, Stdout, Write};
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex};
+
+// Define the output path selecting stdout instead of a directory
+pub const STDOUT_OUTPUT: &str = "-";
+
+// Function to check whether the given output path selects stdout
+pub fn is_stdout_output(path: &Path) -> bool {
+    path == Path::new(STDOUT_OUTPUT)
+}
+
+// Define the chunks streamed so far, with the index of the next chunk to write, the record
+// batches of the chunks finished ahead of it and whether a chunk was abandoned, after which no
+// other chunk can be written in order
+struct StreamState {
+    writer: StreamWriter<BufWriter<Stdout>>,
+    next_chunk: usize,
+    pending: BTreeMap<usize, RecordBatch>,
+    abandoned: bool,
+}
+
+// Define the Arrow IPC stream a split is written to on stdout, shared by the workers converting
+// its chunks, which appends the record batch of every chunk in chunk order as soon as the chunks
+// before it are written, so no chunk file is ever written to disk
+pub struct SplitStream {
+    schema: SchemaRef,
+    state: Mutex<StreamState>,
+    written: Condvar,
+}
+
+// Function to map an error writing to stdout, such as a closed pipe, to an I/O error
+fn stdout_error(e: io::Error) -> Error {
+    Error::io("Failed to write", Path::new(STDOUT_OUTPUT))(e)
+}
+
+impl SplitStream {
+    // Function to start the stream on stdout with the schema of the chunks and the dataset info of
+    // the split embedded in its metadata, compressing the buffers like the chunk files when the
+    // IPC format supports their codec
+    pub fn new(schema: &Schema, info: &DatasetInfo, output: &OutputSettings) -> Result<Self> {
+        let mut metadata = schema.metadata().clone();
+        metadata.insert(
+            DATASET_INFO_KEY.to_string(),
+            serde_json::to_string(info).expect("Failed to serialize metadata"),
+        );
+        let schema = Arc::new(Schema::new_with_metadata(schema.fields().clone(), metadata));
+        let compression = match output.compression {
+            Compression::Snappy => Compression::None,
+            compression => compression,
+        };
+        let options = sink::ipc_write_options(compression)?;
+        let writer =
+            StreamWriter::try_new_with_options(BufWriter::new(io::stdout()), &schema, options)?;
+        Ok(SplitStream {
+            schema,
+            state: Mutex::new(StreamState {
+                writer,
+                next_chunk: 0,
+                pending: BTreeMap::new(),
+                abandoned: false,
+            }),
+            written: Condvar::new(),
+        })
+    }
+
+    // Function to add the record batch of a finished chunk, writing it and the chunks waiting for
+    // it to stdout once every chunk before it is written, or keeping it until then
+    pub fn write_chunk(&self, chunk: usize, batch: RecordBatch) -> Result<()> {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        state.pending.insert(chunk, batch);
+        while let Some(batch) = state.pending.remove(&state.next_chunk) {
+            state
+                .writer
+                .write(&batch.with_schema(Arc::clone(&self.schema))?)?;
+            state.next_chunk += 1;
+        }
+        self.written.notify_all();
+        state.writer.get_mut().flush().map_err(stdout_error)
+    }
+
+    // Function to wait until the given chunk is less than the given number of chunks ahead of the
+    // next one to write, bounding the finished chunks kept in memory while an earlier one is still
+    // being converted, or until a chunk was abandoned
+    pub fn wait_for_room(&self, chunk: usize, max_ahead: usize) {
+        let state = self.state.lock().unwrap();
+        let _state = self
+            .written
+            .wait_while(state, |state| {
+                !state.abandoned && chunk >= state.next_chunk + max_ahead
+            })
+            .unwrap();
+    }
+
+    // Function to give up on a chunk that failed or was abandoned after cancellation, waking the
+    // conversion waiting for room since the chunks after it can no longer be written in order
+    pub fn abandon_chunk(&self) {
+        self.state.lock().unwrap().abandoned = true;
+        self.written.notify_all();
+    }
+
+    // Function to end the stream after the chunks written so far, dropping the chunks finished
+    // after one that was abandoned when the conversion was cancelled
+    pub fn finish(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.pending.clear();
+        state.writer.finish()?;
+        state.writer.get_mut().flush().map_err(stdout_error)
+    }
+}
@@ -0,0 +1,40 @@
+use crate::computed::{per_sample, ComputedColumn, ComputedSample};
+use crate::error::{Error, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+// Define the name of the column holding the human-readable class name of every sample
+pub const CLASS_NAME_COLUMN: &str = "class_name";
+
+// Function to read a mapping from WordNet IDs to class names, either a text file with one
+// "n01440764 tench, Tinca tinca" line per class, separated by a space or a tab as in
+// LOC_synset_mapping.txt and words.txt, or a JSON file shaped like imagenet_class_index.json
+pub fn read_synset_map(path: &Path) -> Result<HashMap<String, String>> {
+    // Read the whole mapping file
+    let content = fs::read_to_string(path).map_err(Error::io("Failed to read", path))?;
+
+    // Parse the JSON index, mapping class indices to a [wnid, name] pair
+    if path.extension().is_some_and(|ext| ext == "json") {
+        let index: BTreeMap<String, (String, String)> =
+            serde_json::from_str(&content).map_err(Error::json(path))?;
+        return Ok(index.into_values().collect());
+    }
+
+    // Otherwise split every non-empty line at its first whitespace
+    Ok(content
+        .lines()
+        .filter_map(|line| {
+            let (wnid, name) = line.trim().split_once(char::is_whitespace)?;
+            Some((wnid.to_string(), name.trim().to_string()))
+        })
+        .collect())
+}
+
+// Function to define the "class_name" column holding the class name mapped from the label of
+// every sample, or null when the label is not a known WordNet ID
+pub fn class_name_column(synsets: HashMap<String, String>) -> impl ComputedColumn {
+    per_sample(CLASS_NAME_COLUMN, move |sample: &ComputedSample<'_>| {
+        synsets.get(&sample.record.label).cloned()
+    })
+}
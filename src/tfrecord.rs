@@ -0,0 +1,128 @@
+use prost::{Message, Oneof};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+// Define the tf.Example message of TensorFlow, a map from feature names to lists of values
+#[derive(Clone, PartialEq, Message)]
+pub struct Example {
+    #[prost(message, optional, tag = "1")]
+    pub features: Option<Features>,
+}
+
+// Define the named features of an Example
+#[derive(Clone, PartialEq, Message)]
+pub struct Features {
+    #[prost(map = "string, message", tag = "1")]
+    pub feature: HashMap<String, Feature>,
+}
+
+// Define a feature of an Example, holding a list of byte strings, floats or integers
+#[derive(Clone, PartialEq, Message)]
+pub struct Feature {
+    #[prost(oneof = "Kind", tags = "1, 2, 3")]
+    pub kind: Option<Kind>,
+}
+
+// Define the lists of values a feature can hold
+#[derive(Clone, PartialEq, Oneof)]
+pub enum Kind {
+    #[prost(message, tag = "1")]
+    Bytes(BytesList),
+    #[prost(message, tag = "2")]
+    Float(FloatList),
+    #[prost(message, tag = "3")]
+    Int64(Int64List),
+}
+
+// Define a list of byte strings
+#[derive(Clone, PartialEq, Message)]
+pub struct BytesList {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub value: Vec<Vec<u8>>,
+}
+
+// Define a list of floats
+#[derive(Clone, PartialEq, Message)]
+pub struct FloatList {
+    #[prost(float, repeated, tag = "1")]
+    pub value: Vec<f32>,
+}
+
+// Define a list of integers
+#[derive(Clone, PartialEq, Message)]
+pub struct Int64List {
+    #[prost(int64, repeated, tag = "1")]
+    pub value: Vec<i64>,
+}
+
+// Define the reversed Castagnoli polynomial of the CRC-32C checksums of TFRecord files
+const CRC32C_POLYNOMIAL: u32 = 0x82f6_3b78;
+
+// Define the constant added to the rotated checksums stored in TFRecord files
+const CRC_MASK_DELTA: u32 = 0xa282_ead8;
+
+// Function to compute the CRC-32C checksum of some bytes
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (CRC32C_POLYNOMIAL & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+// Function to compute the masked CRC-32C checksum stored after the length and the data of every
+// TFRecord record
+pub fn masked_crc32c(data: &[u8]) -> u32 {
+    crc32c(data).rotate_right(15).wrapping_add(CRC_MASK_DELTA)
+}
+
+// Function to read the next record of a TFRecord stream, checking both of its checksums, or None at
+// the end of the stream
+pub fn read_record(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    // Read the length and its checksum, stopping cleanly at the end of the stream
+    let mut header = [0u8; 12];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let (length, length_crc) = header.split_at(8);
+    if masked_crc32c(length) != u32::from_le_bytes(length_crc.try_into().unwrap()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Corrupted TFRecord length",
+        ));
+    }
+
+    // Read the data and check its checksum
+    let length = u64::from_le_bytes(length.try_into().unwrap());
+    let mut data = Vec::new();
+    reader.by_ref().take(length).read_to_end(&mut data)?;
+    let mut data_crc = [0u8; 4];
+    reader.read_exact(&mut data_crc)?;
+    if data.len() as u64 != length || masked_crc32c(&data) != u32::from_le_bytes(data_crc) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Corrupted TFRecord data",
+        ));
+    }
+    Ok(Some(data))
+}
+
+// Function to write a record to a TFRecord stream, framed by its length and both checksums
+pub fn write_record(writer: &mut impl Write, data: &[u8]) -> io::Result<()> {
+    let length = (data.len() as u64).to_le_bytes();
+    writer.write_all(&length)?;
+    writer.write_all(&masked_crc32c(&length).to_le_bytes())?;
+    writer.write_all(data)?;
+    writer.write_all(&masked_crc32c(data).to_le_bytes())
+}
+
+// Function to get the size a record of the given length takes in a TFRecord file, with its length,
+// data and checksums
+pub fn framed_len(length: usize) -> u64 {
+    8 + 4 + length as u64 + 4
+}
@@ -0,0 +1,26 @@
+use crate::error::Result;
+use crate::source::Record;
+
+// Define whether a transformed sample is written to the output or dropped
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Keep {
+    Yes,
+    No,
+}
+
+// Define a transform run on every sample between loading it and adding it to the record batch,
+// able to rewrite its bytes, label and metadata or drop it; an error skips the sample and records
+// it in the error report like a sample that cannot be read
+pub trait Transform: Send + Sync {
+    // Function to transform a sample in place and decide whether to keep it
+    fn apply(&self, record: &mut Record) -> Result<Keep>;
+}
+
+impl<F> Transform for F
+where
+    F: Fn(&mut Record) -> Result<Keep> + Send + Sync,
+{
+    fn apply(&self, record: &mut Record) -> Result<Keep> {
+        self(record)
+    }
+}
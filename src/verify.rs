@@ -0,0 +1,162 @@
+use crate::chunk_io::{
+    count_chunk_rows, read_chunk_batches, read_chunk_schema, ChunkBatches, ChunkFormat,
+};
+use crate::dataset::{ArrowDataset, DatasetSplit};
+use crate::error::{Error, Result};
+use crate::profile::{IpcFormat, OutputSettings};
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::ipc::reader::{FileReader, StreamReader};
+use log::info;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+// Function to verify every split of a converted dataset, returning true when all checks pass and
+// an error only when the dataset metadata cannot be read
+pub fn verify_dataset(path: &Path) -> Result<bool> {
+    // Open the dataset to locate and read the splits under the given path
+    let dataset = ArrowDataset::open(path)?;
+
+    // Fail when no split could be found
+    if dataset.splits().is_empty() {
+        println!("No converted splits found in {:?}", path);
+        return Ok(false);
+    }
+
+    // Track whether every split passed verification
+    let mut all_ok = true;
+
+    // Verify each split in turn
+    for split in dataset.splits() {
+        // Count the rows across all chunks of the split
+        let chunk_paths = split.chunk_paths();
+        let mut total_rows = 0;
+        for chunk_path in &chunk_paths {
+            // Read the chunk and report any failure
+            match count_chunk_rows(chunk_path) {
+                Ok(rows) => total_rows += rows,
+                Err(e) => {
+                    println!("  FAILED {:?}: {}", chunk_path, e);
+                    all_ok = false;
+                }
+            }
+        }
+
+        // Compare the row count with the number of samples recorded in the metadata
+        if total_rows == split.num_samples() {
+            println!(
+                "Split {:?}: {} chunks, {} rows OK",
+                split.dir(),
+                chunk_paths.len(),
+                total_rows
+            );
+        } else {
+            println!(
+                "Split {:?}: expected {} samples but found {} rows",
+                split.dir(),
+                split.num_samples(),
+                total_rows
+            );
+            all_ok = false;
+        }
+    }
+
+    // Return the overall verification result
+    Ok(all_ok)
+}
+
+// Function to build the error of a written chunk that failed verification
+fn verification_error(path: &Path, reason: impl ToString) -> Error {
+    Error::Verification {
+        path: path.to_path_buf(),
+        reason: reason.to_string(),
+    }
+}
+
+// Function to open a written chunk with the reader of the layout it was written in, so chunks
+// truncated or written in another layout fail to open, returning its schema and record batches
+fn open_written_chunk(path: &Path, output: &OutputSettings) -> Result<(SchemaRef, ChunkBatches)> {
+    let file = File::open(path).map_err(Error::io("Failed to open", path))?;
+    match (output.format, output.ipc_format) {
+        (ChunkFormat::Arrow, IpcFormat::File) => {
+            let reader = FileReader::try_new(file, None)?;
+            Ok((reader.schema(), Box::new(reader)))
+        }
+        (ChunkFormat::Arrow, IpcFormat::Stream) => {
+            let reader = StreamReader::try_new(BufReader::new(file), None)?;
+            Ok((reader.schema(), Box::new(reader)))
+        }
+        (ChunkFormat::Parquet, _) => Ok((read_chunk_schema(path)?, read_chunk_batches(path)?)),
+    }
+}
+
+// Function to check a freshly written split before it ships, re-opening every chunk listed in its
+// state.json with the Arrow readers and failing unless each one decodes to the end, has the
+// columns of the given schema and holds the rows index.json records for it; the chunks before the
+// given index were written by an earlier run, possibly in the other IPC layout, so their layout is
+// detected rather than required
+pub fn verify_split_output(
+    split_dir: &Path,
+    schema: &Schema,
+    output: &OutputSettings,
+    first_new_chunk: usize,
+) -> Result<()> {
+    // Read the chunks and their row counts recorded in the metadata of the split
+    let split = DatasetSplit::open(split_dir)?;
+    let chunk_paths = split.chunk_paths();
+    let chunk_rows = split.chunk_rows()?;
+
+    // Decode every chunk, comparing its columns and rows with the recorded ones
+    let mut total_rows = 0;
+    for (i, (chunk_path, &expected_rows)) in chunk_paths.iter().zip(&chunk_rows).enumerate() {
+        let opened = if i < first_new_chunk {
+            read_chunk_schema(chunk_path)
+                .and_then(|schema| Ok((schema, read_chunk_batches(chunk_path)?)))
+        } else {
+            open_written_chunk(chunk_path, output)
+        };
+        let (chunk_schema, batches) =
+            opened.map_err(|e| verification_error(chunk_path, format!("unreadable ({})", e)))?;
+        if chunk_schema.fields() != schema.fields() {
+            return Err(verification_error(
+                chunk_path,
+                "its columns differ from the output schema",
+            ));
+        }
+        let mut rows = 0;
+        for batch in batches {
+            rows += batch
+                .map_err(|e| verification_error(chunk_path, format!("unreadable ({})", e)))?
+                .num_rows();
+        }
+        if rows != expected_rows {
+            return Err(verification_error(
+                chunk_path,
+                format!(
+                    "it holds {} rows but index.json records {}",
+                    rows, expected_rows
+                ),
+            ));
+        }
+        total_rows += rows;
+    }
+
+    // Compare the rows of all chunks with the number of samples of the split
+    if total_rows != split.num_samples() {
+        return Err(verification_error(
+            split_dir,
+            format!(
+                "its chunks hold {} rows but dataset_info.json records {} samples",
+                total_rows,
+                split.num_samples()
+            ),
+        ));
+    }
+    info!(
+        "Verified {} chunks of {} rows in {:?}",
+        chunk_paths.len(),
+        total_rows,
+        split_dir
+    );
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use crate::cancel::CancellationToken;
+use crate::cli::ConvertArgs;
+use crate::error::{Error, Result};
+use crate::source::{self, SampleRef, SourceSettings};
+use crate::split_config::SplitConfig;
+use crate::{output, report, save_to_chunked_arrow, ChunkSettings, SplitJob};
+use indicatif::ProgressBar;
+use log::info;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{rng, Rng, SeedableRng};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+// Define how long the input must stay quiet before new files are converted
+const SETTLE_DELAY: Duration = Duration::from_secs(2);
+
+// Define how often the cancellation token is checked while waiting for changes
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+// Define a struct holding an active filesystem watcher and the channel receiving its events
+pub struct SplitWatcher {
+    // Keep the watcher alive for as long as events are needed
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+}
+
+// Function to start watching the input folder of every split for changes
+pub fn start_watcher(splits: &[(String, PathBuf)]) -> Result<SplitWatcher> {
+    // Create a channel receiving the filesystem events
+    let (tx, rx) = mpsc::channel();
+
+    // Create the watcher best suited to the current platform
+    let mut watcher = notify::recommended_watcher(tx)?;
+
+    // Watch each split folder recursively so new files in class folders are noticed
+    for (_, split_path) in splits {
+        watcher.watch(split_path, RecursiveMode::Recursive)?;
+    }
+
+    // Return the watcher together with its event receiver
+    Ok(SplitWatcher {
+        _watcher: watcher,
+        events: rx,
+    })
+}
+
+// Function to check whether an event may mean new images, ignoring the accesses caused by the
+// converter reading and rescanning the input
+fn is_change(event: &notify::Result<Event>) -> bool {
+    !matches!(event, Ok(event) if event.kind.is_access())
+}
+
+// Function to block until files changed and then stayed unchanged for the settle delay, returning
+// false instead when the token is cancelled first
+fn wait_for_changes(
+    events: &Receiver<notify::Result<Event>>,
+    cancel: &CancellationToken,
+) -> Result<bool> {
+    // Wait for the first change, treating watcher errors such as overflows as changes too, and
+    // checking the token regularly
+    loop {
+        match events.recv_timeout(CANCEL_POLL_INTERVAL) {
+            Ok(event) if is_change(&event) => break,
+            Ok(_) => continue,
+            Err(RecvTimeoutError::Timeout) if cancel.is_cancelled() => return Ok(false),
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return Err(Error::WatcherStopped),
+        }
+    }
+
+    // Keep draining events until the input has been quiet for the settle delay
+    let mut quiet_since = Instant::now();
+    loop {
+        // Stop waiting as soon as the token is cancelled
+        if cancel.is_cancelled() {
+            return Ok(false);
+        }
+
+        // Return once no change arrived for the whole settle delay
+        if quiet_since.elapsed() >= SETTLE_DELAY {
+            return Ok(true);
+        }
+
+        match events.recv_timeout(CANCEL_POLL_INTERVAL) {
+            Ok(event) if is_change(&event) => quiet_since = Instant::now(),
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Err(Error::WatcherStopped),
+        }
+    }
+}
+
+// Function to append new images as extra chunks whenever they appear in the watched splits, until
+// the conversion is cancelled
+pub fn watch_and_append(
+    watcher: SplitWatcher,
+    args: &ConvertArgs,
+    settings: &ChunkSettings,
+    split_config: &SplitConfig,
+    source_settings: &SourceSettings,
+    splits: &[(String, PathBuf)],
+    mut known_paths: Vec<HashSet<PathBuf>>,
+) -> Result<()> {
+    // Print a message indicating the watch has started
+    info!("Watching {} splits for new images...", splits.len());
+
+    // Convert new images each time the input changes, until cancelled
+    loop {
+        // Wait until new files have finished arriving, stopping once cancelled
+        if !wait_for_changes(&watcher.events, &settings.cancel)? {
+            info!("Stopped watching for new images");
+            return Ok(());
+        }
+
+        // Look for new images in each split
+        for ((split, split_path), known) in splits.iter().zip(known_paths.iter_mut()) {
+            // Rescan the split quietly and keep only the images not converted yet
+            let source = source::open_source(args.input_format, split_path, source_settings)?;
+            let mut new_paths: Vec<SampleRef> = source
+                .list(&ProgressBar::hidden())?
+                .into_iter()
+                .filter(|(path, _)| !known.contains(path))
+                .collect();
+
+            // Skip the split when nothing new appeared
+            if new_paths.is_empty() {
+                continue;
+            }
+
+            // Print a message indicating new images were found
+            info!("Found {} new images in {} dataset", new_paths.len(), split);
+
+            // Remember the new images so they are only converted once
+            known.extend(new_paths.iter().map(|(path, _)| path.clone()));
+
+            // Shuffle the new images among themselves unless the config keeps the split sorted
+            let overrides = split_config.overrides(split);
+            let shuffle = overrides.shuffle.unwrap_or(true);
+            let seed = rng().random();
+            if shuffle {
+                new_paths.shuffle(&mut StdRng::seed_from_u64(seed));
+            }
+
+            // Append the new images as extra chunks after the existing ones
+            let split_output = args.output.join(split);
+            let existing = output::read_existing_chunks(&split_output)?;
+            let job = SplitJob {
+                source,
+                samples: new_paths,
+                output_dir: split_output.clone(),
+                dataset_name: format!("{}-{}", args.name, split),
+            };
+            save_to_chunked_arrow(
+                job,
+                &settings.for_split(&overrides),
+                shuffle.then_some(seed),
+                false,
+                existing,
+            )?;
+        }
+
+        // Refresh the report of samples that failed to convert
+        report::write_error_report(&args.output, &settings.errors)?;
+    }
+}
@@ -0,0 +1,28 @@
+use crate::cli::ConvertArgs;
+use crate::error::{Error, Result};
+use crate::source::SourceSettings;
+use crate::split_config::SplitConfig;
+use crate::ChunkSettings;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+// Define the watcher of watch mode, which cannot be created when the "watch" feature is disabled
+pub enum SplitWatcher {}
+
+// Function to report that watch mode is unavailable in this build
+pub fn start_watcher(_splits: &[(String, PathBuf)]) -> Result<SplitWatcher> {
+    Err(Error::FeatureDisabled("Watch mode", "watch"))
+}
+
+// Function standing in for the watch loop, which is never reached since no watcher exists
+pub fn watch_and_append(
+    watcher: SplitWatcher,
+    _args: &ConvertArgs,
+    _settings: &ChunkSettings,
+    _split_config: &SplitConfig,
+    _source_settings: &SourceSettings,
+    _splits: &[(String, PathBuf)],
+    _known_paths: Vec<HashSet<PathBuf>>,
+) -> Result<()> {
+    match watcher {}
+}